@@ -0,0 +1,78 @@
+#![allow(clippy::needless_return)]
+
+// searches the magic numbers of the sliding attack tables, natively because the same search in
+// const eval takes minutes
+use std::{env, fmt::Write, fs, path::Path};
+
+#[path = "src/core/tables/rays.rs"]
+#[allow(unused)]
+mod rays;
+
+use rays::{BISHOP_DIRECTIONS, BitBoard, ROOK_DIRECTIONS, relevant_mask, slider_attacks};
+
+// stockfish's seeds per rank, none of the squares needs more than 30000 candidates with them
+const MAGIC_SEEDS: [u64; 8] = [728, 10316, 55013, 32803, 12281, 15100, 16645, 255];
+
+// xorshift64star, the generator stockfish searches its magics with
+fn next_random(state: &mut u64) -> u64 {
+    *state ^= *state >> 12;
+    *state ^= *state << 25;
+    *state ^= *state >> 27;
+    return state.wrapping_mul(2685821657736338717);
+}
+// tries sparse random numbers until one sends every subset of the mask to an index that no
+// subset with different attacks uses, a try is marked in epoch so the table is never cleared
+fn find_magic(sq: usize, directions: &[(i32, i32); 4]) -> BitBoard {
+    let mask = relevant_mask(sq, directions);
+    let shift = 64 - mask.count_ones();
+    let mut occupancy = vec![];
+    let mut reference = vec![];
+    let mut blockers: BitBoard = 0;
+    loop {
+        occupancy.push(blockers);
+        reference.push(slider_attacks(sq, directions, blockers));
+        blockers = blockers.wrapping_sub(mask) & mask;
+        if blockers == 0 {
+            break;
+        }
+    }
+    let mut used = vec![0; occupancy.len()];
+    let mut epoch = vec![0u32; occupancy.len()];
+    let mut attempt = 0;
+    let mut state = MAGIC_SEEDS[sq / 8];
+    loop {
+        let magic = next_random(&mut state) & next_random(&mut state) & next_random(&mut state);
+        // too few bits in the top byte spread the subsets badly
+        if (magic.wrapping_mul(mask) >> 56).count_ones() < 6 {
+            continue;
+        }
+        attempt += 1;
+        let fits = occupancy.iter().zip(&reference).all(|(&occupied, &attacks)| {
+            let index = (magic.wrapping_mul(occupied) >> shift) as usize;
+            if epoch[index] < attempt {
+                epoch[index] = attempt;
+                used[index] = attacks;
+                return true;
+            }
+            return used[index] == attacks;
+        });
+        if fits {
+            return magic;
+        }
+    }
+}
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed=src/core/tables/rays.rs");
+    let mut code = String::new();
+    for (name, directions) in [("ROOK_MAGICS", ROOK_DIRECTIONS), ("BISHOP_MAGICS", BISHOP_DIRECTIONS)] {
+        writeln!(code, "const {}: [BitBoard; 64] = [", name).unwrap();
+        for sq in 0..64 {
+            writeln!(code, "    0x{:016X},", find_magic(sq, &directions)).unwrap();
+        }
+        writeln!(code, "];").unwrap();
+    }
+    let path = Path::new(&env::var("OUT_DIR").unwrap()).join("magic_numbers.rs");
+    fs::write(path, code).unwrap();
+}
@@ -0,0 +1,129 @@
+use std::{fs, time::{SystemTime, UNIX_EPOCH}};
+
+use crate::core::{
+    Board, Color, Piece,
+    castle_rights::{CastleRightsUtil, KING_SIDE, QUEEN_SIDE},
+    move_list::MoveList,
+    movegen::generate_all_moves,
+    r#move::{Move, MoveUtil},
+    square::SquareUtil,
+    tables::{
+        magics::get_pawn_attacks,
+        polyglot::{POLYGLOT_CASTLE_OFFSET, POLYGLOT_EN_PASSANT_OFFSET, POLYGLOT_RANDOM, POLYGLOT_TURN_OFFSET},
+    },
+};
+
+// key, move, weight and learn, all big endian
+const ENTRY_SIZE: usize = 16;
+
+struct BookEntry {
+    key: u64,
+    mv: u16,
+    weight: u16,
+}
+pub struct Book {
+    // sorted by key like the file
+    entries: Vec<BookEntry>,
+    seed: u64,
+}
+
+// the key of a position in polyglot books, en passant only counts when a pawn can take
+pub fn polyglot_key(board: &Board) -> u64 {
+    let mut key = 0;
+    for sq in 0..64 {
+        if let Some(piece) = board.piece_on(sq) {
+            let white = board.color_combined[Color::White as usize] & (1 << sq) != 0;
+            key ^= POLYGLOT_RANDOM[(piece as usize * 2 + white as usize) * 64 + sq as usize];
+        }
+    }
+    for (i, (color, side)) in
+        [(Color::White, KING_SIDE), (Color::White, QUEEN_SIDE), (Color::Black, KING_SIDE), (Color::Black, QUEEN_SIDE)]
+            .into_iter()
+            .enumerate()
+    {
+        if board.castle_rights.has(color, side) {
+            key ^= POLYGLOT_RANDOM[POLYGLOT_CASTLE_OFFSET + i];
+        }
+    }
+    if let Some(ep) = board.en_passant {
+        let pawns = board.get_piece_bitboard(Piece::Pawn, board.turn);
+        if get_pawn_attacks(ep, !board.turn) & pawns != 0 {
+            key ^= POLYGLOT_RANDOM[POLYGLOT_EN_PASSANT_OFFSET + ep.get_file() as usize];
+        }
+    }
+    if board.turn == Color::White {
+        key ^= POLYGLOT_RANDOM[POLYGLOT_TURN_OFFSET];
+    }
+    return key;
+}
+
+impl Book {
+    pub fn open(path: &str) -> Result<Book, String> {
+        let bytes = fs::read(path).map_err(|e| e.to_string())?;
+        return Book::from_bytes(&bytes);
+    }
+    pub fn from_bytes(bytes: &[u8]) -> Result<Book, String> {
+        if !bytes.len().is_multiple_of(ENTRY_SIZE) {
+            return Err(format!("{} bytes is not a whole number of {} byte entries", bytes.len(), ENTRY_SIZE));
+        }
+        let entries = bytes
+            .chunks_exact(ENTRY_SIZE)
+            .map(|e| BookEntry {
+                key: u64::from_be_bytes(e[0..8].try_into().unwrap()),
+                mv: u16::from_be_bytes(e[8..10].try_into().unwrap()),
+                weight: u16::from_be_bytes(e[10..12].try_into().unwrap()),
+            })
+            .collect::<Vec<BookEntry>>();
+        if entries.windows(2).any(|w| w[0].key > w[1].key) {
+            return Err(String::from("the entries are not sorted by key"));
+        }
+        let seed = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_nanos() as u64);
+        return Ok(Book { entries, seed: seed | 1 });
+    }
+    pub fn len(&self) -> usize {
+        return self.entries.len();
+    }
+    // the legal book moves of a position with their weights, entries for illegal moves are skipped
+    pub fn moves(&self, board: &Board) -> Vec<(Move, u16)> {
+        let key = polyglot_key(board);
+        let start = self.entries.partition_point(|e| e.key < key);
+        let mut legal = MoveList::new();
+        generate_all_moves(board, &mut legal);
+        let mut moves = vec![];
+        for entry in self.entries[start..].iter().take_while(|e| e.key == key) {
+            // from in bits 6 to 11, to in bits 0 to 5 and the promotion piece above, castles are
+            // king takes rook like the engine's own moves
+            let (from, to, promotion) = ((entry.mv >> 6) & 63, entry.mv & 63, (entry.mv >> 12) & 7);
+            let found = legal.into_iter().find(|m| {
+                let sp = if m.is_promotion() { m.get_sp() } else { 0 };
+                return m.get_from() as u16 == from && m.get_to() as u16 == to && sp == promotion;
+            });
+            if let Some(m) = found {
+                moves.push((m, entry.weight));
+            }
+        }
+        return moves;
+    }
+    // the heaviest move, or one drawn in proportion to the weights
+    pub fn probe(&mut self, board: &Board, best: bool) -> Option<Move> {
+        let moves = self.moves(board);
+        let total: u64 = moves.iter().map(|&(_, w)| w as u64).sum();
+        if total == 0 {
+            return None;
+        }
+        if best {
+            return moves.iter().max_by_key(|&&(_, w)| w).map(|&(m, _)| m);
+        }
+        self.seed ^= self.seed << 13;
+        self.seed ^= self.seed >> 7;
+        self.seed ^= self.seed << 17;
+        let mut pick = self.seed % total;
+        for &(m, weight) in &moves {
+            if pick < weight as u64 {
+                return Some(m);
+            }
+            pick -= weight as u64;
+        }
+        return None;
+    }
+}
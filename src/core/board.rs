@@ -1,21 +1,18 @@
-use crate::core::bitboard::{BitBoard, BitBoardUtil};
+use crate::core::bitboard::{BitBoard, BitBoardUtil, RANK_BITBOARDS};
 use crate::core::castle_rights::{
-    CastleRights, BLACK_ALL_REMOVED, BLACK_KING_SIDE_REMOVED, BLACK_KING_SIDE_ROOK,
-    BLACK_QUEEN_SIDE_REMOVED, BLACK_QUEEN_SIDE_ROOK, KING_SIDE_CASTLE_ROOKS,
-    KING_SIDE_CASTLE_ROOK_DEST, QUEEN_SIDE_CASTLE_ROOKS, QUEEN_SIDE_CASTLE_ROOK_DEST,
-    WHITE_ALL_REMOVED, WHITE_KING_SIDE_REMOVED, WHITE_KING_SIDE_ROOK, WHITE_QUEEN_SIDE_REMOVED,
-    WHITE_QUEEN_SIDE_ROOK,
+    castle_right, CastleRights, BLACK_ALL_REMOVED, CASTLE_KING_DEST, CASTLE_ROOK_DEST,
+    DEFAULT_CASTLE_ROOKS, DEFAULT_KING_SQUARES, KING_SIDE, QUEEN_SIDE, WHITE_ALL_REMOVED,
 };
 use crate::core::color::Color;
-use crate::core::piece::{
-    Piece, BISHOP_TYPE_VALUE, KING_TYPE_VALUE, KNIGHT_TYPE_VALUE, PAWN_TYPE_VALUE,
-    QUEEN_TYPE_VALUE, ROOK_TYPE_VALUE,
-};
+use crate::core::piece::{Piece, PAWN_TYPE_VALUE};
+use crate::core::move_list::MoveList;
+use crate::core::movegen::generate_all_moves;
 use crate::core::r#move::{
-    move_from_string, ExtendedMove, Move, MoveUtil, SpecialFalg, EN_PASSANT, KING_SIDE_CASTLE,
-    KING_SIDE_CASTLE_MOVES, PROMOTION_PIECES, QUEEN_SIDE_CASTLE, QUEEN_SIDE_CASTLE_MOVES,
+    move_from_string, ExtendedMove, Move, MoveParseError, MoveUtil, SpecialFalg, KING_SIDE_CASTLE,
+    PROMOTION_PIECES,
 };
-use crate::core::square::{str_to_square, Square};
+use crate::core::fen::{FenError, START_POSITION_FEN};
+use crate::core::square::{parse_square, Square, SquareUtil};
 use crate::core::tables::magics::{
     get_between, get_bishop_moves, get_bishop_rays, get_king_moves, get_knight_moves,
     get_pawn_attacks, get_pawn_controlled_bb, get_rook_moves, get_rook_rays, EP_TARGETS,
@@ -23,81 +20,298 @@ use crate::core::tables::magics::{
 use crate::core::tables::zobrist::{
     get_castle_zobrist, get_ep_zobrist, get_piece_zobrist, get_turn_zobrist,
 };
+use crate::evaluation::nnue::{self, Accumulator};
+use crate::evaluation::endgame::{MaterialKey, material_key_unit};
+use crate::evaluation::score::{PHASE_WEIGHTS, PackedScore};
 use crate::evaluation::tables::get_pst_value;
-use crate::search::defs::Score;
 // use crate::evaluation::tables::get_pst_value;
 const PIECE_LETTERS: [char; 12] = ['p', 'n', 'b', 'r', 'q', 'k', 'P', 'N', 'B', 'R', 'Q', 'K'];
 const CASTLE_RIGHTS_LETTERS: [char; 4] = ['K', 'Q', 'k', 'q'];
-#[derive(Clone, Copy)]
+const FILE_LETTERS: [char; 8] = ['a', 'b', 'c', 'd', 'e', 'f', 'g', 'h'];
+#[derive(Clone, Copy, PartialEq)]
 pub struct Board {
     pub pieces: [BitBoard; 6],
+    pub mailbox: [Option<Piece>; 64],
     pub color_combined: [BitBoard; 2],
     pub combined: BitBoard,
     pub checkers: BitBoard,
     pub pinned: BitBoard,
+    pub attacks: [BitBoard; 2], // squares attacked by each color
     pub castle_rights: CastleRights,
+    pub castle_rooks: [[Square; 2]; 2], // [color][side]
+    pub chess960: bool,
     pub turn: Color,
     pub hash: u64,
     pub pawn_hash: u64,
     pub en_passant: Option<Square>,
     pub halfmove_clock: u8,
-    pub pst_value: Score,
+    pub fullmove_number: u16,
+    pub pst_value: [PackedScore; 2], // piece square values of each color, white point of view
+    pub phase: u8, // MAX_PHASE with all pieces on the board, 0 with only pawns and kings
+    pub material_key: MaterialKey, // piece counts of both colors
+    pub accumulator: Accumulator, // nnue first layer, only kept up to date while a network is active
+}
+// everything make_move can not recover from the move itself
+#[derive(Clone, Copy)]
+pub struct StateInfo {
+    pub captured: Option<Piece>,
+    pub castle_rights: CastleRights,
+    pub en_passant: Option<Square>,
+    pub halfmove_clock: u8,
+    pub hash: u64,
+    pub pawn_hash: u64,
+    pub checkers: BitBoard,
+    pub pinned: BitBoard,
+    pub attacks: [BitBoard; 2],
+    pub pst_value: [PackedScore; 2],
+    pub phase: u8,
+    pub material_key: MaterialKey,
 }
 impl Board {
     pub fn default() -> Board {
-        return Board::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+        return Board::from_fen(START_POSITION_FEN).unwrap();
     }
-    pub fn from_fen(fen: &str) -> Board {
-        let args = fen.split(" ").collect::<Vec<&str>>();
-        let ranks = args[0].split("/").collect::<Vec<&str>>();
-        let mut board = Board {
+    fn empty() -> Board {
+        return Board {
             pieces: [0; 6],
+            mailbox: [None; 64],
             color_combined: [0; 2],
             combined: 0,
             checkers: 0,
             pinned: 0,
+            attacks: [0; 2],
             castle_rights: 0,
+            castle_rooks: DEFAULT_CASTLE_ROOKS,
+            chess960: false,
             turn: Color::White,
             hash: 0,
             en_passant: None,
             pawn_hash: 0,
             halfmove_clock: 0,
-            pst_value: 0,
+            fullmove_number: 1,
+            pst_value: [0; 2],
+            phase: 0,
+            material_key: 0,
+            accumulator: Accumulator::default(),
         };
+    }
+    pub fn from_fen(fen: &str) -> Result<Board, FenError> {
+        let args = fen.split_whitespace().collect::<Vec<&str>>();
+        if args.len() < 4 {
+            return Err(FenError::MissingFields(args.len()));
+        }
+        let ranks = args[0].split("/").collect::<Vec<&str>>();
+        if ranks.len() != 8 {
+            return Err(FenError::WrongRankCount(ranks.len()));
+        }
+        let mut board = Board::empty();
         for i in 0..8 {
             let rank = ranks[7 - i];
-            let mut sq = i * 8;
+            let mut file = 0;
             for c in rank.chars() {
-                if c.is_numeric() {
-                    sq += c.to_digit(10).unwrap() as usize;
+                if let Some(n) = c.to_digit(10) {
+                    if n == 0 || n > 8 {
+                        return Err(FenError::InvalidRankLength(i + 1));
+                    }
+                    file += n as usize;
                     continue;
                 }
-                for n in 0..12 {
-                    if PIECE_LETTERS[n] == c {
-                        let color = if n > 5 { Color::White } else { Color::Black };
-                        board.put_piece(Piece::from_index(n % 6), sq as Square, color);
-                    }
+                let n = match PIECE_LETTERS.iter().position(|&l| l == c) {
+                    Some(n) => n,
+                    None => return Err(FenError::InvalidPiece(c)),
+                };
+                if file >= 8 {
+                    return Err(FenError::InvalidRankLength(i + 1));
                 }
-                sq += 1;
+                let color = if n > 5 { Color::White } else { Color::Black };
+                board.put_piece(Piece::from_index(n % 6), (i * 8 + file) as Square, color);
+                file += 1;
+            }
+            if file != 8 {
+                return Err(FenError::InvalidRankLength(i + 1));
+            }
+        }
+        let white_kings = board.get_piece_bitboard(Piece::King, Color::White).count_ones();
+        let black_kings = board.get_piece_bitboard(Piece::King, Color::Black).count_ones();
+        if white_kings != 1 || black_kings != 1 {
+            return Err(FenError::KingCount(white_kings as usize, black_kings as usize));
+        }
+        if board.pieces[PAWN_TYPE_VALUE] & (RANK_BITBOARDS[0] | RANK_BITBOARDS[7]) != 0 {
+            return Err(FenError::PawnOnBackRank);
+        }
+
+        match args[1] {
+            "w" => {}
+            "b" => {
+                board.turn = Color::Black;
+                board.hash ^= get_turn_zobrist();
             }
+            _ => return Err(FenError::InvalidSideToMove(args[1].to_string())),
         }
-        for c in args[2].chars() {
-            for i in 0..4 {
-                if c == CASTLE_RIGHTS_LETTERS[i] {
-                    board.castle_rights |= 1 << i;
+
+        if args[2] != "-" {
+            for c in args[2].chars() {
+                let color = if c.is_ascii_uppercase() {
+                    Color::White
+                } else {
+                    Color::Black
+                };
+                let back_rank = if color == Color::White { 0 } else { 56 };
+                let king = board.king_square(color);
+                let rooks = board.get_piece_bitboard(Piece::Rook, color);
+                // X-FEN letters pick the outermost rook, Shredder-FEN letters name its file
+                let rook = match c.to_ascii_lowercase() {
+                    'k' => (king + 1..back_rank + 8).rev().find(|&sq| rooks.has_sq(sq)),
+                    'q' => (back_rank..king).find(|&sq| rooks.has_sq(sq)),
+                    f @ 'a'..='h' => Some(back_rank + (f as u8 - b'a')),
+                    _ => return Err(FenError::InvalidCastleRights(args[2].to_string())),
+                };
+                let on_back_rank = |sq: Square| sq >> 3 == back_rank >> 3;
+                let rook = match rook {
+                    Some(rook) if rooks.has_sq(rook) && on_back_rank(king) && on_back_rank(rook) => {
+                        rook
+                    }
+                    _ => return Err(FenError::InconsistentCastleRights(c)),
+                };
+                let side = if rook > king { KING_SIDE } else { QUEEN_SIDE };
+                board.castle_rooks[color as usize][side] = rook;
+                board.castle_rights |= castle_right(color, side);
+                if king != DEFAULT_KING_SQUARES[color as usize]
+                    || rook != DEFAULT_CASTLE_ROOKS[color as usize][side]
+                {
+                    board.chess960 = true;
                 }
             }
         }
         board.hash ^= get_castle_zobrist(board.castle_rights);
-        if args[1] == "b" {
-            board.turn = Color::Black;
+
+        if args[3] != "-" {
+            let ep = parse_square(args[3])
+                .ok_or_else(|| FenError::InvalidEnPassant(args[3].to_string()))?;
+            let ep_rank = if board.turn == Color::White { 5 } else { 2 };
+            // checked before the square behind it is computed, which would leave the board
+            if ep.get_rank() != ep_rank {
+                return Err(FenError::InvalidEnPassant(args[3].to_string()));
+            }
+            let start = if board.turn == Color::White { ep + 8 } else { ep - 8 };
+            if !board.get_piece_bitboard(Piece::Pawn, !board.turn).has_sq(EP_TARGETS[ep as usize])
+                || board.combined.has_sq(ep)
+                || board.combined.has_sq(start)
+            {
+                return Err(FenError::InconsistentEnPassant(args[3].to_string()));
+            }
+            board.en_passant = Some(ep);
+            board.hash ^= get_ep_zobrist(!board.turn, board.en_passant);
+        }
+        if args.len() > 4 {
+            board.halfmove_clock = args[4]
+                .parse::<u8>()
+                .map_err(|_| FenError::InvalidHalfmoveClock(args[4].to_string()))?;
+        }
+        if args.len() > 5 {
+            board.fullmove_number = args[5]
+                .parse::<u16>()
+                .map_err(|_| FenError::InvalidFullmoveNumber(args[5].to_string()))?
+                .max(1);
+        }
+        if board.is_controlled(board.king_square(!board.turn), board.turn) {
+            return Err(FenError::SideNotToMoveInCheck);
+        }
+        board.update_pins_and_checks();
+        board.refresh_accumulator();
+        return Ok(board);
+    }
+    #[allow(clippy::wrong_self_convention)]
+    pub fn to_fen(&self) -> String {
+        let mut fen = String::new();
+        for rank in (0..8).rev() {
+            let mut empty = 0;
+            for file in 0..8 {
+                let sq = rank * 8 + file;
+                let piece = self.piece_on(sq);
+                if piece.is_none() {
+                    empty += 1;
+                    continue;
+                }
+                if empty != 0 {
+                    fen.push_str(&empty.to_string());
+                    empty = 0;
+                }
+                let p = if self.color_combined[Color::White as usize].has_sq(sq) {
+                    piece.unwrap() as usize + 6
+                } else {
+                    piece.unwrap() as usize
+                };
+                fen.push(PIECE_LETTERS[p]);
+            }
+            if empty != 0 {
+                fen.push_str(&empty.to_string());
+            }
+            if rank != 0 {
+                fen.push('/');
+            }
+        }
+        fen.push_str(if self.turn == Color::White { " w " } else { " b " });
+        if self.castle_rights == 0 {
+            fen.push('-');
+        }
+        for (i, letter) in CASTLE_RIGHTS_LETTERS.iter().enumerate() {
+            if self.castle_rights & (1 << i) == 0 {
+                continue;
+            }
+            // chess960 positions are written as Shredder-FEN
+            if self.chess960 {
+                let rook = self.castle_rooks[i / 2][i % 2];
+                let file = FILE_LETTERS[rook.get_file() as usize];
+                fen.push(if i < 2 { file.to_ascii_uppercase() } else { file });
+            } else {
+                fen.push(*letter);
+            }
+        }
+        fen.push(' ');
+        match self.en_passant {
+            Some(sq) => fen.push_str(&sq.to_str()),
+            None => fen.push('-'),
+        }
+        fen.push_str(&format!(" {} {}", self.halfmove_clock, self.fullmove_number));
+        return fen;
+    }
+    // the same position flipped vertically with the colors swapped, the side to move included,
+    // so any evaluation should score it the same from the side to move's point of view
+    pub fn mirror(&self) -> Board {
+        let mut board = Board::empty();
+        let mut occupied = self.combined;
+        while occupied != 0 {
+            let sq = occupied.pop_lsb();
+            let color = if self.color_combined[Color::White as usize].has_sq(sq) {
+                Color::White
+            } else {
+                Color::Black
+            };
+            board.put_piece(self.piece_on(sq).unwrap(), sq ^ 56, !color);
+        }
+        board.turn = !self.turn;
+        if board.turn == Color::Black {
             board.hash ^= get_turn_zobrist();
         }
-        if args[3] != "-" {
-            board.en_passant = Some(str_to_square(args[3]));
-            board.hash ^= get_ep_zobrist(board.turn, board.en_passant);
+        for color in [Color::White, Color::Black] {
+            for side in [KING_SIDE, QUEEN_SIDE] {
+                if self.castle_rights & castle_right(color, side) != 0 {
+                    board.castle_rooks[!color as usize][side] = self.castle_rooks[color as usize][side] ^ 56;
+                    board.castle_rights |= castle_right(!color, side);
+                }
+            }
+        }
+        board.chess960 = self.chess960;
+        board.hash ^= get_castle_zobrist(board.castle_rights);
+        board.en_passant = self.en_passant.map(|sq| sq ^ 56);
+        if board.en_passant.is_some() {
+            board.hash ^= get_ep_zobrist(!board.turn, board.en_passant);
         }
+        board.halfmove_clock = self.halfmove_clock;
+        board.fullmove_number = self.fullmove_number;
         board.update_pins_and_checks();
+        board.refresh_accumulator();
         return board;
     }
     pub fn print(&self) {
@@ -105,12 +319,11 @@ impl Board {
             let mut chars = [' '; 8];
             for file in 0..8 {
                 let sq = rank * 8 + file;
-                let piece = self.piece_on(sq);
-                if piece.is_some() {
+                if let Some(piece) = self.piece_on(sq) {
                     let p = if self.color_combined[Color::White as usize].has_sq(sq) {
-                        piece.unwrap() as usize + 6
+                        piece as usize + 6
                     } else {
-                        piece.unwrap() as usize
+                        piece as usize
                     };
                     chars[file as usize] = PIECE_LETTERS[p];
                 }
@@ -131,144 +344,161 @@ impl Board {
         return self.color_combined[self.turn as usize];
     }
     pub fn piece_on(&self, sq: Square) -> Option<Piece> {
-        if self.pieces[PAWN_TYPE_VALUE].has_sq(sq) {
-            return Some(Piece::Pawn);
-        } else if self.pieces[KNIGHT_TYPE_VALUE].has_sq(sq) {
-            return Some(Piece::Knight);
-        } else if self.pieces[BISHOP_TYPE_VALUE].has_sq(sq) {
-            return Some(Piece::Bishop);
-        } else if self.pieces[ROOK_TYPE_VALUE].has_sq(sq) {
-            return Some(Piece::Rook);
-        } else if self.pieces[QUEEN_TYPE_VALUE].has_sq(sq) {
-            return Some(Piece::Queen);
-        } else if self.pieces[KING_TYPE_VALUE].has_sq(sq) {
-            return Some(Piece::King);
-        }
-        return None;
+        return self.mailbox[sq as usize];
+    }
+    pub fn captured_piece(&self, m: Move) -> Option<Piece> {
+        // castling is encoded as the king capturing its own rook
+        if m.is_castle() {
+            return None;
+        }
+        return self.piece_on(m.get_to());
     }
     pub fn get_line_attackers(&self, color: Color) -> BitBoard {
         return (self.pieces[Piece::Rook as usize] | self.pieces[Piece::Queen as usize])
             & self.color_combined[color as usize];
     }
     pub fn is_controlled(&self, sq: Square, color: Color) -> bool {
-        let sq_bb = 1u64 << sq;
-        if (get_pawn_controlled_bb(self.get_piece_bitboard(Piece::Pawn, color), color) & sq_bb) != 0
-        {
-            return true;
+        return self.is_controlled_through(sq, color, self.combined);
+    }
+    pub fn is_controlled_through(&self, sq: Square, color: Color, blockers: BitBoard) -> bool {
+        return self.attackers_to(sq, blockers) & self.color_combined[color as usize] != 0;
+    }
+    // pieces of both colors attacking sq when only the squares in occupancy block
+    pub fn attackers_to(&self, sq: Square, occupancy: BitBoard) -> BitBoard {
+        let diagonal = self.pieces[Piece::Bishop as usize] | self.pieces[Piece::Queen as usize];
+        let line = self.pieces[Piece::Rook as usize] | self.pieces[Piece::Queen as usize];
+        return (get_pawn_attacks(sq, Color::White) & self.get_piece_bitboard(Piece::Pawn, Color::Black))
+            | (get_pawn_attacks(sq, Color::Black) & self.get_piece_bitboard(Piece::Pawn, Color::White))
+            | (get_knight_moves(sq) & self.pieces[Piece::Knight as usize])
+            | (get_king_moves(sq) & self.pieces[Piece::King as usize])
+            | (get_bishop_moves(sq, occupancy) & diagonal)
+            | (get_rook_moves(sq, occupancy) & line);
+    }
+    // every square attacked by color, occupied or not
+    pub fn attacks_by(&self, color: Color, occupancy: BitBoard) -> BitBoard {
+        let mine = self.color_combined[color as usize];
+        let mut attacks = get_pawn_controlled_bb(self.get_piece_bitboard(Piece::Pawn, color), color)
+            | get_king_moves(self.king_square(color));
+        let mut knights = self.pieces[Piece::Knight as usize] & mine;
+        while knights != 0 {
+            attacks |= get_knight_moves(knights.pop_lsb());
         }
-        let mut line_attackers = (self.pieces[Piece::Rook as usize]
-            | self.pieces[Piece::Queen as usize])
-            & self.color_combined[color as usize];
-        while line_attackers != 0 {
-            if (get_rook_moves(line_attackers.pop_lsb(), self.combined) & sq_bb) != 0 {
-                return true;
-            }
+        let mut diagonal = (self.pieces[Piece::Bishop as usize] | self.pieces[Piece::Queen as usize]) & mine;
+        while diagonal != 0 {
+            attacks |= get_bishop_moves(diagonal.pop_lsb(), occupancy);
         }
-        let mut diagonal_attackers = (self.pieces[Piece::Bishop as usize]
-            | self.pieces[Piece::Queen as usize])
-            & self.color_combined[color as usize];
-        while diagonal_attackers != 0 {
-            if (get_bishop_moves(diagonal_attackers.pop_lsb(), self.combined) & sq_bb) != 0 {
-                return true;
-            }
+        let mut line = (self.pieces[Piece::Rook as usize] | self.pieces[Piece::Queen as usize]) & mine;
+        while line != 0 {
+            attacks |= get_rook_moves(line.pop_lsb(), occupancy);
         }
-        let mut knights = self.get_piece_bitboard(Piece::Knight, color);
-        while knights != 0 {
-            if (get_knight_moves(knights.pop_lsb()) & sq_bb) != 0 {
-                return true;
+        return attacks;
+    }
+    // squares a piece of the side to move would give check from
+    pub fn check_squares(&self, piece: Piece) -> BitBoard {
+        let king = self.king_square(!self.turn);
+        return match piece {
+            Piece::Pawn => get_pawn_attacks(king, !self.turn),
+            Piece::Knight => get_knight_moves(king),
+            Piece::Bishop => get_bishop_moves(king, self.combined),
+            Piece::Rook => get_rook_moves(king, self.combined),
+            Piece::Queen => get_bishop_moves(king, self.combined) | get_rook_moves(king, self.combined),
+            Piece::King => 0,
+        };
+    }
+    // pieces of the side to move that block one of its own sliders from the enemy king
+    pub fn discovered_check_candidates(&self) -> BitBoard {
+        let king = self.king_square(!self.turn);
+        let friendly_pieces = self.get_friendly_pieces();
+        let mut candidates = 0;
+        let mut sliders = ((self.pieces[Piece::Bishop as usize] | self.pieces[Piece::Queen as usize])
+            & get_bishop_rays(king)
+            | (self.pieces[Piece::Rook as usize] | self.pieces[Piece::Queen as usize])
+                & get_rook_rays(king))
+            & friendly_pieces;
+        while sliders != 0 {
+            let bb = get_between(king, sliders.pop_lsb()) & self.combined;
+            if bb.count_ones() == 1 && bb & friendly_pieces != 0 {
+                candidates |= bb;
             }
         }
-        return (get_king_moves(self.get_piece_bitboard(Piece::King, color).pop_lsb()) & sq_bb)
-            != 0;
+        return candidates;
     }
     fn update_pins_and_checks(&mut self) {
-        self.pinned = 0;
-        self.checkers = 0;
-        let king = self.get_piece_bitboard(Piece::King, self.turn).to_sq();
+        let king = self.king_square(self.turn);
         let friendly_pieces = self.get_friendly_pieces();
-        let mut diagonal_pinners = (self.pieces[Piece::Bishop as usize]
-            | self.pieces[Piece::Queen as usize])
-            & self.get_enemy_pieces()
-            & get_bishop_rays(king);
-        let mut line_pinners = (self.pieces[Piece::Rook as usize]
-            | self.pieces[Piece::Queen as usize])
-            & self.get_enemy_pieces()
-            & get_rook_rays(king);
-        while diagonal_pinners != 0 {
-            let piece = diagonal_pinners.pop_lsb();
-            let bb = get_between(king, piece);
-            if (bb & friendly_pieces) != (bb & self.combined) {
-                continue;
-            }
-            match (friendly_pieces & bb).count_ones() {
-                0 => {
-                    self.checkers |= 1 << piece;
-                }
-                1 => {
-                    self.pinned |= friendly_pieces & bb;
-                }
-                _ => {}
-            }
-        }
-        while line_pinners != 0 {
-            let piece = line_pinners.pop_lsb();
-            let bb = get_between(king, piece);
-            if (bb & friendly_pieces) != (bb & self.combined) {
-                continue;
-            }
-            match (friendly_pieces & bb).count_ones() {
-                0 => {
-                    self.checkers |= 1 << piece;
-                }
-                1 => {
-                    self.pinned |= friendly_pieces & bb;
-                }
-                _ => {}
+        let enemy_pieces = self.get_enemy_pieces();
+        self.checkers = self.attackers_to(king, self.combined) & enemy_pieces;
+        self.pinned = 0;
+        let mut snipers = ((self.pieces[Piece::Bishop as usize] | self.pieces[Piece::Queen as usize])
+            & get_bishop_rays(king)
+            | (self.pieces[Piece::Rook as usize] | self.pieces[Piece::Queen as usize])
+                & get_rook_rays(king))
+            & enemy_pieces;
+        while snipers != 0 {
+            let bb = get_between(king, snipers.pop_lsb()) & self.combined;
+            if bb.count_ones() == 1 && bb & friendly_pieces != 0 {
+                self.pinned |= bb;
             }
         }
-        self.checkers |=
-            self.get_piece_bitboard(Piece::Knight, !self.turn) & get_knight_moves(king);
-            
-        self.checkers |=
-            self.get_piece_bitboard(Piece::Pawn, !self.turn) & get_pawn_attacks(king, self.turn);
+        self.attacks = [
+            self.attacks_by(Color::White, self.combined),
+            self.attacks_by(Color::Black, self.combined),
+        ];
     }
-    fn put_piece(&mut self, piece: Piece, sq: Square, color: Color) {
+    fn set_piece(&mut self, piece: Piece, sq: Square, color: Color) {
         let bb = 1u64 << sq;
         self.pieces[piece as usize] |= bb;
         self.color_combined[color as usize] |= bb;
         self.combined |= bb;
-        self.hash ^= get_piece_zobrist(piece, color, sq);
-        if piece == Piece::Pawn {
-            self.pawn_hash ^= get_piece_zobrist(Piece::Pawn, color, sq);
-        } 
-        else if piece != Piece::Pawn && piece != Piece::King {
-            self.pst_value += get_pst_value(color, piece, sq);
+        self.mailbox[sq as usize] = Some(piece);
+        if let Some(network) = nnue::active() {
+            self.accumulator.add(network, piece, sq, color);
         }
     }
-    fn remove_piece(&mut self, piece: Piece, sq: Square, color: Color) {
+    fn clear_piece(&mut self, piece: Piece, sq: Square, color: Color) {
         let bb = 1u64 << sq;
         self.pieces[piece as usize] ^= bb;
         self.color_combined[color as usize] ^= bb;
         self.combined ^= bb;
+        self.mailbox[sq as usize] = None;
+        if let Some(network) = nnue::active() {
+            self.accumulator.sub(network, piece, sq, color);
+        }
+    }
+    // recomputes the accumulator from scratch, needed after a network is loaded or enabled
+    pub fn refresh_accumulator(&mut self) {
+        if let Some(network) = nnue::active() {
+            self.accumulator = Accumulator::refresh(self, network);
+        }
+    }
+    fn put_piece(&mut self, piece: Piece, sq: Square, color: Color) {
+        self.set_piece(piece, sq, color);
         self.hash ^= get_piece_zobrist(piece, color, sq);
         if piece == Piece::Pawn {
             self.pawn_hash ^= get_piece_zobrist(Piece::Pawn, color, sq);
-        } 
-        else if piece != Piece::Pawn && piece != Piece::King {
-            self.pst_value -= get_pst_value(color, piece, sq);
         }
+        self.pst_value[color as usize] += get_pst_value(color, piece, sq);
+        self.phase += PHASE_WEIGHTS[piece as usize];
+        self.material_key += material_key_unit(color, piece);
     }
-    fn remove_white_king_side(&mut self) {
-        self.castle_rights &= WHITE_KING_SIDE_REMOVED;
-    }
-    fn remove_white_queen_side(&mut self) {
-        self.castle_rights &= WHITE_QUEEN_SIDE_REMOVED;
-    }
-    fn remove_black_king_side(&mut self) {
-        self.castle_rights &= BLACK_KING_SIDE_REMOVED;
+    fn remove_piece(&mut self, piece: Piece, sq: Square, color: Color) {
+        self.clear_piece(piece, sq, color);
+        self.hash ^= get_piece_zobrist(piece, color, sq);
+        if piece == Piece::Pawn {
+            self.pawn_hash ^= get_piece_zobrist(Piece::Pawn, color, sq);
+        }
+        self.pst_value[color as usize] -= get_pst_value(color, piece, sq);
+        self.phase -= PHASE_WEIGHTS[piece as usize];
+        self.material_key -= material_key_unit(color, piece);
     }
-    fn remove_black_queen_side(&mut self) {
-        self.castle_rights &= BLACK_QUEEN_SIDE_REMOVED;
+    fn remove_rook_castle_rights(&mut self, sq: Square) {
+        for color in [Color::White, Color::Black] {
+            for side in [KING_SIDE, QUEEN_SIDE] {
+                if self.castle_rooks[color as usize][side] == sq {
+                    self.castle_rights &= !castle_right(color, side);
+                }
+            }
+        }
     }
     fn remove_castle_rights(&mut self, color: Color) {
         if color == Color::White {
@@ -282,59 +512,67 @@ impl Board {
         new_board.make_move(m);
         return new_board;
     }
-    pub fn make_move(&mut self, m: Move) {
+    fn move_piece(&mut self, m: Move, piece: Piece, target: Option<Piece>, me: Color) {
         let from = m.get_from();
         let to = m.get_to();
-        let sp = m.get_sp();
-        let piece = self.piece_on(from).unwrap();
-        let target = self.piece_on(to);
-        let me = self.turn;
-        let them = !me;
-        let offset = u8::max(from, to) - u8::min(from, to);
-
-        if target.is_some() {
-            self.remove_piece(target.unwrap(), to, them);
+        if let Some(target) = target {
+            self.remove_piece(target, to, !me);
         }
         self.remove_piece(piece, from, me);
         if m.is_promotion() {
-            self.put_piece(PROMOTION_PIECES[sp as usize], to, me);
+            self.put_piece(PROMOTION_PIECES[m.get_sp() as usize], to, me);
         } else {
             self.put_piece(piece, to, me);
         }
 
         if m.is_ep() {
-            self.remove_piece(Piece::Pawn, EP_TARGETS[to as usize], them);
+            self.remove_piece(Piece::Pawn, EP_TARGETS[to as usize], !me);
+        }
+    }
+    pub fn make_move(&mut self, m: Move) -> StateInfo {
+        let from = m.get_from();
+        let to = m.get_to();
+        let piece = self.piece_on(from).unwrap();
+        let me = self.turn;
+        let offset = u8::max(from, to) - u8::min(from, to);
+
+        let target = self.captured_piece(m);
+        let state = StateInfo {
+            captured: target,
+            castle_rights: self.castle_rights,
+            en_passant: self.en_passant,
+            halfmove_clock: self.halfmove_clock,
+            hash: self.hash,
+            pawn_hash: self.pawn_hash,
+            checkers: self.checkers,
+            pinned: self.pinned,
+            attacks: self.attacks,
+            pst_value: self.pst_value,
+            phase: self.phase,
+            material_key: self.material_key,
+        };
+        if m.is_castle() {
+            let side = if m.get_sp() == KING_SIDE_CASTLE {
+                KING_SIDE
+            } else {
+                QUEEN_SIDE
+            };
+            self.remove_piece(Piece::King, from, me);
+            self.remove_piece(Piece::Rook, to, me);
+            self.put_piece(Piece::King, CASTLE_KING_DEST[me as usize][side], me);
+            self.put_piece(Piece::Rook, CASTLE_ROOK_DEST[me as usize][side], me);
+        } else {
+            self.move_piece(m, piece, target, me);
         }
 
         self.hash ^= get_castle_zobrist(self.castle_rights);
         if piece == Piece::King {
             self.remove_castle_rights(me);
         }
-        if from == WHITE_KING_SIDE_ROOK || to == WHITE_KING_SIDE_ROOK {
-            self.remove_white_king_side();
-        }
-        if from == BLACK_KING_SIDE_ROOK || to == BLACK_KING_SIDE_ROOK {
-            self.remove_black_king_side();
-        }
-        if from == WHITE_QUEEN_SIDE_ROOK || to == WHITE_QUEEN_SIDE_ROOK {
-            self.remove_white_queen_side();
-        }
-        if from == BLACK_QUEEN_SIDE_ROOK || to == BLACK_QUEEN_SIDE_ROOK {
-            self.remove_black_queen_side();
-        }
+        self.remove_rook_castle_rights(from);
+        self.remove_rook_castle_rights(to);
         self.hash ^= get_castle_zobrist(self.castle_rights);
 
-        if m.is_castle() {
-            if sp == KING_SIDE_CASTLE {
-                self.remove_piece(Piece::Rook, KING_SIDE_CASTLE_ROOKS[me as usize], me);
-                self.put_piece(Piece::Rook, KING_SIDE_CASTLE_ROOK_DEST[me as usize], me);
-            }
-            if sp == QUEEN_SIDE_CASTLE {
-                self.remove_piece(Piece::Rook, QUEEN_SIDE_CASTLE_ROOKS[me as usize], me);
-                self.put_piece(Piece::Rook, QUEEN_SIDE_CASTLE_ROOK_DEST[me as usize], me);
-            }
-        }
-
         self.hash ^= get_ep_zobrist(!self.turn, self.en_passant);
         if piece == Piece::Pawn && offset == 16 {
             self.en_passant = Some((from + to) >> 1);
@@ -343,6 +581,9 @@ impl Board {
         }
         self.hash ^= get_ep_zobrist(self.turn, self.en_passant);
 
+        if self.turn == Color::Black {
+            self.fullmove_number += 1;
+        }
         self.turn = !self.turn;
         self.hash ^= get_turn_zobrist();
 
@@ -352,26 +593,84 @@ impl Board {
             self.halfmove_clock = 0;
         }
         self.update_pins_and_checks();
+        return state;
     }
-    pub fn make_move_from_str(&mut self, str: &str) {
-        let mut m = move_from_string(str);
-        if self.piece_on(m.get_from()).unwrap() == Piece::King {
-            match str {
-                "e1g1" => m |= KING_SIDE_CASTLE_MOVES[0],
-                "e8g8" => m |= KING_SIDE_CASTLE_MOVES[1],
-                "e1c1" => m |= QUEEN_SIDE_CASTLE_MOVES[0],
-                "e8c8" => m |= QUEEN_SIDE_CASTLE_MOVES[1],
-                _ => {}
+    pub fn unmake_move(&mut self, m: Move, state: &StateInfo) {
+        self.turn = !self.turn;
+        if self.turn == Color::Black {
+            self.fullmove_number -= 1;
+        }
+        let me = self.turn;
+        let from = m.get_from();
+        let to = m.get_to();
+
+        if m.is_castle() {
+            let side = if m.get_sp() == KING_SIDE_CASTLE {
+                KING_SIDE
+            } else {
+                QUEEN_SIDE
+            };
+            self.clear_piece(Piece::King, CASTLE_KING_DEST[me as usize][side], me);
+            self.clear_piece(Piece::Rook, CASTLE_ROOK_DEST[me as usize][side], me);
+            self.set_piece(Piece::King, from, me);
+            self.set_piece(Piece::Rook, to, me);
+        } else {
+            let moved = self.piece_on(to).unwrap();
+            self.clear_piece(moved, to, me);
+            if m.is_promotion() {
+                self.set_piece(Piece::Pawn, from, me);
+            } else {
+                self.set_piece(moved, from, me);
             }
-        } else if self.piece_on(m.get_from()).unwrap() == Piece::Pawn {
-            let offset = m.get_from().abs_diff(m.get_to());
-            if offset == 7 || offset == 9 {
-                if self.piece_on(m.get_to()).is_none() {
-                    m |= EN_PASSANT << 12;
-                }
+            if let Some(captured) = state.captured {
+                self.set_piece(captured, to, !me);
+            }
+            if m.is_ep() {
+                self.set_piece(Piece::Pawn, EP_TARGETS[to as usize], !me);
             }
         }
-        self.make_move(m);
+
+        self.castle_rights = state.castle_rights;
+        self.en_passant = state.en_passant;
+        self.halfmove_clock = state.halfmove_clock;
+        self.hash = state.hash;
+        self.pawn_hash = state.pawn_hash;
+        self.checkers = state.checkers;
+        self.pinned = state.pinned;
+        self.attacks = state.attacks;
+        self.pst_value = state.pst_value;
+        self.phase = state.phase;
+        self.material_key = state.material_key;
+    }
+    // states holds the undo information of every move leading to this position
+    pub fn is_repetition(&self, states: &[StateInfo]) -> bool {
+        let reversible = usize::min(self.halfmove_clock as usize, states.len());
+        let mut i = 2;
+        while i <= reversible {
+            if states[states.len() - i].hash == self.hash {
+                return true;
+            }
+            i += 2;
+        }
+        return false;
+    }
+    pub fn is_draw(&self, states: &[StateInfo]) -> bool {
+        return self.halfmove_clock >= 100 || self.is_repetition(states);
+    }
+    pub fn parse_move(&self, str: &str) -> Result<Move, MoveParseError> {
+        let m = move_from_string(str)?;
+        let mut moves = MoveList::new();
+        generate_all_moves(self, &mut moves);
+        for legal in &moves {
+            if legal.to_uci(self.chess960) == m.to_str() {
+                return Ok(legal);
+            }
+        }
+        return Err(MoveParseError::IllegalMove(str.to_string()));
+    }
+    pub fn make_move_from_str(&mut self, str: &str) -> Result<StateInfo, MoveParseError> {
+        let m = self.parse_move(str)?;
+        return Ok(self.make_move(m));
     }
     #[allow(unused)]
     pub fn get_extended_move(&self, mv: Move) -> ExtendedMove {
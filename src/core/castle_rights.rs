@@ -1,7 +1,6 @@
 use crate::core::{color::Color, square::Square};
 pub trait CastleRightsUtil<T> {
-    fn has_king_side(&self, color: Color) -> bool;
-    fn has_queen_side(&self, color: Color) -> bool;
+    fn has(&self, color: Color, side: usize) -> bool;
     #[allow(unused)]
     fn color(&self, color: Color) -> Rights;
 }
@@ -14,31 +13,34 @@ const BLACK_KING_SIDE: CastleRights = 4;
 const BLACK_QUEEN_SIDE: CastleRights = 8;
 const BLACK_ALL: CastleRights = 12;
 
-pub const WHITE_KING_SIDE_REMOVED: CastleRights = !WHITE_KING_SIDE;
-pub const WHITE_QUEEN_SIDE_REMOVED: CastleRights = !WHITE_QUEEN_SIDE;
-pub const BLACK_KING_SIDE_REMOVED: CastleRights = !BLACK_KING_SIDE;
-pub const BLACK_QUEEN_SIDE_REMOVED: CastleRights = !BLACK_QUEEN_SIDE;
 pub const WHITE_ALL_REMOVED: CastleRights = !(WHITE_KING_SIDE | WHITE_QUEEN_SIDE);
 pub const BLACK_ALL_REMOVED: CastleRights = !(BLACK_KING_SIDE | BLACK_QUEEN_SIDE);
 
+pub const KING_SIDE: usize = 0;
+pub const QUEEN_SIDE: usize = 1;
+
 pub const WHITE_KING_SIDE_ROOK: Square = 7;
 pub const WHITE_QUEEN_SIDE_ROOK: Square = 0;
 pub const BLACK_KING_SIDE_ROOK: Square = 63;
 pub const BLACK_QUEEN_SIDE_ROOK: Square = 56;
 
-pub const KING_SIDE_CASTLE_ROOKS: [Square; 2] = [WHITE_KING_SIDE_ROOK, BLACK_KING_SIDE_ROOK];
-pub const QUEEN_SIDE_CASTLE_ROOKS: [Square; 2] = [WHITE_QUEEN_SIDE_ROOK, BLACK_QUEEN_SIDE_ROOK];
-pub const KING_SIDE_CASTLE_ROOK_DEST: [Square; 2] = [5, 61];
-pub const QUEEN_SIDE_CASTLE_ROOK_DEST: [Square; 2] = [3, 59];
+// indexed by [color][side], the rooks of a standard game
+pub const DEFAULT_CASTLE_ROOKS: [[Square; 2]; 2] = [
+    [WHITE_KING_SIDE_ROOK, WHITE_QUEEN_SIDE_ROOK],
+    [BLACK_KING_SIDE_ROOK, BLACK_QUEEN_SIDE_ROOK],
+];
+pub const DEFAULT_KING_SQUARES: [Square; 2] = [4, 60];
+// the king and rook always end up on the same squares, also in chess960
+pub const CASTLE_KING_DEST: [[Square; 2]; 2] = [[6, 2], [62, 58]];
+pub const CASTLE_ROOK_DEST: [[Square; 2]; 2] = [[5, 3], [61, 59]];
+
+pub fn castle_right(color: Color, side: usize) -> CastleRights {
+    return 1 << (color as usize * 2 + side);
+}
 
 impl CastleRightsUtil<CastleRights> for CastleRights {
-    fn has_king_side(&self, color: Color) -> bool {
-        return (color == Color::White && (self & WHITE_KING_SIDE) != 0)
-            || (color == Color::Black && (self & BLACK_KING_SIDE) != 0);
-    }
-    fn has_queen_side(&self, color: Color) -> bool {
-        return (color == Color::White && (self & WHITE_QUEEN_SIDE) != 0)
-            || (color == Color::Black && (self & BLACK_QUEEN_SIDE) != 0);
+    fn has(&self, color: Color, side: usize) -> bool {
+        return self & castle_right(color, side) != 0;
     }
     fn color(&self, color: Color) -> Rights {
         let x = if color == Color::White {
@@ -55,6 +57,7 @@ impl CastleRightsUtil<CastleRights> for CastleRights {
     }
 }
 #[derive(PartialEq, Eq, Clone, Copy)]
+#[allow(clippy::enum_variant_names)]
 pub enum Rights {
     NoRights,
     KingSide,
@@ -0,0 +1,64 @@
+use std::fmt;
+
+pub const START_POSITION_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum FenError {
+    MissingFields(usize),
+    WrongRankCount(usize),
+    InvalidRankLength(usize),
+    InvalidPiece(char),
+    InvalidSideToMove(String),
+    InvalidCastleRights(String),
+    InvalidEnPassant(String),
+    InvalidHalfmoveClock(String),
+    InvalidFullmoveNumber(String),
+    KingCount(usize, usize),
+    PawnOnBackRank,
+    SideNotToMoveInCheck,
+    InconsistentCastleRights(char),
+    InconsistentEnPassant(String),
+}
+
+impl fmt::Display for FenError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FenError::MissingFields(n) => write!(f, "expected at least 4 fields, found {}", n),
+            FenError::WrongRankCount(n) => write!(f, "expected 8 ranks, found {}", n),
+            FenError::InvalidRankLength(rank) => {
+                write!(f, "rank {} does not describe exactly 8 squares", rank)
+            }
+            FenError::InvalidPiece(c) => write!(f, "invalid piece character '{}'", c),
+            FenError::InvalidSideToMove(s) => write!(f, "invalid side to move '{}'", s),
+            FenError::InvalidCastleRights(s) => write!(f, "invalid castling field '{}'", s),
+            FenError::InvalidEnPassant(s) => write!(f, "invalid en passant field '{}'", s),
+            FenError::InvalidHalfmoveClock(s) => write!(f, "invalid halfmove clock '{}'", s),
+            FenError::InvalidFullmoveNumber(s) => write!(f, "invalid fullmove number '{}'", s),
+            FenError::KingCount(white, black) => write!(
+                f,
+                "expected one king per side, found {} white and {} black",
+                white, black
+            ),
+            FenError::PawnOnBackRank => write!(f, "pawns can not stand on the first or last rank"),
+            FenError::SideNotToMoveInCheck => write!(f, "the side not to move is in check"),
+            FenError::InconsistentCastleRights(c) => write!(
+                f,
+                "castling right '{}' does not match the king and rook placement",
+                c
+            ),
+            FenError::InconsistentEnPassant(s) => {
+                write!(f, "en passant square {} is not reachable by a double push", s)
+            }
+        }
+    }
+}
+
+// splits a dataset line into the fen and what follows it, the move counters are optional
+pub fn fen_fields(line: &str) -> (String, String) {
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    let mut end = 4.min(fields.len());
+    while end < fields.len().min(6) && fields[end].parse::<u32>().is_ok() {
+        end += 1;
+    }
+    return (fields[..end].join(" "), fields[end..].join(" "));
+}
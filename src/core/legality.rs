@@ -0,0 +1,145 @@
+use crate::core::{
+    bitboard::{BitBoard, BitBoardUtil},
+    castle_rights::{CastleRightsUtil, CASTLE_KING_DEST, CASTLE_ROOK_DEST, KING_SIDE, QUEEN_SIDE},
+    r#move::{Move, MoveUtil, EN_PASSANT, KING_SIDE_CASTLE},
+    square::{Square, SquareUtil},
+    tables::magics::{
+        get_between, get_bishop_moves, get_king_moves, get_knight_moves, get_line,
+        get_pawn_attacks, get_rook_moves,
+    },
+    Board, Color, Piece,
+};
+
+impl Board {
+    pub fn piece_attacks(&self, piece: Piece, sq: Square, color: Color) -> BitBoard {
+        return match piece {
+            Piece::Pawn => get_pawn_attacks(sq, color),
+            Piece::Knight => get_knight_moves(sq),
+            Piece::Bishop => get_bishop_moves(sq, self.combined),
+            Piece::Rook => get_rook_moves(sq, self.combined),
+            Piece::Queen => get_bishop_moves(sq, self.combined) | get_rook_moves(sq, self.combined),
+            Piece::King => get_king_moves(sq),
+        };
+    }
+    pub fn can_castle(&self, side: usize) -> bool {
+        let me = self.turn;
+        if self.checkers != 0 || !self.castle_rights.has(me, side) {
+            return false;
+        }
+        let king = self.king_square(me);
+        let rook = self.castle_rooks[me as usize][side];
+        let king_dest = CASTLE_KING_DEST[me as usize][side];
+        let rook_dest = CASTLE_ROOK_DEST[me as usize][side];
+        let king_path = get_between(king, king_dest) | (1 << king_dest);
+        let rook_path = get_between(rook, rook_dest) | (1 << rook_dest);
+        let blockers = self.combined ^ (1 << rook);
+        if (king_path | rook_path) & blockers & !(1 << king) != 0 {
+            return false;
+        }
+        if king_path & self.attacks[!me as usize] != 0 {
+            return false;
+        }
+        // in chess960 the castling rook may be the piece shielding the king's path
+        if self.chess960 {
+            let mut squares = king_path;
+            while squares != 0 {
+                if self.is_controlled_through(squares.pop_lsb(), !me, blockers) {
+                    return false;
+                }
+            }
+        }
+        return true;
+    }
+    // true when m could be generated here, ignoring whether it leaves the own king in check
+    pub fn is_pseudo_legal(&self, m: Move) -> bool {
+        let from = m.get_from();
+        let to = m.get_to();
+        let sp = m.get_sp();
+        let me = self.turn;
+        if from == to || sp > EN_PASSANT || !self.get_friendly_pieces().has_sq(from) {
+            return false;
+        }
+        let piece = self.piece_on(from).unwrap();
+        if m.is_castle() {
+            let side = if sp == KING_SIDE_CASTLE {
+                KING_SIDE
+            } else {
+                QUEEN_SIDE
+            };
+            return piece == Piece::King
+                && self.castle_rooks[me as usize][side] == to
+                && self.can_castle(side);
+        }
+        if self.get_friendly_pieces().has_sq(to) {
+            return false;
+        }
+        if piece != Piece::Pawn {
+            return sp == 0 && self.piece_attacks(piece, from, me).has_sq(to);
+        }
+
+        let promotion_rank = if me == Color::White { 7 } else { 0 };
+        if (to.get_rank() == promotion_rank) != m.is_promotion() {
+            return false;
+        }
+        if m.is_ep() {
+            return self.en_passant == Some(to) && get_pawn_attacks(from, me).has_sq(to);
+        }
+        if self.get_enemy_pieces().has_sq(to) {
+            return get_pawn_attacks(from, me).has_sq(to);
+        }
+        let (push, start_rank) = if me == Color::White {
+            (from + 8, 1)
+        } else {
+            (from.wrapping_sub(8), 6)
+        };
+        if to == push {
+            return true;
+        }
+        let double_push = if me == Color::White {
+            from + 16
+        } else {
+            from.wrapping_sub(16)
+        };
+        return from.get_rank() == start_rank && to == double_push && !self.combined.has_sq(push);
+    }
+    // assumes m is pseudo legal
+    pub fn is_legal(&self, m: Move) -> bool {
+        if m.is_castle() {
+            return true;
+        }
+        let me = self.turn;
+        let from = m.get_from();
+        let to = m.get_to();
+        let king = self.king_square(me);
+        if from == king {
+            return !self.is_controlled_through(to, !me, self.combined ^ (1 << from));
+        }
+        if m.is_ep() {
+            let after = self.make_move_new(m);
+            return !after.is_controlled(king, !me);
+        }
+        if self.checkers != 0 {
+            if self.checkers.count_ones() > 1 {
+                return false;
+            }
+            let checker = self.checkers.to_sq();
+            if !(get_between(checker, king) | self.checkers).has_sq(to) {
+                return false;
+            }
+        }
+        return !self.pinned.has_sq(from) || get_line(from, king).has_sq(to);
+    }
+    // assumes m is legal
+    pub fn gives_check(&self, m: Move) -> bool {
+        if m.get_sp() != 0 {
+            return self.make_move_new(m).checkers != 0;
+        }
+        let from = m.get_from();
+        let to = m.get_to();
+        if self.check_squares(self.piece_on(from).unwrap()).has_sq(to) {
+            return true;
+        }
+        return self.discovered_check_candidates().has_sq(from)
+            && !get_line(from, self.king_square(!self.turn)).has_sq(to);
+    }
+}
@@ -4,11 +4,18 @@ pub use board::Board;
 pub mod castle_rights;
 pub mod color;
 pub use color::Color;
+pub mod fen;
+pub mod legality;
 pub mod r#move;
+pub mod move_list;
 pub mod movegen;
 pub mod perft;
+pub mod perft_diff;
+pub mod perft_suite;
 pub mod perft_test;
 pub mod piece;
+pub mod san;
+pub mod san_test;
 pub use piece::Piece;
 pub mod square;
 pub mod tables;
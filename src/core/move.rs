@@ -1,6 +1,8 @@
+use std::fmt;
+
 use crate::core::{
     piece::Piece,
-    square::{str_to_square, Square, SquareUtil},
+    square::{parse_square, Square, SquareUtil},
 };
 pub type Move = u16;
 
@@ -46,8 +48,6 @@ impl SpecialFalg {
         };
     }
 }
-pub const KING_SIDE_CASTLE_MOVES: [Move; 2] = [20868, 24508];
-pub const QUEEN_SIDE_CASTLE_MOVES: [Move; 2] = [24708, 28348];
 
 pub const PROMOTION_PIECES: [Piece; 5] = [
     Piece::Pawn,
@@ -64,6 +64,7 @@ pub trait MoveUtil<T> {
     fn get_to(&self) -> Square;
     fn get_sp(&self) -> u16;
     fn to_str(&self) -> String;
+    fn to_uci(&self, chess960: bool) -> String;
     fn is_promotion(&self) -> bool;
     fn is_castle(&self) -> bool;
     fn is_ep(&self) -> bool;
@@ -79,10 +80,19 @@ impl MoveUtil<Move> for Move {
         return (self & SP_MASK) >> 12;
     }
     fn to_str(&self) -> String {
+        return self.to_uci(false);
+    }
+    fn to_uci(&self, chess960: bool) -> String {
+        // castles are stored as king takes rook, which is also the chess960 notation
+        let to = match self.get_sp() {
+            KING_SIDE_CASTLE if !chess960 => (self.get_from() & 56) | 6,
+            QUEEN_SIDE_CASTLE if !chess960 => (self.get_from() & 56) | 2,
+            _ => self.get_to(),
+        };
         return format!(
             "{}{}{}",
             self.get_from().to_str(),
-            self.get_to().to_str(),
+            to.to_str(),
             SP[self.get_sp() as usize]
         );
     }
@@ -101,22 +111,36 @@ impl MoveUtil<Move> for Move {
 pub fn new_move(from: Square, to: Square, sp: u16) -> Move {
     return (from as u16) | ((to as u16) << 6) | (sp << 12);
 }
-pub fn move_from_string(m: &str) -> Move {
-    let c = m.chars().collect::<Vec<char>>();
-    if c.len() == 4 {
-        return new_move(
-            str_to_square(format!("{}{}", c[0], c[1]).as_str()),
-            str_to_square(format!("{}{}", c[2], c[3]).as_str()),
-            0,
-        );
+pub fn move_from_string(m: &str) -> Result<Move, MoveParseError> {
+    let invalid = || MoveParseError::InvalidFormat(m.to_string());
+    if !m.is_ascii() || (m.len() != 4 && m.len() != 5) {
+        return Err(invalid());
+    }
+    let from = parse_square(&m[0..2]).ok_or_else(invalid)?;
+    let to = parse_square(&m[2..4]).ok_or_else(invalid)?;
+    let sp = if m.len() == 5 {
+        match SP[1..=QUEEN_PROMOTION as usize].iter().position(|&r| r == &m[4..]) {
+            Some(i) => i as u16 + 1,
+            None => return Err(invalid()),
+        }
     } else {
-        return new_move(
-            str_to_square(format!("{}{}", c[0], c[1]).as_str()),
-            str_to_square(format!("{}{}", c[2], c[3]).as_str()),
-            SP.iter()
-                .position(|&r| r == c[4].to_string().as_str())
-                .unwrap() as u16,
-        );
+        0
+    };
+    return Ok(new_move(from, to, sp));
+}
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum MoveParseError {
+    InvalidFormat(String),
+    IllegalMove(String),
+    AmbiguousMove(String),
+}
+impl fmt::Display for MoveParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MoveParseError::InvalidFormat(m) => write!(f, "'{}' is not a move in coordinate notation", m),
+            MoveParseError::IllegalMove(m) => write!(f, "'{}' is not a legal move in this position", m),
+            MoveParseError::AmbiguousMove(m) => write!(f, "'{}' matches more than one legal move", m),
+        }
     }
 }
 #[derive(PartialEq, Clone, Copy)]
@@ -0,0 +1,113 @@
+use std::{
+    mem::MaybeUninit,
+    ops::{Deref, DerefMut},
+};
+
+use crate::core::r#move::Move;
+
+// above the 218 legal moves of the richest known position, but a fen can hold more pieces than a
+// game ever reaches, so push still checks it
+pub const MAX_MOVES: usize = 256;
+
+// fixed capacity move buffer living on the stack, the slots past len are never read
+pub struct MoveList {
+    moves: [MaybeUninit<Move>; MAX_MOVES],
+    scores: [MaybeUninit<i32>; MAX_MOVES],
+    len: usize,
+}
+
+#[allow(unused)]
+impl MoveList {
+    #[inline]
+    pub fn new() -> MoveList {
+        return MoveList {
+            moves: [const { MaybeUninit::uninit() }; MAX_MOVES],
+            scores: [const { MaybeUninit::uninit() }; MAX_MOVES],
+            len: 0,
+        };
+    }
+    #[inline]
+    pub fn push(&mut self, m: Move) {
+        self.push_scored(m, 0);
+    }
+    #[inline]
+    pub fn push_scored(&mut self, m: Move, score: i32) {
+        assert!(self.len < MAX_MOVES, "more than {} moves in one position", MAX_MOVES);
+        self.moves[self.len] = MaybeUninit::new(m);
+        self.scores[self.len] = MaybeUninit::new(score);
+        self.len += 1;
+    }
+    #[inline]
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+    #[inline]
+    pub fn score(&self, i: usize) -> i32 {
+        assert!(i < self.len);
+        return unsafe { self.scores[i].assume_init() };
+    }
+    #[inline]
+    pub fn set_score(&mut self, i: usize, score: i32) {
+        assert!(i < self.len);
+        self.scores[i] = MaybeUninit::new(score);
+    }
+    // stable insertion sort on the scores, highest first
+    pub fn sort_by_score(&mut self) {
+        for i in 1..self.len {
+            let m = self.moves[i];
+            let score = self.score(i);
+            let mut j = i;
+            while j > 0 && self.score(j - 1) < score {
+                self.moves[j] = self.moves[j - 1];
+                self.scores[j] = self.scores[j - 1];
+                j -= 1;
+            }
+            self.moves[j] = m;
+            self.scores[j] = MaybeUninit::new(score);
+        }
+    }
+    // moves the best scored move of start.. to start, for lazy move picking
+    pub fn pick_best(&mut self, start: usize) -> Move {
+        let mut best = start;
+        for i in start + 1..self.len {
+            if self.score(i) > self.score(best) {
+                best = i;
+            }
+        }
+        self.moves.swap(start, best);
+        self.scores.swap(start, best);
+        return self[start];
+    }
+}
+
+impl Default for MoveList {
+    fn default() -> Self {
+        return MoveList::new();
+    }
+}
+
+impl Deref for MoveList {
+    type Target = [Move];
+    #[inline]
+    fn deref(&self) -> &[Move] {
+        return unsafe { std::slice::from_raw_parts(self.moves.as_ptr() as *const Move, self.len) };
+    }
+}
+
+// sorting through the slice reorders the moves only, use sort_by_score to keep scores attached
+impl DerefMut for MoveList {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut [Move] {
+        return unsafe {
+            std::slice::from_raw_parts_mut(self.moves.as_mut_ptr() as *mut Move, self.len)
+        };
+    }
+}
+
+impl<'a> IntoIterator for &'a MoveList {
+    type Item = Move;
+    type IntoIter = std::iter::Copied<std::slice::Iter<'a, Move>>;
+    fn into_iter(self) -> Self::IntoIter {
+        return self.iter().copied();
+    }
+}
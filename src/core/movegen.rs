@@ -1,21 +1,38 @@
 use crate::core::{
+    move_list::MoveList,
     bitboard::{BitBoard, BitBoardUtil, FULL_BITBOARD, RANK_BITBOARDS},
-    castle_rights::CastleRightsUtil,
+    castle_rights::{KING_SIDE, QUEEN_SIDE},
     r#move::{
-        new_move, Move, EN_PASSANT, KING_SIDE_CASTLE_MOVES, KNIGHT_PROMOTION, QUEEN_PROMOTION,
-        QUEEN_SIDE_CASTLE_MOVES,
+        new_move, MoveUtil, EN_PASSANT, KING_SIDE_CASTLE, KNIGHT_PROMOTION, QUEEN_PROMOTION,
+        QUEEN_SIDE_CASTLE, ROOK_PROMOTION,
     },
     square::{Square, SquareUtil},
     tables::magics::{
         get_between, get_bishop_moves, get_king_moves, get_knight_moves, get_line,
-        get_pawn_attacks, get_pawn_moves, get_rook_moves, KINGSIDE_CASTLE_SQUARES,
-        QUEENSIDE_CASTLE_SQUARES, QUEEN_SIDE_CASTLE_SQUARES_CONTROLLED,
+        get_pawn_attacks, get_pawn_moves, get_rook_moves,
     },
     Board, Color, Piece,
 };
 
-fn generate_king_moves(board: &Board, king: Square, movelist: &mut Vec<Move>) {
-    let mut moves = get_king_moves(king) & !board.get_friendly_pieces();
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum GenType {
+    All,
+    Captures,    // captures and queen promotions
+    Quiets,      // non captures and underpromotions
+    Evasions,    // every legal move while in check
+    QuietChecks, // non promoting quiet moves that give check, castling excluded
+}
+
+fn target_squares(board: &Board, gen_type: GenType) -> BitBoard {
+    return match gen_type {
+        GenType::Captures => board.get_enemy_pieces(),
+        GenType::Quiets | GenType::QuietChecks => !board.combined,
+        GenType::All | GenType::Evasions => !board.get_friendly_pieces(),
+    };
+}
+
+fn generate_king_moves(board: &Board, king: Square, targets: BitBoard, movelist: &mut MoveList) {
+    let mut moves = get_king_moves(king) & targets;
     let mut checkers = board.checkers;
     while checkers != 0 {
         let sq = checkers.pop_lsb();
@@ -25,28 +42,47 @@ fn generate_king_moves(board: &Board, king: Square, movelist: &mut Vec<Move>) {
     }
     while moves != 0 {
         let dest = moves.pop_lsb();
-        if !board.is_controlled(dest, !board.turn) {
+        if !board.attacks[!board.turn as usize].has_sq(dest) {
             movelist.push(new_move(king, dest, 0));
         }
     }
 }
 
-fn add_pawn_moves(from: Square, to: Square, movelist: &mut Vec<Move>) {
-    if to > 55 || to < 8 {
-        for i in KNIGHT_PROMOTION..=QUEEN_PROMOTION {
+fn add_pawn_moves(from: Square, to: Square, capture: bool, gen_type: GenType, movelist: &mut MoveList) {
+    if !(8..=55).contains(&to) {
+        let promotions = match gen_type {
+            GenType::Captures => QUEEN_PROMOTION..=QUEEN_PROMOTION,
+            GenType::Quiets => KNIGHT_PROMOTION..=ROOK_PROMOTION,
+            GenType::QuietChecks => return,
+            GenType::All | GenType::Evasions => KNIGHT_PROMOTION..=QUEEN_PROMOTION,
+        };
+        for i in promotions {
             movelist.push(new_move(from, to, i));
         }
         return;
     }
-    movelist.push(new_move(from, to, 0));
+    let wanted = match gen_type {
+        GenType::Captures => capture,
+        GenType::Quiets | GenType::QuietChecks => !capture,
+        GenType::All | GenType::Evasions => true,
+    };
+    if wanted {
+        movelist.push(new_move(from, to, 0));
+    }
 }
 
-fn generate_pawn_moves(board: &Board, movelist: &mut Vec<Move>, mut mask: BitBoard, king: Square) {
+fn generate_pawn_moves(
+    board: &Board,
+    movelist: &mut MoveList,
+    mut mask: BitBoard,
+    king: Square,
+    gen_type: GenType,
+) {
     let mut pawns = board.get_piece_bitboard(Piece::Pawn, board.turn);
     let mut enemy_pieces = board.get_enemy_pieces();
-    let ep = if board.en_passant.is_some() {
-        enemy_pieces |= 1 << board.en_passant.unwrap();
-        board.en_passant.unwrap()
+    let ep = if let Some(ep) = board.en_passant {
+        enemy_pieces |= 1 << ep;
+        ep
     } else {
         64
     };
@@ -73,10 +109,12 @@ fn generate_pawn_moves(board: &Board, movelist: &mut Vec<Move>, mut mask: BitBoa
                         }
                     }
                 }
-                movelist.push(new_move(pawn, target, EN_PASSANT));
+                if gen_type != GenType::Quiets {
+                    movelist.push(new_move(pawn, target, EN_PASSANT));
+                }
                 continue 'l;
             }
-            add_pawn_moves(pawn, target, movelist);
+            add_pawn_moves(pawn, target, true, gen_type, movelist);
         }
 
         //generate quiets
@@ -90,18 +128,17 @@ fn generate_pawn_moves(board: &Board, movelist: &mut Vec<Move>, mut mask: BitBoa
             continue;
         }
         while quiet_targets != 0 {
-            add_pawn_moves(pawn, quiet_targets.pop_lsb(), movelist);
+            add_pawn_moves(pawn, quiet_targets.pop_lsb(), false, gen_type, movelist);
         }
     }
 }
 
-fn generate_diagonal_moves(board: &Board, king: Square, mask: BitBoard, movelist: &mut Vec<Move>) {
+fn generate_diagonal_moves(board: &Board, king: Square, targets: BitBoard, movelist: &mut MoveList) {
     let mut pieces = (board.pieces[Piece::Bishop as usize] | board.pieces[Piece::Queen as usize])
         & board.get_friendly_pieces();
-    let available_squares = !board.get_friendly_pieces();
     while pieces != 0 {
         let piece = pieces.pop_lsb();
-        let mut dest = get_bishop_moves(piece, board.combined) & mask & available_squares;
+        let mut dest = get_bishop_moves(piece, board.combined) & targets;
         if board.pinned.has_sq(piece) {
             dest &= get_line(piece, king)
         }
@@ -111,13 +148,12 @@ fn generate_diagonal_moves(board: &Board, king: Square, mask: BitBoard, movelist
     }
 }
 
-fn generate_line_moves(board: &Board, king: Square, mask: BitBoard, movelist: &mut Vec<Move>) {
+fn generate_line_moves(board: &Board, king: Square, targets: BitBoard, movelist: &mut MoveList) {
     let mut pieces = (board.pieces[Piece::Rook as usize] | board.pieces[Piece::Queen as usize])
         & board.get_friendly_pieces();
-    let available_squares = !board.get_friendly_pieces();
     while pieces != 0 {
         let piece = pieces.pop_lsb();
-        let mut dest = get_rook_moves(piece, board.combined) & mask & available_squares;
+        let mut dest = get_rook_moves(piece, board.combined) & targets;
         if board.pinned.has_sq(piece) {
             dest &= get_line(piece, king)
         }
@@ -127,93 +163,82 @@ fn generate_line_moves(board: &Board, king: Square, mask: BitBoard, movelist: &m
     }
 }
 
-fn generate_knight_moves(board: &Board, movelist: &mut Vec<Move>, mask: BitBoard) {
+fn generate_knight_moves(board: &Board, movelist: &mut MoveList, targets: BitBoard) {
     let mut knights = board.get_piece_bitboard(Piece::Knight, board.turn) & !board.pinned;
-    let available_squares = !board.get_friendly_pieces();
     while knights != 0 {
         let knight = knights.pop_lsb();
-        let mut dest = get_knight_moves(knight) & mask & available_squares;
+        let mut dest = get_knight_moves(knight) & targets;
         while dest != 0 {
             movelist.push(new_move(knight, dest.pop_lsb(), 0));
         }
     }
 }
 
-fn generate_castle_moves(board: &Board, movelist: &mut Vec<Move>) {
-    if board.checkers != 0 {
-        return;
-    }
-    if board.castle_rights.has_king_side(board.turn)
-        && ((KINGSIDE_CASTLE_SQUARES[board.turn as usize] & board.combined) == 0)
-    {
-        let mut is_legal = true;
-        let mut squares = KINGSIDE_CASTLE_SQUARES[board.turn as usize];
-        while squares != 0 {
-            if board.is_controlled(squares.pop_lsb(), !board.turn) {
-                is_legal = false;
-                break;
-            }
-        }
-        if is_legal {
-            movelist.push(KING_SIDE_CASTLE_MOVES[board.turn as usize]);
-        }
-    }
-    if board.castle_rights.has_queen_side(board.turn)
-        && ((QUEENSIDE_CASTLE_SQUARES[board.turn as usize] & board.combined) == 0)
-    {
-        let mut is_legal = true;
-        let mut squares = QUEEN_SIDE_CASTLE_SQUARES_CONTROLLED[board.turn as usize];
-        while squares != 0 {
-            if board.is_controlled(squares.pop_lsb(), !board.turn) {
-                is_legal = false;
-                break;
-            }
-        }
-        if is_legal {
-            movelist.push(QUEEN_SIDE_CASTLE_MOVES[board.turn as usize]);
+fn generate_castle_moves(board: &Board, movelist: &mut MoveList) {
+    let me = board.turn;
+    for (side, sp) in [(KING_SIDE, KING_SIDE_CASTLE), (QUEEN_SIDE, QUEEN_SIDE_CASTLE)] {
+        if board.can_castle(side) {
+            movelist.push(new_move(board.king_square(me), board.castle_rooks[me as usize][side], sp));
         }
     }
 }
 
-pub fn generate_all_moves(board: &Board, movelist: &mut Vec<Move>) {
+pub fn generate_moves(board: &Board, gen_type: GenType, movelist: &mut MoveList) {
+    match gen_type {
+        GenType::Evasions if board.checkers == 0 => return,
+        GenType::QuietChecks => {
+            generate_quiet_checks(board, movelist);
+            return;
+        }
+        _ => {}
+    }
     let king = board.king_square(board.turn);
+    let targets = target_squares(board, gen_type);
 
     let mask = if board.checkers == 0 {
         FULL_BITBOARD
     } else if board.checkers.count_ones() == 1 {
-        get_between(
-            board.checkers.to_sq(),
-            board.get_piece_bitboard(Piece::King, board.turn).to_sq(),
-        ) | board.checkers
+        get_between(board.checkers.to_sq(), king) | board.checkers
     } else {
-        generate_king_moves(board, king, movelist);
+        generate_king_moves(board, king, targets, movelist);
         return;
     };
 
-    generate_castle_moves(board, movelist);
-    generate_pawn_moves(board, movelist, mask, king);
-    generate_diagonal_moves(board, king, mask, movelist);
-    generate_line_moves(board, king, mask, movelist);
-    generate_knight_moves(board, movelist, mask);
-    generate_king_moves(board, king, movelist);
+    if gen_type == GenType::All || gen_type == GenType::Quiets {
+        generate_castle_moves(board, movelist);
+    }
+    generate_pawn_moves(board, movelist, mask, king, gen_type);
+    generate_diagonal_moves(board, king, mask & targets, movelist);
+    generate_line_moves(board, king, mask & targets, movelist);
+    generate_knight_moves(board, movelist, mask & targets);
+    generate_king_moves(board, king, targets, movelist);
 }
-#[allow(unused)]
-pub fn generate_quiescence_moves(board: &Board, movelist: &mut Vec<Move>) {
-    let king = board.king_square(board.turn);
-
-    let mask = if board.checkers == 0 {
-        board.get_enemy_pieces()
-    } else if board.checkers.count_ones() == 1 {
-        get_between(
-            board.checkers.to_sq(),
-            board.get_piece_bitboard(Piece::King, board.turn).to_sq(),
-        ) | board.checkers
-    } else {
-        generate_king_moves(board, king, movelist);
+fn generate_quiet_checks(board: &Board, movelist: &mut MoveList) {
+    if board.checkers != 0 {
         return;
-    };
-    generate_pawn_moves(board, movelist, mask, king);
-    generate_diagonal_moves(board, king, mask, movelist);
-    generate_line_moves(board, king, mask, movelist);
-    generate_knight_moves(board, movelist, mask);
+    }
+    let mut quiets = MoveList::new();
+    generate_moves(board, GenType::Quiets, &mut quiets);
+    let enemy_king = board.king_square(!board.turn);
+    let discoverers = board.discovered_check_candidates();
+    let mut check_squares = [0; 6];
+    for piece in [Piece::Pawn, Piece::Knight, Piece::Bishop, Piece::Rook, Piece::Queen] {
+        check_squares[piece as usize] = board.check_squares(piece);
+    }
+    for m in &quiets {
+        if m.is_castle() || m.is_promotion() {
+            continue;
+        }
+        let from = m.get_from();
+        let to = m.get_to();
+        let piece = board.piece_on(from).unwrap();
+        if check_squares[piece as usize].has_sq(to)
+            || (discoverers.has_sq(from) && !get_line(from, enemy_king).has_sq(to))
+        {
+            movelist.push(m);
+        }
+    }
+}
+pub fn generate_all_moves(board: &Board, movelist: &mut MoveList) {
+    generate_moves(board, GenType::All, movelist);
 }
@@ -1,41 +1,73 @@
-use std::time::Instant;
+use std::{
+    sync::atomic::{AtomicU64, AtomicUsize, Ordering},
+    thread,
+    time::{Duration, Instant},
+};
 
 use crate::core::{
     board::Board,
+    move_list::MoveList,
     movegen::generate_all_moves,
     r#move::{Move, MoveUtil},
 };
 
-pub fn perft(board: &Board, depth: usize, moves: &mut Vec<Move>) -> usize {
+pub fn perft(board: &mut Board, depth: usize, moves: &mut Vec<Move>) -> usize {
     if depth == 0 {
         return 1;
     }
     let mut nodes = 0;
-    let mut all_moves = Vec::with_capacity(40);
+    let mut all_moves = MoveList::new();
     generate_all_moves(board, &mut all_moves);
     if depth == 1 {
         return all_moves.len();
     }
-    for m in all_moves {
-        let new_board = board.make_move_new(m);
+    for m in &all_moves {
+        let state = board.make_move(m);
         moves.push(m);
-        nodes += perft(&new_board, depth - 1, moves);
+        nodes += perft(board, depth - 1, moves);
         moves.pop();
+        board.unmake_move(m, &state);
     }
     return nodes;
 }
-pub fn start_perft(board: &Board, depth: usize) -> usize {
+// copy-make reference implementation, kept to compare against make/unmake
+pub fn perft_copy(board: &Board, depth: usize) -> usize {
     if depth == 0 {
         return 1;
     }
-    let start = Instant::now();
     let mut nodes = 0;
-    let mut all_moves = Vec::with_capacity(40);
+    let mut all_moves = MoveList::new();
     generate_all_moves(board, &mut all_moves);
-    for m in all_moves {
+    if depth == 1 {
+        return all_moves.len();
+    }
+    for m in &all_moves {
         let new_board = board.make_move_new(m);
-        let n = perft(&new_board, depth - 1, &mut vec![]);
-        println!("{}: {}", m.to_str(), n);
+        nodes += perft_copy(&new_board, depth - 1);
+    }
+    return nodes;
+}
+// node count below every root move
+pub fn divide(board: &Board, depth: usize) -> Vec<(Move, usize)> {
+    let mut board = *board;
+    let mut all_moves = MoveList::new();
+    generate_all_moves(&board, &mut all_moves);
+    let mut counts = Vec::with_capacity(all_moves.len());
+    for m in &all_moves {
+        let state = board.make_move(m);
+        counts.push((m, perft(&mut board, depth - 1, &mut vec![])));
+        board.unmake_move(m, &state);
+    }
+    return counts;
+}
+pub fn start_perft(board: &Board, depth: usize) -> usize {
+    if depth == 0 {
+        return 1;
+    }
+    let start = Instant::now();
+    let mut nodes = 0;
+    for (m, n) in divide(board, depth) {
+        println!("{}: {}", m.to_uci(board.chess960), n);
         nodes += n;
     }
 
@@ -44,3 +76,173 @@ pub fn start_perft(board: &Board, depth: usize) -> usize {
     println!("{} nodes per second", (nodes as f64 / secs).floor());
     return nodes;
 }
+// lockless perft hash, the key is stored xored with the data so a torn write never verifies
+struct PerftEntry {
+    key: AtomicU64,
+    data: AtomicU64,
+}
+pub struct PerftTable {
+    entries: Vec<PerftEntry>,
+    mask: usize,
+}
+impl PerftTable {
+    pub fn new(mb: usize) -> PerftTable {
+        // rounded down to a power of two so the table stays within the given megabytes
+        let count = (mb * 1024 * 1024 / std::mem::size_of::<PerftEntry>()).max(1);
+        let size = 1 << count.ilog2();
+        return PerftTable {
+            entries: (0..size)
+                .map(|_| PerftEntry { key: AtomicU64::new(0), data: AtomicU64::new(0) })
+                .collect(),
+            mask: size - 1,
+        };
+    }
+    // data is the node count above the depth byte, depth 0 is never stored so empty entries miss
+    #[inline]
+    fn probe(&self, hash: u64, depth: usize) -> Option<usize> {
+        let entry = &self.entries[hash as usize & self.mask];
+        let data = entry.data.load(Ordering::Relaxed);
+        if entry.key.load(Ordering::Relaxed) ^ data == hash && (data & 0xFF) as usize == depth {
+            return Some((data >> 8) as usize);
+        }
+        return None;
+    }
+    #[inline]
+    fn store(&self, hash: u64, depth: usize, nodes: usize) {
+        let entry = &self.entries[hash as usize & self.mask];
+        let data = ((nodes as u64) << 8) | depth as u64;
+        entry.key.store(hash ^ data, Ordering::Relaxed);
+        entry.data.store(data, Ordering::Relaxed);
+    }
+}
+pub fn perft_hashed(board: &mut Board, depth: usize, table: &PerftTable) -> usize {
+    if depth == 0 {
+        return 1;
+    }
+    let mut all_moves = MoveList::new();
+    generate_all_moves(board, &mut all_moves);
+    if depth == 1 {
+        return all_moves.len();
+    }
+    if let Some(nodes) = table.probe(board.hash, depth) {
+        return nodes;
+    }
+    let mut nodes = 0;
+    for m in &all_moves {
+        let state = board.make_move(m);
+        nodes += perft_hashed(board, depth - 1, table);
+        board.unmake_move(m, &state);
+    }
+    table.store(board.hash, depth, nodes);
+    return nodes;
+}
+// divide like start_perft, with the root moves handed out to threads one at a time and an
+// optional shared hash, hash_mb 0 disables it
+pub fn start_perft_parallel(board: &Board, depth: usize, threads: usize, hash_mb: usize) -> usize {
+    if depth == 0 {
+        return 1;
+    }
+    let start = Instant::now();
+    let mut all_moves = MoveList::new();
+    generate_all_moves(board, &mut all_moves);
+    let table = if hash_mb > 0 { Some(PerftTable::new(hash_mb)) } else { None };
+    let next = AtomicUsize::new(0);
+    let mut counts = vec![0; all_moves.len()];
+    thread::scope(|scope| {
+        let handles: Vec<_> = (0..threads.max(1))
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut board = *board;
+                    let mut results = vec![];
+                    loop {
+                        let i = next.fetch_add(1, Ordering::Relaxed);
+                        if i >= all_moves.len() {
+                            break;
+                        }
+                        let state = board.make_move(all_moves[i]);
+                        let n = match &table {
+                            Some(table) => perft_hashed(&mut board, depth - 1, table),
+                            None => perft(&mut board, depth - 1, &mut vec![]),
+                        };
+                        board.unmake_move(all_moves[i], &state);
+                        results.push((i, n));
+                    }
+                    return results;
+                })
+            })
+            .collect();
+        for handle in handles {
+            for (i, n) in handle.join().unwrap() {
+                counts[i] = n;
+            }
+        }
+    });
+    let mut nodes = 0;
+    for (i, m) in all_moves.iter().enumerate() {
+        println!("{}: {}", m.to_uci(board.chess960), counts[i]);
+        nodes += counts[i];
+    }
+
+    println!("{} nodes searched in {:?}", nodes, start.elapsed());
+    println!("{} nodes per second", nps(nodes, start.elapsed()));
+    return nodes;
+}
+pub fn nps(nodes: usize, elapsed: Duration) -> f64 {
+    return (nodes as f64 / (elapsed.as_micros().max(1) as f64 / 1000000.0)).floor();
+}
+pub fn compare_perft(board: &Board, depth: usize) -> bool {
+    let start = Instant::now();
+    let copy_nodes = perft_copy(board, depth);
+    let copy_time = start.elapsed();
+    println!("copy-make   {} nodes in {:?} ({} nps)", copy_nodes, copy_time, nps(copy_nodes, copy_time));
+
+    let start = Instant::now();
+    let mut unmake_board = *board;
+    let unmake_nodes = perft(&mut unmake_board, depth, &mut vec![]);
+    let unmake_time = start.elapsed();
+    println!("make/unmake {} nodes in {:?} ({} nps)", unmake_nodes, unmake_time, nps(unmake_nodes, unmake_time));
+
+    let passed = copy_nodes == unmake_nodes && unmake_board == *board;
+    if !passed {
+        println!("perft compare failed: node counts or restored board differ");
+    }
+    println!(
+        "speedup {:.2}x",
+        copy_time.as_secs_f64() / unmake_time.as_secs_f64().max(1e-9)
+    );
+    return passed;
+}
+// times the magic and pext sliding attack backends on the same perft, then restores the default
+#[cfg(all(feature = "pext", target_arch = "x86_64"))]
+pub fn compare_slider_backends(board: &Board, depth: usize) -> bool {
+    use crate::core::tables::magics::pext;
+
+    if !pext::is_supported() {
+        println!("info string this cpu has no bmi2, only the magic backend is available");
+        return false;
+    }
+    let mut results = vec![];
+    for (name, use_pext) in [("magic", false), ("pext ", true)] {
+        pext::set_enabled(use_pext);
+        let start = Instant::now();
+        let nodes = perft(&mut board.clone(), depth, &mut vec![]);
+        let elapsed = start.elapsed();
+        println!("{} {} nodes in {:?} ({} nps)", name, nodes, elapsed, nps(nodes, elapsed));
+        results.push((nodes, elapsed));
+    }
+    pext::init();
+    let passed = results[0].0 == results[1].0;
+    if !passed {
+        println!("backend compare failed: node counts differ");
+    }
+    println!(
+        "speedup {:.2}x",
+        results[0].1.as_secs_f64() / results[1].1.as_secs_f64().max(1e-9)
+    );
+    return passed;
+}
+#[cfg(not(all(feature = "pext", target_arch = "x86_64")))]
+pub fn compare_slider_backends(_board: &Board, _depth: usize) -> bool {
+    println!("info string built without the pext feature, only the magic backend is available");
+    return false;
+}
@@ -0,0 +1,243 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    io::{BufRead, BufReader, Write},
+    process::{Child, ChildStdin, ChildStdout, Command, Stdio},
+};
+
+use crate::core::{
+    board::Board,
+    r#move::{Move, MoveUtil},
+    perft::divide,
+};
+
+// where the reference divides come from
+pub enum DiffReference {
+    Stdin,
+    File(String),
+    Engine(String),
+}
+// a reference opened for the whole diff, it is asked again for every position the diff walks into
+enum Source {
+    Stdin,
+    // the listings of a file by position key
+    File(String, HashMap<String, BTreeMap<String, usize>>),
+    Engine(Child, ChildStdin, BufReader<ChildStdout>),
+}
+
+// two squares and an optional promotion piece, so a line like "perft 3" is not taken for a move
+fn looks_like_move(m: &str) -> bool {
+    let b = m.as_bytes();
+    let square = |f: u8, r: u8| (b'a'..=b'h').contains(&f) && (b'1'..=b'8').contains(&r);
+    return (b.len() == 4 || b.len() == 5) && square(b[0], b[1]) && square(b[2], b[3]);
+}
+// "e2e4: 600" or "e2e4 600" per line, anything else such as a node total is skipped
+pub fn parse_divide(text: &str) -> BTreeMap<String, usize> {
+    let mut counts = BTreeMap::new();
+    for line in text.lines() {
+        let parts: Vec<&str> = line
+            .split(|c: char| c == ':' || c.is_whitespace())
+            .filter(|p| !p.is_empty())
+            .collect();
+        if let [m, n] = parts[..]
+            && let Ok(n) = n.parse::<usize>()
+            && looks_like_move(m)
+        {
+            counts.insert(m.to_string(), n);
+        }
+    }
+    return counts;
+}
+// the move counters are left out, a reference tool may not track them the same way
+fn position_key(fen: &str) -> String {
+    return fen.split_whitespace().take(4).collect::<Vec<&str>>().join(" ");
+}
+// listings that follow a "position fen <fen>" line belong to that position, the ones before the
+// first such line to the root
+fn parse_divide_file(text: &str, root: &Board) -> HashMap<String, BTreeMap<String, usize>> {
+    let mut listings = HashMap::new();
+    let mut key = position_key(&root.to_fen());
+    let mut listing = String::new();
+    for line in text.lines() {
+        if let Some(fen) = line.trim().strip_prefix("position fen ") {
+            listings.insert(key, parse_divide(&listing));
+            key = position_key(fen);
+            listing.clear();
+            continue;
+        }
+        listing.push_str(line);
+        listing.push('\n');
+    }
+    listings.insert(key, parse_divide(&listing));
+    listings.retain(|_, counts| !counts.is_empty());
+    return listings;
+}
+// one listing from stdin, ended by an empty line or a "nodes" total line
+fn read_divide_from_stdin() -> BTreeMap<String, usize> {
+    let mut text = String::new();
+    for line in std::io::stdin().lock().lines() {
+        let Ok(line) = line else {
+            break;
+        };
+        if line.trim().is_empty() || line.to_lowercase().contains("nodes") {
+            break;
+        }
+        text.push_str(&line);
+        text.push('\n');
+    }
+    return parse_divide(&text);
+}
+impl Source {
+    fn open(reference: &DiffReference, board: &Board) -> Result<Source, String> {
+        match reference {
+            DiffReference::Stdin => return Ok(Source::Stdin),
+            DiffReference::File(path) => {
+                let text = fs::read_to_string(path).map_err(|e| format!("could not read {}: {}", path, e))?;
+                return Ok(Source::File(path.clone(), parse_divide_file(&text, board)));
+            }
+            DiffReference::Engine(path) => {
+                let mut engine = Command::new(path)
+                    .stdin(Stdio::piped())
+                    .stdout(Stdio::piped())
+                    .spawn()
+                    .map_err(|e| format!("could not start {}: {}", path, e))?;
+                let mut stdin = engine.stdin.take().unwrap();
+                let stdout = BufReader::new(engine.stdout.take().unwrap());
+                if board.chess960 {
+                    writeln!(stdin, "setoption name UCI_Chess960 value true").map_err(|e| e.to_string())?;
+                }
+                return Ok(Source::Engine(engine, stdin, stdout));
+            }
+        }
+    }
+    fn divide(&mut self, board: &Board, depth: usize) -> Result<BTreeMap<String, usize>, String> {
+        match self {
+            Source::Stdin => {
+                println!(
+                    "paste the reference divide of {} at depth {}, end with an empty line",
+                    board.to_fen(),
+                    depth
+                );
+                return Ok(read_divide_from_stdin());
+            }
+            Source::File(path, listings) => {
+                return listings
+                    .get(&position_key(&board.to_fen()))
+                    .cloned()
+                    .ok_or(format!("{} has no divide of {}", path, board.to_fen()));
+            }
+            // the answer to isready comes once the perft is done, everything before it is the
+            // divide
+            Source::Engine(_, stdin, stdout) => {
+                writeln!(stdin, "position fen {}", board.to_fen()).map_err(|e| e.to_string())?;
+                writeln!(stdin, "go perft {}", depth).map_err(|e| e.to_string())?;
+                writeln!(stdin, "isready").map_err(|e| e.to_string())?;
+                let mut text = String::new();
+                loop {
+                    let mut line = String::new();
+                    if stdout.read_line(&mut line).map_err(|e| e.to_string())? == 0 {
+                        return Err("the reference engine exited".to_string());
+                    }
+                    if line.trim() == "readyok" {
+                        return Ok(parse_divide(&text));
+                    }
+                    text.push_str(&line);
+                }
+            }
+        }
+    }
+    fn close(self) {
+        if let Source::Engine(mut engine, mut stdin, _) = self {
+            let _ = writeln!(stdin, "quit");
+            drop(stdin);
+            let _ = engine.wait();
+        }
+    }
+}
+// compares our divide with the reference one and walks into the first move whose count differs,
+// down to the position where a move is missing or extra or a single leaf is counted differently
+pub fn diff_divide(board: &Board, depth: usize, reference: &DiffReference) -> bool {
+    let mut source = match Source::open(reference, board) {
+        Ok(source) => source,
+        Err(e) => {
+            println!("info string {}", e);
+            return false;
+        }
+    };
+    let result = walk_divide(board, depth, &mut source);
+    source.close();
+    return result;
+}
+fn walk_divide(board: &Board, depth: usize, source: &mut Source) -> bool {
+    let mut board = *board;
+    let mut depth = depth;
+    let mut path: Vec<Move> = vec![];
+    // the position and move of the deepest count difference so far
+    let mut smallest: Option<(String, String)> = None;
+    while depth > 0 {
+        let expected = match source.divide(&board, depth) {
+            Ok(expected) => expected,
+            Err(e) => {
+                println!("info string {}", e);
+                if let Some((fen, uci)) = smallest {
+                    println!("smallest mismatch: position fen {} move {}", fen, uci);
+                }
+                return false;
+            }
+        };
+        let found = divide(&board, depth);
+        let ours: BTreeMap<String, usize> = found
+            .iter()
+            .map(|&(m, n)| (m.to_uci(board.chess960), n))
+            .collect();
+        // missing moves are in the reference only, extra moves only in our generator
+        let missing: Vec<&str> = expected
+            .keys()
+            .filter(|m| !ours.contains_key(*m))
+            .map(|m| m.as_str())
+            .collect();
+        let extra: Vec<&str> = ours
+            .keys()
+            .filter(|m| !expected.contains_key(*m))
+            .map(|m| m.as_str())
+            .collect();
+        if !missing.is_empty() || !extra.is_empty() {
+            let line: Vec<String> = path.iter().map(|m| m.to_uci(board.chess960)).collect();
+            println!("position fen {}", board.to_fen());
+            println!("moves from the root: {}", line.join(" "));
+            println!("missing moves: {}", missing.join(" "));
+            println!("extra moves: {}", extra.join(" "));
+            return false;
+        }
+        let Some(&(m, n)) = found
+            .iter()
+            .find(|(m, n)| expected[&m.to_uci(board.chess960)] != *n)
+        else {
+            println!("divide matches the reference at depth {}", depth);
+            // the reference contradicts its own count one level up
+            if let Some((fen, uci)) = smallest {
+                println!("smallest mismatch: position fen {} move {}", fen, uci);
+            }
+            return path.is_empty();
+        };
+        let uci = m.to_uci(board.chess960);
+        println!(
+            "{} differs at depth {}: expected {} found {}",
+            uci, depth, expected[&uci], n
+        );
+        if depth == 1 {
+            println!(
+                "the reference counts more than one leaf below {} in {}",
+                uci,
+                board.to_fen()
+            );
+            println!("smallest mismatch: position fen {} move {}", board.to_fen(), uci);
+            return false;
+        }
+        smallest = Some((board.to_fen(), uci));
+        board.make_move(m);
+        path.push(m);
+        depth -= 1;
+    }
+    return false;
+}
@@ -0,0 +1,121 @@
+use std::{fs, time::Instant};
+
+use crate::core::{
+    board::Board,
+    perft::{divide, nps, perft},
+    r#move::MoveUtil,
+};
+
+// one epd line, the expected node count for every listed depth
+pub struct PerftCase {
+    pub fen: String,
+    pub results: Vec<(usize, usize)>,
+}
+pub struct SuiteResult {
+    pub failed: Vec<usize>,
+    pub nodes: usize,
+    pub skipped: usize,
+}
+
+// "<fen> ;D1 20 ;D2 400", other opcodes are ignored
+pub fn parse_epd_line(line: &str) -> Result<PerftCase, String> {
+    let mut fields = line.split(';');
+    let fen = fields.next().unwrap_or("").trim().to_string();
+    if let Err(e) = Board::from_fen(&fen) {
+        return Err(format!("invalid fen {}: {}", fen, e));
+    }
+    let mut results = vec![];
+    for field in fields {
+        let parts: Vec<&str> = field.split_whitespace().collect();
+        let (Some(opcode), Some(value)) = (parts.first(), parts.get(1)) else {
+            continue;
+        };
+        let Some(depth) = opcode.strip_prefix('D') else {
+            continue;
+        };
+        match (depth.parse::<usize>(), value.parse::<usize>()) {
+            (Ok(depth), Ok(nodes)) => results.push((depth, nodes)),
+            _ => return Err(format!("invalid perft result {} in {}", field.trim(), fen)),
+        }
+    }
+    results.sort();
+    return Ok(PerftCase { fen, results });
+}
+pub fn parse_epd(text: &str) -> Result<Vec<PerftCase>, String> {
+    let mut cases = vec![];
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        cases.push(parse_epd_line(line).map_err(|e| format!("line {}: {}", i + 1, e))?);
+    }
+    return Ok(cases);
+}
+// runs the depths of every case in order and stops a case at its first wrong count, printing
+// the divide of that depth so it can be compared against a reference engine
+pub fn run_perft_suite(cases: &[PerftCase], max_depth: usize) -> SuiteResult {
+    let mut result = SuiteResult { failed: vec![], nodes: 0, skipped: 0 };
+    for (i, case) in cases.iter().enumerate() {
+        println!("position fen {}", case.fen);
+        let start = Instant::now();
+        let mut nodes = 0;
+        let mut board = Board::from_fen(&case.fen).unwrap();
+        for &(depth, expected) in &case.results {
+            if depth > max_depth {
+                result.skipped += 1;
+                continue;
+            }
+            let found = perft(&mut board, depth, &mut vec![]);
+            nodes += found;
+            if found != expected {
+                println!(
+                    "perft test case {} failed at depth {}: expected {} found {}",
+                    i + 1,
+                    depth,
+                    expected,
+                    found
+                );
+                if depth > 0 {
+                    println!("divide at depth {}:", depth);
+                    for (m, n) in divide(&board, depth) {
+                        println!("{}: {}", m.to_uci(board.chess960), n);
+                    }
+                }
+                result.failed.push(i);
+                break;
+            }
+        }
+        println!(
+            "perft test case {} in {:?} nodes {} nps {}",
+            i + 1,
+            start.elapsed(),
+            nodes,
+            nps(nodes, start.elapsed())
+        );
+        result.nodes += nodes;
+    }
+    return result;
+}
+pub fn start_perft_suite(path: &str, max_depth: usize) -> bool {
+    let cases = match fs::read_to_string(path).map_err(|e| e.to_string()).and_then(|t| parse_epd(&t)) {
+        Ok(cases) => cases,
+        Err(e) => {
+            println!("info string could not read perft suite {}: {}", path, e);
+            return false;
+        }
+    };
+    let start = Instant::now();
+    let result = run_perft_suite(&cases, max_depth);
+    println!(
+        "perft suite {}: {} of {} cases passed in {:?}, {} depths skipped",
+        path,
+        cases.len() - result.failed.len(),
+        cases.len(),
+        start.elapsed(),
+        result.skipped
+    );
+    println!("total nodes: {}", result.nodes);
+    println!("nodes per second: {}", nps(result.nodes, start.elapsed()));
+    return result.failed.is_empty();
+}
@@ -1,12 +1,20 @@
-use std::time::Instant;
+use std::{mem::discriminant, time::Instant};
 
-use crate::core::{board::Board, perft::perft};
+use crate::core::{
+    board::Board,
+    fen::{FenError, START_POSITION_FEN},
+    move_list::MoveList,
+    movegen::{generate_all_moves, generate_moves, GenType},
+    perft::nps,
+    perft_suite::{run_perft_suite, PerftCase},
+    r#move::{Move, MoveParseError, MoveUtil},
+};
 #[derive(Clone, Copy)]
 struct PerftTestCase<'a> {
     fen: &'a str,
     results: [usize; 8],
 }
-const TEST_CASES: [PerftTestCase; 25] = [
+const TEST_CASES: [PerftTestCase; 29] = [
     PerftTestCase {
         fen: "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
         results: [1, 20, 400, 8902, 197281, 4865609, 119060324, 3195901860],
@@ -107,69 +115,322 @@ const TEST_CASES: [PerftTestCase; 25] = [
         fen: "8/5k2/8/5N2/5Q2/2K5/8/8 w - - 0 1",
         results: [1, 37, 183, 6559, 23527, 811573, 3114998, 0],
     },
+    // chess960
+    PerftTestCase {
+        fen: "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9",
+        results: [1, 21, 528, 12189, 326672, 8146062, 227689589, 0],
+    },
+    PerftTestCase {
+        fen: "2nnrbkr/p1qppppp/8/1ppb4/6PP/3PP3/PPP2P2/BQNNRBKR w HEhe - 1 9",
+        results: [1, 21, 807, 18002, 667366, 16253601, 0, 0],
+    },
+    PerftTestCase {
+        fen: "b1q1rrkb/pppppppp/3nn3/8/P7/1PPP4/4PPPP/BQNNRKRB w GE - 1 9",
+        results: [1, 20, 479, 10471, 273318, 6417013, 0, 0],
+    },
+    PerftTestCase {
+        fen: "1nbbnrkr/p1p1ppp1/3p4/1p3P1p/3Pq2P/8/PPP1P1P1/QNBBNRKR w HFhf - 0 9",
+        results: [1, 28, 1120, 31058, 1171749, 0, 0, 0],
+    },
+];
+const INVALID_FENS: [(&str, FenError); 16] = [
+    ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP w KQkq", FenError::MissingFields(3)),
+    ("rnbqkbnr/pppppppp/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", FenError::WrongRankCount(7)),
+    ("rnbqkbnr/pppppppp/9/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", FenError::InvalidRankLength(6)),
+    ("rnbqkbnr/ppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", FenError::InvalidRankLength(7)),
+    ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNX w KQkq - 0 1", FenError::InvalidPiece('X')),
+    ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR x KQkq - 0 1", FenError::InvalidSideToMove(String::new())),
+    ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQxq - 0 1", FenError::InvalidCastleRights(String::new())),
+    ("rnbq1bnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQ - 0 1", FenError::KingCount(1, 0)),
+    ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNP w KQkq - 0 1", FenError::PawnOnBackRank),
+    ("4k3/8/8/8/8/8/4r3/4K3 b - - 0 1", FenError::SideNotToMoveInCheck),
+    ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBN1 w KQkq - 0 1", FenError::InconsistentCastleRights('K')),
+    ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq e6 0 1", FenError::InconsistentEnPassant(String::new())),
+    ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq j9 0 1", FenError::InvalidEnPassant(String::new())),
+    ("4k3/8/8/8/8/8/8/4K3 b - a1 0 1", FenError::InvalidEnPassant(String::new())),
+    ("4k3/8/8/8/8/8/8/4K3 w - h8 0 1", FenError::InvalidEnPassant(String::new())),
+    ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - x 1", FenError::InvalidHalfmoveClock(String::new())),
 ];
-static mut NODES: usize = 0;
-pub fn test_perft() {
+const INVALID_MOVES: [(&str, &str, MoveParseError); 8] = [
+    (START_POSITION_FEN, "e2", MoveParseError::InvalidFormat(String::new())),
+    (START_POSITION_FEN, "e2e9", MoveParseError::InvalidFormat(String::new())),
+    (START_POSITION_FEN, "e7e8k", MoveParseError::InvalidFormat(String::new())),
+    (START_POSITION_FEN, "e3e4", MoveParseError::IllegalMove(String::new())),
+    (START_POSITION_FEN, "e2e5", MoveParseError::IllegalMove(String::new())),
+    (START_POSITION_FEN, "e1g1", MoveParseError::IllegalMove(String::new())),
+    ("4k3/1P6/8/8/8/8/K7/8 w - - 0 1", "b7b8", MoveParseError::IllegalMove(String::new())),
+    ("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1", "b5b6", MoveParseError::IllegalMove(String::new())),
+];
+// the built-in cases through the suite runner, depths above max_depth are skipped
+pub fn test_perft(max_depth: usize) -> bool {
     let start = Instant::now();
-    let mut result = [true; TEST_CASES.len()];
-    let mut passed = true;
-    for i in 0..TEST_CASES.len() {
-        println!("position fen {}", TEST_CASES[i].fen);
-        let res = test_case(i);
-        passed &= res;
-        if !res {
-            result[i] = false;
+    let cases: Vec<PerftCase> = TEST_CASES
+        .iter()
+        .map(|case| PerftCase {
+            fen: case.fen.to_string(),
+            results: case.results.iter().copied().enumerate().filter(|&(_, n)| n != 0).collect(),
+        })
+        .collect();
+    let result = run_perft_suite(&cases, max_depth);
+    if result.failed.is_empty() {
+        println!("perft test completed successfully in {:?}", start.elapsed());
+        println!("total nodes: {}", result.nodes);
+        println!("nodes per second: {}", nps(result.nodes, start.elapsed()));
+    } else {
+        for &i in &result.failed {
+            println!("perft failed test {} fen {}", i + 1, TEST_CASES[i].fen);
         }
     }
-    if passed {
-        let mut total_nodes = 0;
-        for i in 0..TEST_CASES.len(){
-            for j in 0..TEST_CASES[i].results.len(){
-                total_nodes += TEST_CASES[i].results[j];
+    return result.failed.is_empty();
+}
+pub fn test_fen() -> bool {
+    let mut passed = true;
+    for case in TEST_CASES {
+        let board = Board::from_fen(case.fen).unwrap();
+        if board.to_fen() != case.fen {
+            println!("fen round-trip failed: {} -> {}", case.fen, board.to_fen());
+            passed = false;
+        }
+        let mut moves = MoveList::new();
+        generate_all_moves(&board, &mut moves);
+        for m in &moves {
+            let new_board = board.make_move_new(m);
+            let fen = new_board.to_fen();
+            let parsed = Board::from_fen(&fen);
+            if parsed.as_ref().map(|b| (b.to_fen(), b.hash)) != Ok((fen.clone(), new_board.hash)) {
+                println!(
+                    "fen round-trip failed after {} in {}: {}",
+                    m.to_str(),
+                    case.fen,
+                    fen
+                );
+                passed = false;
             }
         }
-        println!("perft test completed successfully in {:?}", start.elapsed());
-        println!("total nodes: {}", total_nodes);
-        println!("nodes per second: {}", (total_nodes * 1000) / (start.elapsed().as_millis() as usize));
-
-    } else {
-        for i in 0..result.len() {
-            if !result[i] {
-                println!("perft failed test {} fen {}", i, TEST_CASES[i].fen);
+    }
+    for (fen, error) in INVALID_FENS {
+        match Board::from_fen(fen) {
+            Err(e) if discriminant(&e) == discriminant(&error) => {}
+            Err(e) => {
+                println!("fen {} failed with \"{}\" instead of {:?}", fen, e, error);
+                passed = false;
+            }
+            Ok(_) => {
+                println!("fen {} should fail with {:?}", fen, error);
+                passed = false;
             }
         }
     }
+    if passed {
+        println!("fen test completed successfully");
+    }
+    return passed;
 }
-pub fn test_case(i: usize) -> bool {
+pub fn test_move_parsing() -> bool {
     let mut passed = true;
-    unsafe {
-        NODES = 0;
-    }
-    let start = Instant::now();
-    let case = TEST_CASES[i];
-    let board = Board::from_fen(case.fen);
-    for depth in 0..case.results.len() {
-        if case.results[depth] != 0 {
-            let mut moves = Vec::with_capacity(10);
-            let res = perft(&board, depth, &mut moves);
-            unsafe {
-                NODES += NODES + res;
+    for case in TEST_CASES {
+        let board = Board::from_fen(case.fen).unwrap();
+        let mut moves = MoveList::new();
+        generate_all_moves(&board, &mut moves);
+        for m in &moves {
+            if board.parse_move(&m.to_uci(board.chess960)) != Ok(m) {
+                println!("move {} in {} did not parse back", m.to_uci(board.chess960), case.fen);
+                passed = false;
             }
-            if res != case.results[depth] {
+        }
+    }
+    for (fen, mv, error) in INVALID_MOVES {
+        let board = Board::from_fen(fen).unwrap();
+        match board.parse_move(mv) {
+            Err(e) if discriminant(&e) == discriminant(&error) => {}
+            res => {
+                println!("move {} in {} gave {:?} instead of {:?}", mv, fen, res, error);
                 passed = false;
             }
         }
     }
+    if passed {
+        println!("move parsing test completed successfully");
+    }
+    return passed;
+}
+fn verify_unmake(board: &mut Board, depth: usize) -> bool {
+    if depth == 0 {
+        return true;
+    }
+    let mut moves = MoveList::new();
+    generate_all_moves(board, &mut moves);
+    for m in &moves {
+        let before = *board;
+        let copy = board.make_move_new(m);
+        let state = board.make_move(m);
+        if *board != copy {
+            println!("make_move {} differs from copy-make in {}", m.to_str(), before.to_fen());
+            return false;
+        }
+        let passed = verify_unmake(board, depth - 1);
+        board.unmake_move(m, &state);
+        if !passed {
+            return false;
+        }
+        if *board != before {
+            println!("unmake_move {} did not restore {}", m.to_str(), before.to_fen());
+            return false;
+        }
+    }
+    return true;
+}
+const REPETITIONS: [(&str, &str, bool); 4] = [
+    (START_POSITION_FEN, "g1f3 g8f6 f3g1 f6g8", true),
+    (START_POSITION_FEN, "g1f3 g8f6 f3g1", false),
+    (START_POSITION_FEN, "e2e4 g8f6 g1f3 f6g8 f3g1", false),
+    ("4k3/8/8/8/8/8/4P3/4K2R w K - 0 1", "h1h2 e8d8 h2h1 d8e8", false),
+];
+pub fn test_unmake() -> bool {
+    let mut passed = true;
+    for case in TEST_CASES {
+        let mut board = Board::from_fen(case.fen).unwrap();
+        passed &= verify_unmake(&mut board, 3);
+    }
+    for (fen, moves, expected) in REPETITIONS {
+        let mut board = Board::from_fen(fen).unwrap();
+        let mut states = Vec::new();
+        for m in moves.split_whitespace() {
+            states.push(board.make_move_from_str(m).unwrap());
+        }
+        if board.is_repetition(&states) != expected {
+            println!("repetition after {} in {} should be {}", moves, fen, expected);
+            passed = false;
+        }
+    }
+    if passed {
+        println!("unmake test completed successfully");
+    }
+    return passed;
+}
+fn sorted_moves(board: &Board, gen_type: GenType) -> Vec<Move> {
+    let mut moves = MoveList::new();
+    generate_moves(board, gen_type, &mut moves);
+    let mut moves = moves.to_vec();
+    moves.sort();
+    return moves;
+}
+fn verify_generators(board: &mut Board, depth: usize) -> bool {
+    let all = sorted_moves(board, GenType::All);
+    let captures = sorted_moves(board, GenType::Captures);
+    let quiets = sorted_moves(board, GenType::Quiets);
+    let evasions = sorted_moves(board, GenType::Evasions);
+    let quiet_checks = sorted_moves(board, GenType::QuietChecks);
 
-    unsafe {
-        let nps = ((NODES as f64) / (start.elapsed().as_micros() as f64)) * 1000_000.0;
-        println!(
-            "perft test case {} in {:?} nodes {} nps {}",
-            i + 1,
-            start.elapsed(),
-            NODES,
-            nps.floor()
-        );
+    let mut union = [captures.clone(), quiets.clone()].concat();
+    union.sort();
+    let expected_evasions = if board.checkers != 0 { all.clone() } else { vec![] };
+    let expected_checks = if board.checkers != 0 {
+        vec![]
+    } else {
+        quiets
+            .iter()
+            .copied()
+            .filter(|m| !m.is_castle() && !m.is_promotion() && board.make_move_new(*m).checkers != 0)
+            .collect()
+    };
+    let mut passed = true;
+    if union != all {
+        println!("captures and quiets do not add up to all moves in {}", board.to_fen());
+        passed = false;
+    }
+    if evasions != expected_evasions {
+        println!("evasions differ from all moves in {}", board.to_fen());
+        passed = false;
+    }
+    if quiet_checks != expected_checks {
+        println!("quiet checks are wrong in {}", board.to_fen());
+        passed = false;
+    }
+    if !passed || depth == 0 {
+        return passed;
+    }
+    for m in all {
+        let state = board.make_move(m);
+        let res = verify_generators(board, depth - 1);
+        board.unmake_move(m, &state);
+        if !res {
+            return false;
+        }
+    }
+    return true;
+}
+pub fn test_generators() -> bool {
+    let mut passed = true;
+    for case in TEST_CASES {
+        let mut board = Board::from_fen(case.fen).unwrap();
+        passed &= verify_generators(&mut board, 2);
+    }
+    if passed {
+        println!("move generator test completed successfully");
+    }
+    return passed;
+}
+// xorshift, only used to draw move candidates for the legality fuzzer
+fn next_random(state: &mut u64) -> u64 {
+    *state ^= *state << 13;
+    *state ^= *state >> 7;
+    *state ^= *state << 17;
+    return *state;
+}
+fn verify_legality(board: &mut Board, depth: usize, candidates: &mut Vec<Move>, seed: &mut u64) -> bool {
+    let mut moves = MoveList::new();
+    generate_all_moves(board, &mut moves);
+    candidates.extend(moves.iter());
+    for _ in 0..64 {
+        candidates.push(next_random(seed) as Move);
+    }
+    if candidates.len() > 1024 {
+        candidates.drain(..candidates.len() - 1024);
+    }
+    for &m in candidates.iter() {
+        let legal = board.is_pseudo_legal(m) && board.is_legal(m);
+        if legal != moves.contains(&m) {
+            println!(
+                "legality of {} (sp {}) in {} is {}",
+                m.to_str(),
+                m.get_sp(),
+                board.to_fen(),
+                legal
+            );
+            return false;
+        }
+    }
+    for m in &moves {
+        if board.gives_check(m) != (board.make_move_new(m).checkers != 0) {
+            println!("gives_check of {} in {} is wrong", m.to_str(), board.to_fen());
+            return false;
+        }
+    }
+    if depth == 0 {
+        return true;
+    }
+    for m in &moves {
+        let state = board.make_move(m);
+        let passed = verify_legality(board, depth - 1, candidates, seed);
+        board.unmake_move(m, &state);
+        if !passed {
+            return false;
+        }
+    }
+    return true;
+}
+pub fn test_legality() -> bool {
+    let mut passed = true;
+    let mut seed = 0x9E3779B97F4A7C15;
+    for case in TEST_CASES {
+        let mut board = Board::from_fen(case.fen).unwrap();
+        // moves of sibling and cousin positions make the most interesting candidates
+        let mut candidates = Vec::new();
+        passed &= verify_legality(&mut board, 2, &mut candidates, &mut seed);
+    }
+    if passed {
+        println!("legality test completed successfully");
     }
     return passed;
 }
@@ -18,10 +18,15 @@ const ALL_PIECES: [Piece; 6] = [
 ];
 const SLIDE: [bool; 6] = [false, false, true, true, true, false];
 pub const PAWN_TYPE_VALUE: usize = 0;
+#[allow(unused)]
 pub const KNIGHT_TYPE_VALUE: usize = 1;
+#[allow(unused)]
 pub const BISHOP_TYPE_VALUE: usize = 2;
+#[allow(unused)]
 pub const ROOK_TYPE_VALUE: usize = 3;
+#[allow(unused)]
 pub const QUEEN_TYPE_VALUE: usize = 4;
+#[allow(unused)]
 pub const KING_TYPE_VALUE: usize = 5;
 impl Piece {
     pub fn from_index(i: usize) -> Piece {
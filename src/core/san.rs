@@ -0,0 +1,169 @@
+use crate::core::{
+    bitboard::BitBoardUtil,
+    move_list::MoveList,
+    movegen::generate_all_moves,
+    r#move::{Move, MoveParseError, MoveUtil, KING_SIDE_CASTLE, PROMOTION_PIECES, QUEEN_SIDE_CASTLE},
+    square::{parse_square, SquareUtil},
+    Board, Piece,
+};
+
+const SAN_PIECE_LETTERS: [char; 6] = ['P', 'N', 'B', 'R', 'Q', 'K'];
+const FILE_LETTERS: [char; 8] = ['a', 'b', 'c', 'd', 'e', 'f', 'g', 'h'];
+
+fn piece_from_letter(c: char) -> Option<Piece> {
+    return SAN_PIECE_LETTERS
+        .iter()
+        .position(|&l| l == c)
+        .map(Piece::from_index);
+}
+
+impl Board {
+    pub fn move_to_san(&self, m: Move) -> String {
+        let mut san = String::new();
+        let piece = self.piece_on(m.get_from()).unwrap();
+        let from = m.get_from();
+        let to = m.get_to();
+
+        if m.get_sp() == KING_SIDE_CASTLE {
+            san.push_str("O-O");
+        } else if m.get_sp() == QUEEN_SIDE_CASTLE {
+            san.push_str("O-O-O");
+        } else {
+            let is_capture = m.is_ep() || self.get_enemy_pieces().has_sq(to);
+            if piece == Piece::Pawn {
+                if is_capture {
+                    san.push(FILE_LETTERS[from.get_file() as usize]);
+                }
+            } else {
+                san.push(SAN_PIECE_LETTERS[piece as usize]);
+                let mut moves = MoveList::new();
+                generate_all_moves(self, &mut moves);
+                let mut ambiguous = false;
+                let mut same_file = false;
+                let mut same_rank = false;
+                for other in &moves {
+                    if other.get_to() != to
+                        || other.get_from() == from
+                        || self.piece_on(other.get_from()) != Some(piece)
+                    {
+                        continue;
+                    }
+                    ambiguous = true;
+                    same_file |= other.get_from().get_file() == from.get_file();
+                    same_rank |= other.get_from().get_rank() == from.get_rank();
+                }
+                if ambiguous {
+                    if !same_file {
+                        san.push(FILE_LETTERS[from.get_file() as usize]);
+                    } else if !same_rank {
+                        san.push_str(&(from.get_rank() + 1).to_string());
+                    } else {
+                        san.push_str(&from.to_str());
+                    }
+                }
+            }
+            if is_capture {
+                san.push('x');
+            }
+            san.push_str(&to.to_str());
+            if m.is_promotion() {
+                san.push('=');
+                san.push(SAN_PIECE_LETTERS[PROMOTION_PIECES[m.get_sp() as usize] as usize]);
+            }
+        }
+
+        let new_board = self.make_move_new(m);
+        if new_board.checkers != 0 {
+            let mut replies = MoveList::new();
+            generate_all_moves(&new_board, &mut replies);
+            san.push(if replies.is_empty() { '#' } else { '+' });
+        }
+        return san;
+    }
+
+    pub fn parse_san(&self, str: &str) -> Result<Move, MoveParseError> {
+        let invalid = || MoveParseError::InvalidFormat(str.to_string());
+        let san = str
+            .trim()
+            .trim_end_matches("e.p.")
+            .trim_end()
+            .trim_end_matches(['+', '#', '!', '?']);
+        if !san.is_ascii() || san.len() < 2 {
+            return Err(invalid());
+        }
+        let mut moves = MoveList::new();
+        generate_all_moves(self, &mut moves);
+
+        let castle = match san {
+            "O-O" | "0-0" => Some(KING_SIDE_CASTLE),
+            "O-O-O" | "0-0-0" => Some(QUEEN_SIDE_CASTLE),
+            _ => None,
+        };
+        if let Some(sp) = castle {
+            return moves
+                .iter()
+                .copied()
+                .find(|m| m.get_sp() == sp)
+                .ok_or_else(|| MoveParseError::IllegalMove(str.to_string()));
+        }
+
+        let mut chars = san.chars().collect::<Vec<char>>();
+        let piece = match piece_from_letter(chars[0]) {
+            Some(p) if p != Piece::Pawn => {
+                chars.remove(0);
+                p
+            }
+            _ => Piece::Pawn,
+        };
+        let mut promotion = None;
+        if let Some(p) = chars.last().and_then(|&c| piece_from_letter(c)) {
+            promotion = Some(p);
+            chars.pop();
+            if chars.last() == Some(&'=') {
+                chars.pop();
+            }
+        }
+        chars.retain(|&c| c != 'x' && c != ':' && c != '-');
+        if chars.len() < 2 {
+            return Err(invalid());
+        }
+        let dest: String = chars[chars.len() - 2..].iter().collect();
+        let to = parse_square(&dest).ok_or_else(invalid)?;
+        let mut from_file = None;
+        let mut from_rank = None;
+        for &c in &chars[..chars.len() - 2] {
+            if let Some(file) = FILE_LETTERS.iter().position(|&f| f == c) {
+                from_file = Some(file as u8);
+            } else if let Some(rank) = c.to_digit(10).filter(|r| (1..=8).contains(r)) {
+                from_rank = Some(rank as u8 - 1);
+            } else {
+                return Err(invalid());
+            }
+        }
+
+        let mut found = None;
+        for m in &moves {
+            if m.get_to() != to
+                || m.is_castle()
+                || self.piece_on(m.get_from()) != Some(piece)
+                || from_file.is_some_and(|f| f != m.get_from().get_file())
+                || from_rank.is_some_and(|r| r != m.get_from().get_rank())
+            {
+                continue;
+            }
+            let promoted = if m.is_promotion() {
+                Some(PROMOTION_PIECES[m.get_sp() as usize])
+            } else {
+                None
+            };
+            if promoted != promotion {
+                continue;
+            }
+            if found.is_some() {
+                return Err(MoveParseError::AmbiguousMove(str.to_string()));
+            }
+            found = Some(m);
+        }
+        return found.ok_or_else(|| MoveParseError::IllegalMove(str.to_string()));
+    }
+}
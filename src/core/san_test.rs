@@ -0,0 +1,113 @@
+use std::mem::discriminant;
+
+use crate::core::{
+    board::Board,
+    fen::START_POSITION_FEN,
+    r#move::{MoveParseError, MoveUtil},
+};
+
+struct SanGame<'a> {
+    fen: &'a str,
+    moves: &'a str,
+}
+const GAMES: [SanGame; 8] = [
+    // Morphy - Duke of Brunswick and Count Isouard, Paris 1858
+    SanGame {
+        fen: START_POSITION_FEN,
+        moves: "e4 e5 Nf3 d6 d4 Bg4 dxe5 Bxf3 Qxf3 dxe5 Bc4 Nf6 Qb3 Qe7 Nc3 c6 Bg5 b5 Nxb5 cxb5 \
+                Bxb5+ Nbd7 O-O-O Rd8 Rxd7 Rxd7 Rd1 Qe6 Bxd7+ Nxd7 Qb8+ Nxb8 Rd8#",
+    },
+    SanGame {
+        fen: START_POSITION_FEN,
+        moves: "f3 e5 g4 Qh4#",
+    },
+    SanGame {
+        fen: START_POSITION_FEN,
+        moves: "e4 e5 Bc4 Nc6 Qh5 Nf6 Qxf7#",
+    },
+    SanGame {
+        fen: START_POSITION_FEN,
+        moves: "e4 Nf6 e5 d5 exd6 Nc6 dxc7 e5 cxd8=Q+ Kxd8 Nf3 Bf5 Be2 Ke7 O-O",
+    },
+    SanGame {
+        fen: "4k3/8/8/R7/8/8/8/R3K3 w - - 0 1",
+        moves: "R1a3 Kd7 R5a4 Kc6 Ra2 Kb5 R4a3",
+    },
+    SanGame {
+        fen: "4k3/8/8/8/8/Q7/8/Q1Q1K3 w - - 0 1",
+        moves: "Qa1b2 Kd7 Qaa2 Ke7 Qcb1",
+    },
+    SanGame {
+        fen: "7k/4P3/6K1/8/8/8/8/8 w - - 0 1",
+        moves: "e8=Q#",
+    },
+    SanGame {
+        fen: "8/8/8/8/k2Pp3/8/8/4K3 b - d3 0 1",
+        moves: "exd3 Kf2 d2 Ke2 d1=Q+ Kxd1 Kb3",
+    },
+];
+const ALTERNATE_SAN: [(&str, &str, &str); 6] = [
+    (START_POSITION_FEN, "Ng1-f3", "g1f3"),
+    (START_POSITION_FEN, "e2e4", "e2e4"),
+    ("r3k3/8/8/8/8/8/8/4K3 b q - 0 1", "0-0-0", "e8c8"),
+    ("8/8/8/8/k2Pp3/8/8/4K3 b - d3 0 1", "exd3 e.p.", "e4d3"),
+    ("7k/4P3/8/8/8/8/8/4K3 w - - 0 1", "e8Q+", "e7e8q"),
+    ("4k3/8/8/8/8/Q7/8/Q1Q1K3 w - - 0 1", "Qa3xb2!?", "a3b2"),
+];
+const INVALID_SAN: [(&str, &str, MoveParseError); 5] = [
+    (START_POSITION_FEN, "Ne4", MoveParseError::IllegalMove(String::new())),
+    (START_POSITION_FEN, "O-O", MoveParseError::IllegalMove(String::new())),
+    (START_POSITION_FEN, "Zf3", MoveParseError::InvalidFormat(String::new())),
+    ("4k3/8/8/8/8/Q7/8/Q1Q1K3 w - - 0 1", "Qb2", MoveParseError::AmbiguousMove(String::new())),
+    ("7k/4P3/8/8/8/8/8/4K3 w - - 0 1", "e8", MoveParseError::IllegalMove(String::new())),
+];
+pub fn test_san() -> bool {
+    let mut passed = true;
+    for game in GAMES {
+        let mut board = Board::from_fen(game.fen).unwrap();
+        for san in game.moves.split_whitespace() {
+            let m = match board.parse_san(san) {
+                Ok(m) => m,
+                Err(e) => {
+                    println!("{} in {}: {}", san, board.to_fen(), e);
+                    passed = false;
+                    break;
+                }
+            };
+            if board.move_to_san(m) != san {
+                println!(
+                    "{} in {} was formatted as {}",
+                    san,
+                    board.to_fen(),
+                    board.move_to_san(m)
+                );
+                passed = false;
+            }
+            board.make_move(m);
+        }
+    }
+    for (fen, san, expected) in ALTERNATE_SAN {
+        let board = Board::from_fen(fen).unwrap();
+        match board.parse_san(san) {
+            Ok(m) if m.to_str() == expected => {}
+            res => {
+                println!("{} in {} gave {:?} instead of {}", san, fen, res.map(|m| m.to_str()), expected);
+                passed = false;
+            }
+        }
+    }
+    for (fen, san, error) in INVALID_SAN {
+        let board = Board::from_fen(fen).unwrap();
+        match board.parse_san(san) {
+            Err(e) if discriminant(&e) == discriminant(&error) => {}
+            res => {
+                println!("{} in {} gave {:?} instead of {:?}", san, fen, res.map(|m| m.to_str()), error);
+                passed = false;
+            }
+        }
+    }
+    if passed {
+        println!("san test completed successfully");
+    }
+    return passed;
+}
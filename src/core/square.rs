@@ -1,5 +1,4 @@
 const FILES: [char; 8] = ['a', 'b', 'c', 'd', 'e', 'f', 'g', 'h'];
-const ROWS: [char; 8] = ['a', 'b', 'c', 'd', 'e', 'f', 'g', 'h'];
 
 const DISTANCE_FROM_CENTER_FILE: [u8; 8] = [3, 2, 1, 0, 0, 1, 2, 3];
 pub trait SquareUtil<T> {
@@ -24,12 +23,15 @@ impl SquareUtil<Square> for Square {
         return DISTANCE_FROM_CENTER_FILE[self.get_file() as usize];
     }
 }
-pub fn str_to_square(str: &str) -> Square {
+pub fn parse_square(str: &str) -> Option<Square> {
     let chars = str.chars().collect::<Vec<char>>();
-    for i in 0..8 {
-        if chars[0] == ROWS[i] {
-            return (i as u32 + 8 * (chars[1].to_digit(10).unwrap() - 1)) as Square;
-        }
+    if chars.len() != 2 {
+        return None;
     }
-    return 0;
+    let file = FILES.iter().position(|&f| f == chars[0])?;
+    let rank = chars[1].to_digit(10)?;
+    if rank == 0 || rank > 8 {
+        return None;
+    }
+    return Some((file as u32 + 8 * (rank - 1)) as Square);
 }
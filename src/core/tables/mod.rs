@@ -1,2 +1,4 @@
 pub mod magics;
+mod rays;
 pub mod zobrist;
+pub mod polyglot;
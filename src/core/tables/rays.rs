@@ -0,0 +1,55 @@
+// board geometry for the sliding pieces, shared with build.rs which searches the magic numbers,
+// so it uses nothing from the crate
+pub type BitBoard = u64;
+
+// (file, rank) steps
+pub const ROOK_DIRECTIONS: [(i32, i32); 4] = [(0, 1), (0, -1), (1, 0), (-1, 0)];
+pub const BISHOP_DIRECTIONS: [(i32, i32); 4] = [(1, 1), (1, -1), (-1, 1), (-1, -1)];
+
+pub const fn on_board(file: i32, rank: i32) -> bool {
+    return file >= 0 && file < 8 && rank >= 0 && rank < 8;
+}
+pub const fn square_bb(file: i32, rank: i32) -> BitBoard {
+    return 1 << (rank * 8 + file);
+}
+// attacks along one direction, the first blocker included
+pub const fn ray_attacks(sq: usize, direction: (i32, i32), blockers: BitBoard) -> BitBoard {
+    let mut attacks = 0;
+    let mut f = (sq % 8) as i32 + direction.0;
+    let mut r = (sq / 8) as i32 + direction.1;
+    while on_board(f, r) {
+        attacks |= square_bb(f, r);
+        if blockers & square_bb(f, r) != 0 {
+            break;
+        }
+        f += direction.0;
+        r += direction.1;
+    }
+    return attacks;
+}
+pub const fn slider_attacks(sq: usize, directions: &[(i32, i32); 4], blockers: BitBoard) -> BitBoard {
+    let mut attacks = 0;
+    let mut i = 0;
+    while i < 4 {
+        attacks |= ray_attacks(sq, directions[i], blockers);
+        i += 1;
+    }
+    return attacks;
+}
+// squares whose occupancy changes the attacks, the last square of every ray never does
+pub const fn relevant_mask(sq: usize, directions: &[(i32, i32); 4]) -> BitBoard {
+    let mut mask = 0;
+    let mut i = 0;
+    while i < 4 {
+        let (df, dr) = directions[i];
+        let mut f = (sq % 8) as i32 + df;
+        let mut r = (sq / 8) as i32 + dr;
+        while on_board(f + df, r + dr) {
+            mask |= square_bb(f, r);
+            f += df;
+            r += dr;
+        }
+        i += 1;
+    }
+    return mask;
+}
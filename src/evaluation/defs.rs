@@ -1,14 +1,99 @@
-pub const PIECE_VALUES: [i16; 5] = [100, 310, 320, 500, 975];
-pub const ENDGAME_MATERIAL_START: f32 =
-    (PIECE_VALUES[3] * 2 + PIECE_VALUES[2] + PIECE_VALUES[1]) as f32;
-pub const MULTIPLIER: f32 = 1.0 / ENDGAME_MATERIAL_START as f32;
+use crate::evaluation::params::EvalParams;
 
-pub const UNHEALTHY_PAWN_PENALTY: i16 = 10;
-pub const OPEN_UNHEALTHY_PAWN_PENALTY: i16 = 10;
-pub const PASSED_PAWN_VALUES: [i16; 7] = [0, 90, 60, 40, 25, 15, 15];
-pub const BISHOP_PAIR_VALUE: i16 = 50;
-pub const BISHOP_MOBILITY_SCORE: i16 = 5;
-pub const KNIGHT_MOBILITY_SCORE: i16 = 4;
-pub const ROOK_MOBILITY_SCORE_ENDGAME: i16 = 4;
-pub const ROOK_MOBILITY_SCORE_MIDDLEGAME: i16 = 2;
-pub const PAWN_STORM_PENALTY: [i16; 8] = [0, 0, -60, -30, -10, 0, 0, 0];
+#[rustfmt::skip]
+pub const DEFAULT_PARAMS: EvalParams = EvalParams {
+    piece_values: [[100, 100], [310, 310], [320, 320], [500, 500], [975, 975]],
+    pst: [
+        [
+            [   0,   0], [   0,   0], [   0,   0], [   0,   0], [   0,   0], [   0,   0], [   0,   0], [   0,   0],
+            [   5,  10], [  10,  10], [  10,  10], [ -20,  10], [ -20,  10], [  10,  10], [  10,  10], [   5,  10],
+            [   5,  10], [  -5,  10], [ -10,  10], [   0,  10], [   0,  10], [ -10,  10], [  -5,  10], [   5,  10],
+            [   0,  20], [   0,  20], [   0,  20], [  20,  20], [  20,  20], [   0,  20], [   0,  20], [   0,  20],
+            [   5,  30], [   5,  30], [  10,  30], [  25,  30], [  25,  30], [  10,  30], [   5,  30], [   5,  30],
+            [  10,  50], [  10,  50], [  20,  50], [  30,  50], [  30,  50], [  20,  50], [  10,  50], [  10,  50],
+            [  50,  80], [  50,  80], [  50,  80], [  50,  80], [  50,  80], [  50,  80], [  50,  80], [  50,  80],
+            [   0,   0], [   0,   0], [   0,   0], [   0,   0], [   0,   0], [   0,   0], [   0,   0], [   0,   0],
+        ],
+        [
+            [ -50, -50], [ -40, -40], [ -30, -30], [ -30, -30], [ -30, -30], [ -30, -30], [ -40, -40], [ -50, -50],
+            [ -40, -40], [ -20, -20], [   0,   0], [   5,   5], [   5,   5], [   0,   0], [ -20, -20], [ -40, -40],
+            [ -30, -30], [   5,   5], [  10,  10], [  15,  15], [  15,  15], [  10,  10], [   5,   5], [ -30, -30],
+            [ -30, -30], [   0,   0], [  15,  15], [  20,  20], [  20,  20], [  15,  15], [   0,   0], [ -30, -30],
+            [ -30, -30], [   5,   5], [  15,  15], [  20,  20], [  20,  20], [  15,  15], [   5,   5], [ -30, -30],
+            [ -30, -30], [   0,   0], [  10,  10], [  15,  15], [  15,  15], [  10,  10], [   0,   0], [ -30, -30],
+            [ -40, -40], [ -20, -20], [   0,   0], [   0,   0], [   0,   0], [   0,   0], [ -20, -20], [ -40, -40],
+            [ -50, -50], [ -40, -40], [ -30, -30], [ -30, -30], [ -30, -30], [ -30, -30], [ -40, -40], [ -50, -50],
+        ],
+        [
+            [ -20, -20], [ -10, -10], [ -10, -10], [ -10, -10], [ -10, -10], [ -10, -10], [ -10, -10], [ -20, -20],
+            [ -10, -10], [   5,   5], [   0,   0], [   0,   0], [   0,   0], [   0,   0], [   5,   5], [ -10, -10],
+            [ -10, -10], [  10,  10], [  10,  10], [  10,  10], [  10,  10], [  10,  10], [  10,  10], [ -10, -10],
+            [ -10, -10], [   0,   0], [  10,  10], [  10,  10], [  10,  10], [  10,  10], [   0,   0], [ -10, -10],
+            [ -10, -10], [   5,   5], [   5,   5], [  10,  10], [  10,  10], [   5,   5], [   5,   5], [ -10, -10],
+            [ -10, -10], [   0,   0], [   5,   5], [  10,  10], [  10,  10], [   5,   5], [   0,   0], [ -10, -10],
+            [ -10, -10], [   0,   0], [   0,   0], [   0,   0], [   0,   0], [   0,   0], [   0,   0], [ -10, -10],
+            [ -20, -20], [ -10, -10], [ -10, -10], [ -10, -10], [ -10, -10], [ -10, -10], [ -10, -10], [ -20, -20],
+        ],
+        [
+            [   0,   0], [   0,   0], [   0,   0], [   5,   5], [   5,   5], [   2,   2], [   0,   0], [   0,   0],
+            [  -5,  -5], [   0,   0], [   0,   0], [   0,   0], [   0,   0], [   0,   0], [   0,   0], [  -5,  -5],
+            [  -5,  -5], [   0,   0], [   0,   0], [   0,   0], [   0,   0], [   0,   0], [   0,   0], [  -5,  -5],
+            [  -5,  -5], [   0,   0], [   0,   0], [   0,   0], [   0,   0], [   0,   0], [   0,   0], [  -5,  -5],
+            [  -5,  -5], [   0,   0], [   0,   0], [   0,   0], [   0,   0], [   0,   0], [   0,   0], [  -5,  -5],
+            [  -5,  -5], [   0,   0], [   0,   0], [   0,   0], [   0,   0], [   0,   0], [   0,   0], [  -5,  -5],
+            [   5,   5], [  10,  10], [  10,  10], [  10,  10], [  10,  10], [  10,  10], [  10,  10], [   5,   5],
+            [   0,   0], [   0,   0], [   0,   0], [   0,   0], [   0,   0], [   0,   0], [   0,   0], [   0,   0],
+        ],
+        [
+            [ -20, -20], [ -10, -10], [ -10, -10], [  -5,  -5], [  -5,  -5], [ -10, -10], [ -10, -10], [ -20, -20],
+            [ -10, -10], [   0,   0], [   0,   0], [   0,   0], [   0,   0], [   5,   5], [   0,   0], [ -10, -10],
+            [ -10, -10], [   0,   0], [   5,   5], [   5,   5], [   5,   5], [   5,   5], [   5,   5], [ -10, -10],
+            [  -5,  -5], [   0,   0], [   5,   5], [   5,   5], [   5,   5], [   5,   5], [   0,   0], [   0,   0],
+            [  -5,  -5], [   0,   0], [   5,   5], [   5,   5], [   5,   5], [   5,   5], [   0,   0], [  -5,  -5],
+            [ -10, -10], [   0,   0], [   5,   5], [   5,   5], [   5,   5], [   5,   5], [   0,   0], [ -10, -10],
+            [ -10, -10], [   0,   0], [   0,   0], [   0,   0], [   0,   0], [   0,   0], [   0,   0], [ -10, -10],
+            [ -20, -20], [ -10, -10], [ -10, -10], [  -5,  -5], [  -5,  -5], [ -10, -10], [ -10, -10], [ -20, -20],
+        ],
+        [
+            [  20, -50], [  30, -30], [  10, -30], [   0, -30], [   0, -30], [  10, -30], [  30, -30], [  20, -50],
+            [  20, -30], [  20, -25], [  -5,   0], [  -5,   0], [  -5,   0], [  -5,   0], [  20, -25], [  20, -30],
+            [ -10, -25], [ -20, -20], [ -20,  20], [ -20,  25], [ -20,  25], [ -20,  20], [ -20, -20], [ -10, -25],
+            [ -20, -20], [ -30, -15], [ -30,  30], [ -40,  40], [ -40,  40], [ -30,  30], [ -30, -15], [ -20, -20],
+            [ -30, -15], [ -40, -10], [ -40,  35], [ -50,  45], [ -50,  45], [ -40,  35], [ -40, -10], [ -30, -15],
+            [ -40, -10], [ -50,  -5], [ -50,  20], [ -60,  30], [ -60,  30], [ -50,  20], [ -50,  -5], [ -40, -10],
+            [ -60,  -5], [ -60,   0], [ -60,   5], [ -60,   5], [ -60,   5], [ -60,   5], [ -60,   0], [ -60,  -5],
+            [ -80, -20], [ -70, -10], [ -70, -10], [ -70, -10], [ -70, -10], [ -70, -10], [ -70, -10], [ -80, -20],
+        ],
+    ],
+    unhealthy_pawn: [10, 10],
+    open_unhealthy_pawn: [10, 10],
+    passed_pawn: [[0, 0], [90, 90], [60, 60], [40, 40], [25, 25], [15, 15], [15, 15]],
+    bishop_pair: [50, 50],
+    mobility_offsets: [4, 6, 7],
+    knight_mobility: [4, 4],
+    bishop_mobility: [5, 5],
+    rook_mobility: [2, 4],
+    mop_up_center: [0, 10],
+    mop_up_king_distance: [0, 4],
+    rook_closed_file: [10, 10],
+    rook_open_file: [10, 10],
+    rook_open_king_adjacent: [20, 10],
+    rook_open_king_file: [30, 10],
+    rook_semi_open_king_adjacent: [10, 0],
+    rook_semi_open_king_file: [20, 0],
+    queen_proximity: [10, 10],
+    seventh_rank_rook: [10, 30],
+    seventh_rank_queen: [10, 20],
+    tempo: [20, 10],
+    hanging: [30, 20],
+    threat_by_pawn: [50, 40],
+    threat_by_minor: [40, 30],
+    threat_by_rook: [40, 30],
+    safe_checks: [[40, 5], [20, 5], [40, 5], [30, 5]],
+    xray_threat: [20, 15],
+    pawn_shield: 36,
+    pawn_storm: [0, 0, -60, -30, -10, 0, 0, 0],
+    king_attacker_values: [1, 1, 2, 4],
+    king_attack_scale: 20,
+    king_attack_weights: [0, 0, 50, 75, 88, 94, 97, 99],
+};
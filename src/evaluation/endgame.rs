@@ -0,0 +1,265 @@
+use crate::{
+    core::{
+        Board, Color::{self, Black, White}, Piece,
+        bitboard::{BitBoardUtil, DARK_SQUARES, FILE_BITBOARDS},
+        square::{Square, SquareUtil},
+    },
+    evaluation::{
+        defs::DEFAULT_PARAMS,
+        kpk::probe_kpk,
+        tables::{get_distance, get_distance_from_center},
+    },
+};
+
+// four bits for the number of pieces of every color and type, white in the low half
+pub type MaterialKey = u64;
+// known wins score at least this much, far from the mate scores
+pub const KNOWN_WIN: i16 = 10000;
+// scale factors are out of this, a draw scales to 0
+pub const SCALE_NORMAL: i32 = 64;
+
+// the exact evaluators by material signature, the strong side's pieces first
+type EndgameFn = fn(&Board, Color) -> i16;
+const ENDGAMES: [(&str, EndgameFn); 4] = [("KPK", kpk), ("KBNK", kbnk), ("KRKP", krkp), ("KQKP", kqkp)];
+// every signature with white and with black as the strong side
+const ENDGAME_KEYS: [(MaterialKey, Color, EndgameFn); ENDGAMES.len() * 2] = {
+    let mut keys = [(0, White, kpk as EndgameFn); ENDGAMES.len() * 2];
+    let mut i = 0;
+    while i < ENDGAMES.len() {
+        keys[i * 2] = (signature_key(ENDGAMES[i].0, White), White, ENDGAMES[i].1);
+        keys[i * 2 + 1] = (signature_key(ENDGAMES[i].0, Black), Black, ENDGAMES[i].1);
+        i += 1;
+    }
+    keys
+};
+// kings getting closer to each other, by their distance
+const PUSH_CLOSE: [i16; 8] = [0, 0, 100, 80, 60, 40, 20, 10];
+
+pub const fn material_key_unit(color: Color, piece: Piece) -> MaterialKey {
+    return 1 << (4 * (color as usize * 6 + piece as usize));
+}
+pub const fn signature_key(code: &str, strong: Color) -> MaterialKey {
+    let bytes = code.as_bytes();
+    let weak = if matches!(strong, White) { Black } else { White };
+    let mut key = 0;
+    let mut color = strong;
+    let mut i = 0;
+    while i < bytes.len() {
+        let piece = match bytes[i] {
+            b'P' => Piece::Pawn,
+            b'N' => Piece::Knight,
+            b'B' => Piece::Bishop,
+            b'R' => Piece::Rook,
+            b'Q' => Piece::Queen,
+            _ => Piece::King,
+        };
+        if i > 0 && matches!(piece, Piece::King) {
+            color = weak;
+        }
+        key += material_key_unit(color, piece);
+        i += 1;
+    }
+    return key;
+}
+fn value(piece: Piece) -> i16 {
+    return DEFAULT_PARAMS.piece_values[piece as usize][1];
+}
+fn non_pawn_material(board: &Board, color: Color) -> i16 {
+    let mut material = 0;
+    for piece in [Piece::Knight, Piece::Bishop, Piece::Rook, Piece::Queen] {
+        material += board.get_piece_bitboard(piece, color).count_ones() as i16 * value(piece);
+    }
+    return material;
+}
+// squares seen from the strong side, as if it was white
+fn relative(sq: Square, strong: Color) -> Square {
+    if strong == White {
+        return sq;
+    }
+    return sq ^ 56;
+}
+
+// the evaluation of a known endgame from white's point of view
+pub fn evaluate_endgame(board: &Board) -> Option<i16> {
+    let bare = [
+        board.color_combined[White as usize].count_ones() == 1,
+        board.color_combined[Black as usize].count_ones() == 1,
+    ];
+    // every endgame below has at most a queen or a bare king
+    if board.phase > 4 && !bare[0] && !bare[1] {
+        return None;
+    }
+    let mut result = None;
+    for &(key, strong, endgame) in &ENDGAME_KEYS {
+        if key == board.material_key {
+            result = Some((endgame(board, strong), strong));
+        }
+    }
+    for strong in [White, Black] {
+        if result.is_none() && bare[!strong as usize] && has_mating_material(board, strong) {
+            result = Some((kxk(board, strong), strong));
+        }
+    }
+    if result.is_none() && insufficient_material(board) {
+        return Some(0);
+    }
+    return result.map(|(value, strong)| if strong == White { value } else { -value });
+}
+// the exact kpk result from the side to move's point of view, the search stops there
+pub fn kpk_score(board: &Board) -> Option<i16> {
+    for strong in [White, Black] {
+        if board.material_key == signature_key("KPK", strong) {
+            let value = kpk(board, strong);
+            return Some(if board.turn == strong { value } else { -value });
+        }
+    }
+    return None;
+}
+// how much of an eval from white's point of view is kept, the side ahead is the strong one
+pub fn scale(board: &Board, eval: i16) -> i16 {
+    let strong = if eval > 0 { White } else { Black };
+    return (eval as i32 * scale_factor(board, strong) / SCALE_NORMAL) as i16;
+}
+pub fn scale_factor(board: &Board, strong: Color) -> i32 {
+    // every rule below has at most a rook and a bishop per side
+    if board.phase > 6 {
+        return SCALE_NORMAL;
+    }
+    let weak = !strong;
+    let strong_material = non_pawn_material(board, strong);
+    let weak_material = non_pawn_material(board, weak);
+    let strong_pawns = board.get_piece_bitboard(Piece::Pawn, strong);
+
+    // without pawns a minor piece more is not enough to win
+    if strong_pawns == 0 && strong_material - weak_material <= value(Piece::Bishop) {
+        return if strong_material < value(Piece::Rook) { 0 } else { 14 };
+    }
+    // rook pawns with a bishop that does not control the promotion square, the weak king
+    // only has to reach the corner
+    let strong_bishops = board.get_piece_bitboard(Piece::Bishop, strong);
+    if strong_material == value(Piece::Bishop) && strong_bishops != 0 {
+        for file in [0, 7] {
+            if strong_pawns & !FILE_BITBOARDS[file] != 0 {
+                continue;
+            }
+            let corner = relative(56 + file as Square, strong);
+            let bishop_on_dark = strong_bishops & DARK_SQUARES != 0;
+            let corner_dark = DARK_SQUARES.has_sq(corner);
+            if bishop_on_dark != corner_dark && get_distance(board.king_square(weak), corner) <= 1 {
+                return 0;
+            }
+        }
+    }
+    // opposite colored bishops, very drawish with nothing else on the board
+    let weak_bishops = board.get_piece_bitboard(Piece::Bishop, weak);
+    if strong_bishops.count_ones() == 1
+        && weak_bishops.count_ones() == 1
+        && ((strong_bishops & DARK_SQUARES) == 0) != ((weak_bishops & DARK_SQUARES) == 0)
+    {
+        if strong_material == value(Piece::Bishop) && weak_material == value(Piece::Bishop) {
+            return 16;
+        }
+        return 44;
+    }
+    return SCALE_NORMAL;
+}
+
+fn has_mating_material(board: &Board, color: Color) -> bool {
+    let bishops = board.get_piece_bitboard(Piece::Bishop, color);
+    let knights = board.get_piece_bitboard(Piece::Knight, color);
+    return board.get_piece_bitboard(Piece::Queen, color) != 0
+        || board.get_piece_bitboard(Piece::Rook, color) != 0
+        || (bishops & DARK_SQUARES != 0 && bishops & !DARK_SQUARES != 0)
+        || (bishops != 0 && knights != 0)
+        || knights.count_ones() >= 3;
+}
+// neither side can mate, two knights can not force it against a bare king either
+fn insufficient_material(board: &Board) -> bool {
+    let majors_or_pawns = board.pieces[Piece::Pawn as usize] | board.pieces[Piece::Rook as usize] | board.pieces[Piece::Queen as usize];
+    if majors_or_pawns != 0 {
+        return false;
+    }
+    let minors = |color: Color| -> u32 {
+        return (board.get_piece_bitboard(Piece::Knight, color) | board.get_piece_bitboard(Piece::Bishop, color)).count_ones();
+    };
+    let bishops = board.pieces[Piece::Bishop as usize];
+    let knights = board.pieces[Piece::Knight as usize];
+    return (minors(White) <= 1 && minors(Black) <= 1)
+        || (knights == 0 && (bishops & DARK_SQUARES == 0 || bishops & !DARK_SQUARES == 0))
+        || (bishops == 0 && knights.count_ones() == 2 && (minors(White) == 0 || minors(Black) == 0));
+}
+
+// a bare king against mating material, driven to the edge
+fn kxk(board: &Board, strong: Color) -> i16 {
+    let strong_king = board.king_square(strong);
+    let weak_king = board.king_square(!strong);
+    let pawns = board.get_piece_bitboard(Piece::Pawn, strong).count_ones() as i16;
+    return KNOWN_WIN
+        + non_pawn_material(board, strong)
+        + pawns * value(Piece::Pawn)
+        + get_distance_from_center(weak_king) as i16 * 20
+        + PUSH_CLOSE[get_distance(strong_king, weak_king) as usize];
+}
+// the weak king has to be driven to a corner the bishop controls
+fn kbnk(board: &Board, strong: Color) -> i16 {
+    let strong_king = board.king_square(strong);
+    let weak_king = board.king_square(!strong);
+    let corners: [Square; 2] = if board.get_piece_bitboard(Piece::Bishop, strong) & DARK_SQUARES != 0 {
+        [0, 63]
+    } else {
+        [7, 56]
+    };
+    let corner_distance = get_distance(weak_king, corners[0]).min(get_distance(weak_king, corners[1])) as i16;
+    return KNOWN_WIN + value(Piece::Bishop) + value(Piece::Knight)
+        + (7 - corner_distance) * 40
+        + get_distance_from_center(weak_king) as i16 * 10
+        + PUSH_CLOSE[get_distance(strong_king, weak_king) as usize];
+}
+// exact from the bitbase, a won position scores more the further the pawn is
+fn kpk(board: &Board, strong: Color) -> i16 {
+    let pawn = board.get_piece_bitboard(Piece::Pawn, strong).to_sq();
+    let strong_king = board.king_square(strong);
+    let weak_king = board.king_square(!strong);
+    if !probe_kpk(strong, strong_king, weak_king, pawn, board.turn == strong) {
+        return 0;
+    }
+    return KNOWN_WIN + value(Piece::Pawn) + relative(pawn, strong).get_rank() as i16 * 10;
+}
+// wins unless the pawn is far advanced and supported, then it is about who is closer
+fn krkp(board: &Board, strong: Color) -> i16 {
+    let strong_king = relative(board.king_square(strong), strong);
+    let weak_king = relative(board.king_square(!strong), strong);
+    let rook = relative(board.get_piece_bitboard(Piece::Rook, strong).to_sq(), strong);
+    let pawn = relative(board.get_piece_bitboard(Piece::Pawn, !strong).to_sq(), strong);
+    let promotion = pawn.get_file();
+    let strong_to_move = board.turn == strong;
+    let distance = |a: Square, b: Square| get_distance(a, b) as i16;
+
+    if strong_king.get_file() == pawn.get_file() && strong_king < pawn {
+        return value(Piece::Rook) - distance(strong_king, pawn);
+    }
+    if distance(weak_king, pawn) >= 3 + if strong_to_move { 0 } else { 1 } && distance(weak_king, rook) >= 3 {
+        return value(Piece::Rook) - distance(strong_king, pawn);
+    }
+    if weak_king.get_rank() <= 2
+        && distance(weak_king, pawn) == 1
+        && strong_king.get_rank() >= 3
+        && distance(strong_king, pawn) > 2 + if strong_to_move { 1 } else { 0 }
+    {
+        return 80 - 8 * distance(strong_king, pawn);
+    }
+    return 200 - 8 * (distance(strong_king, pawn - 8) - distance(weak_king, pawn - 8) - distance(pawn, promotion));
+}
+// wins unless a bishop or rook pawn on the seventh is supported by its king
+fn kqkp(board: &Board, strong: Color) -> i16 {
+    let strong_king = board.king_square(strong);
+    let weak_king = board.king_square(!strong);
+    let pawn = board.get_piece_bitboard(Piece::Pawn, !strong).to_sq();
+    let mut result = PUSH_CLOSE[get_distance(strong_king, pawn) as usize];
+    let seventh = relative(pawn, strong).get_rank() == 1;
+    let drawing_file = [0, 2, 5, 7].contains(&pawn.get_file());
+    if !seventh || get_distance(weak_king, pawn) != 1 || !drawing_file {
+        result += value(Piece::Queen) - value(Piece::Pawn);
+    }
+    return result;
+}
@@ -3,50 +3,99 @@ use crate::{
         Board, Color::{self, Black, White}, Piece, bitboard::{
             BitBoard, BitBoardUtil, DARK_SQUARES, FILE_BITBOARDS, LIGHT_SQUARES, SECOND_RANK,
             SEVENTH_RANK,
-        }, castle_rights::{CastleRightsUtil, Rights}, square::Square, tables::magics::{get_bishop_moves, get_knight_moves, get_rook_moves}
+        }, castle_rights::{CastleRightsUtil, Rights}, square::Square
     },
     evaluation::{
-        defs::{
-            BISHOP_MOBILITY_SCORE, BISHOP_PAIR_VALUE, ENDGAME_MATERIAL_START,
-            KNIGHT_MOBILITY_SCORE, MULTIPLIER, OPEN_UNHEALTHY_PAWN_PENALTY, PASSED_PAWN_VALUES,
-            PAWN_STORM_PENALTY, PIECE_VALUES, ROOK_MOBILITY_SCORE_ENDGAME,
-            ROOK_MOBILITY_SCORE_MIDDLEGAME, UNHEALTHY_PAWN_PENALTY,
-        },
+        endgame,
+        nnue,
+        defs::DEFAULT_PARAMS,
+        params::EvalParams,
+        threats::{AttackMaps, evaluate_threats},
+        score::{PackedScore, PackedScoreUtil, packed, s},
         tables::{
-            KING_SIDE_CASTLE_FILESET, QUEEN_SIDE_CASTLE_FILESET, calc_king_pst, get_adjacent_files, get_adjacent_fileset, get_distance_from_center, get_fileset_bb, get_front_span, get_king_attacks, get_orthogonal_distance, get_pawn_endgame_value, get_pst_value
+            KING_SIDE_CASTLE_FILESET, QUEEN_SIDE_CASTLE_FILESET, get_adjacent_files, get_adjacent_fileset, get_distance_from_center, get_fileset_bb, get_front_span, get_orthogonal_distance
         },
     },
     transposition_table::{PawnEntry, TranspositionTable},
 };
 
-fn count_material(board: &Board, color_combined: u64) -> i16 {
-    let queens = (board.pieces[4] & color_combined).count_ones() as i16;
-    let rooks = (board.pieces[3] & color_combined).count_ones() as i16;
-    let bishops = (board.pieces[2] & color_combined).count_ones() as i16;
-    let knights = (board.pieces[1] & color_combined).count_ones() as i16;
+// middlegame values of the pieces without pawns, only used to decide who is winning for mop up
+#[inline(always)]
+fn count_material(board: &Board, color_combined: u64, params: &EvalParams) -> i16 {
+    let mut material = 0;
+    for piece in 1..5 {
+        material += (board.pieces[piece] & color_combined).count_ones() as i16 * params.piece_values[piece][0];
+    }
+    return material;
+}
+#[inline(always)]
+fn count(bb: BitBoard) -> PackedScore {
+    return bb.count_ones() as PackedScore;
+}
 
-    return queens * PIECE_VALUES[4]
-        + rooks * PIECE_VALUES[3]
-        + bishops * PIECE_VALUES[2]
-        + knights * PIECE_VALUES[1];
+#[derive(Clone, Copy, PartialEq)]
+pub enum Term {
+    Material,
+    Mobility,
+    MopUp,
+    Pst,
+    Pawns,
+    BishopPair,
+    Rooks,
+    Queens,
+    SeventhRank,
+    Tempo,
+    KingSafety,
+    Threats,
 }
+pub const TERM_COUNT: usize = 12;
+pub const TERM_NAMES: [&str; TERM_COUNT] = [
+    "Material",
+    "Mobility",
+    "Mop up",
+    "PST",
+    "Pawns",
+    "Bishop pair",
+    "Rooks",
+    "Queens",
+    "Seventh rank",
+    "Tempo",
+    "King safety",
+    "Threats",
+];
+// every term for white and black, each from its own point of view
+pub type Terms = [[PackedScore; 2]; TERM_COUNT];
 
-fn get_endgame_weight(m: i16) -> f32 {
-    let material = m as f32;
-    if material < ENDGAME_MATERIAL_START {
-        return 1.0 - (material * MULTIPLIER);
+// known endgames are evaluated exactly, everything else by the active backend scaled down in
+// drawish endgames
+pub fn evaluate(board: &Board, tt: &TranspositionTable) -> i16 {
+    let eval = if let Some(eval) = endgame::evaluate_endgame(board) {
+        eval
+    } else if let Some(network) = nnue::active() {
+        let eval = network.evaluate(&board.accumulator, board.turn);
+        endgame::scale(board, if board.turn == White { eval } else { -eval })
     } else {
-        return 0.0;
+        endgame::scale(board, classical_eval(board, &DEFAULT_PARAMS, Some(tt)))
     };
+    if board.turn == White {
+        return eval;
+    }
+    return -eval;
 }
-fn get_value<T>(m: T, e: T, endgame: f32) -> T {
-    if endgame == 0.0 {
-        return m;
+// from white's point of view, board.pst_value has to come from the same params and the pawn
+// cache is only valid for the default ones
+#[inline(always)]
+pub fn classical_eval(board: &Board, params: &EvalParams, tt: Option<&TranspositionTable>) -> i16 {
+    let terms = evaluate_terms(board, params, tt);
+    let mut eval = 0;
+    for term in terms {
+        eval += term[0] - term[1];
     }
-    return e;
+    return eval.interpolate(board.phase);
 }
-
-pub fn evaluate(board: &Board, tt: &TranspositionTable) -> i16 {
+#[inline(always)]
+pub fn evaluate_terms(board: &Board, params: &EvalParams, tt: Option<&TranspositionTable>) -> Terms {
+    let mut terms = [[0; 2]; TERM_COUNT];
     let white_combined = board.color_combined[0];
     let black_combined = board.color_combined[1];
 
@@ -59,52 +108,33 @@ pub fn evaluate(board: &Board, tt: &TranspositionTable) -> i16 {
     let wp = board.get_piece_bitboard(Piece::Pawn, White);
     let bp = board.get_piece_bitboard(Piece::Pawn, Black);
 
-    let white_material_without_pawns = count_material(board, white_combined);
-    let black_material_without_pawns = count_material(board, black_combined);
-
-    let white_endgame = get_endgame_weight(white_material_without_pawns);
-    let black_endgame = get_endgame_weight(black_material_without_pawns);
-
-    let white_middlegame = 1.0 - white_endgame;
-    let black_middlegame = 1.0 - black_endgame;
-
-    let material_eval = white_material_without_pawns + PIECE_VALUES[0] * wp.count_ones() as i16
-        - black_material_without_pawns
-        - PIECE_VALUES[0] * bp.count_ones() as i16;
+    let mut material = [0; 2];
+    for piece in 0..5 {
+        let value = packed(params.piece_values[piece]);
+        material[0] += count(board.pieces[piece] & white_combined) * value;
+        material[1] += count(board.pieces[piece] & black_combined) * value;
+    }
+    terms[Term::Material as usize] = material;
 
-    let piece_scores = board.pst_value
-        + calc_king_pst(0, wk, black_endgame, black_middlegame)
-        + calc_king_pst(1, bk, white_endgame, white_middlegame);
+    // black tables hold negated values
+    terms[Term::Pst as usize] = [board.pst_value[0], -board.pst_value[1]];
 
-    let mop_eval: i16 = mop_up_eval(
-        wk,
-        bk,
-        white_material_without_pawns,
-        black_material_without_pawns,
-        black_endgame,
-    ) - mop_up_eval(
-        bk,
-        wk,
-        black_material_without_pawns,
-        white_material_without_pawns,
-        white_endgame,
-    );
+    let white_material_without_pawns = count_material(board, white_combined, params);
+    let black_material_without_pawns = count_material(board, black_combined, params);
+    terms[Term::MopUp as usize] = [
+        mop_up_eval(wk, bk, white_material_without_pawns, black_material_without_pawns, params),
+        mop_up_eval(bk, wk, black_material_without_pawns, white_material_without_pawns, params),
+    ];
 
-    let (pawn_eval, wp_fileset, bp_fileset) = evaluate_pawns(
-        tt,
-        board.pawn_hash,
-        (white_endgame, black_endgame),
-        (white_middlegame, black_middlegame),
-        wp,
-        bp,
-    );
+    let (pawn_eval, wp_fileset, bp_fileset) = evaluate_pawns(params, tt, board.pawn_hash, wp, bp);
+    terms[Term::Pawns as usize] = pawn_eval;
 
     let closed = wp_fileset & bp_fileset;
     let open = (!wp_fileset) & (!bp_fileset);
     let semi_open_white = bp_fileset & (!wp_fileset);
-    let semi_open_black = wp_fileset & (bp_fileset);
+    let semi_open_black = wp_fileset & (!bp_fileset);
 
-    let rooks_eval = evaluate_rooks(
+    terms[Term::Rooks as usize] = evaluate_rooks(
         wr,
         br,
         get_fileset_bb(open),
@@ -113,75 +143,82 @@ pub fn evaluate(board: &Board, tt: &TranspositionTable) -> i16 {
         get_fileset_bb(closed),
         wk as usize,
         bk as usize,
-        (white_endgame, black_endgame),
+        params,
     );
 
-    let bishop_eval = evaluate_bishop_pair(board.get_piece_bitboard(Piece::Bishop, White))
-        - evaluate_bishop_pair(board.get_piece_bitboard(Piece::Bishop, Black));
+    terms[Term::BishopPair as usize] = [
+        evaluate_bishop_pair(board.get_piece_bitboard(Piece::Bishop, White), params),
+        evaluate_bishop_pair(board.get_piece_bitboard(Piece::Bishop, Black), params),
+    ];
 
-    let queens_eval = evaluate_queens(board.get_piece_bitboard(Piece::Queen, White), bk)
-        - evaluate_queens(board.get_piece_bitboard(Piece::Queen, Black), wk);
+    terms[Term::Queens as usize] = [
+        evaluate_queens(board.get_piece_bitboard(Piece::Queen, White), bk, params),
+        evaluate_queens(board.get_piece_bitboard(Piece::Queen, Black), wk, params),
+    ];
 
-    let seventh_rank_value = if (bp & SEVENTH_RANK) != 0 || bk > 55 {
-        seventh_rank_bounus(wq & SEVENTH_RANK, wr & SEVENTH_RANK, black_endgame)
-    } else {
-        0
-    } - if (wp & SECOND_RANK) != 0 || wk < 8 {
-        seventh_rank_bounus(bq & SECOND_RANK, br & SECOND_RANK, white_endgame)
-    } else {
-        0
-    };
+    terms[Term::SeventhRank as usize] = [
+        if (bp & SEVENTH_RANK) != 0 || bk > 55 {
+            seventh_rank_bounus(wq & SEVENTH_RANK, wr & SEVENTH_RANK, params)
+        } else {
+            0
+        },
+        if (wp & SECOND_RANK) != 0 || wk < 8 {
+            seventh_rank_bounus(bq & SECOND_RANK, br & SECOND_RANK, params)
+        } else {
+            0
+        },
+    ];
 
-    let (white_mobility_score, white_attack_count, white_attack_value) =
-        evaluate_mobility(board, get_king_attacks(bk), White, black_endgame);
-    let (black_mobility_score, black_attack_count, black_attack_value) =
-        evaluate_mobility(board, get_king_attacks(wk), Black, white_endgame);
+    let maps = AttackMaps::new(board, params);
+    terms[Term::Mobility as usize] = [evaluate_mobility(&maps, White, params), evaluate_mobility(&maps, Black, params)];
 
-    let king_eval = if black_endgame != 0.0 && bq != 0 {
-        evaluate_king_safety(
-            wp,
-            bp,
-            wk as usize,
-            0,
-            board,
-            black_attack_count,
-            black_attack_value,
-        )
-    } else {
-        0
-    } - if white_endgame != 0.0 && wq != 0 {
-        evaluate_king_safety(
-            bp,
-            wp,
-            bk as usize,
-            1,
-            board,
-            white_attack_count,
-            white_attack_value,
-        )
-    } else {
-        0
-    };
+    // king safety is a middlegame only term, the phase fades it out
+    terms[Term::KingSafety as usize] = [
+        if bq != 0 {
+            s(
+                evaluate_king_safety(
+                    wp,
+                    bp,
+                    wk as usize,
+                    0,
+                    board,
+                    &maps,
+                    params,
+                ),
+                0,
+            )
+        } else {
+            0
+        },
+        if wq != 0 {
+            s(
+                evaluate_king_safety(
+                    bp,
+                    wp,
+                    bk as usize,
+                    1,
+                    board,
+                    &maps,
+                    params,
+                ),
+                0,
+            )
+        } else {
+            0
+        },
+    ];
 
-    let tempo_bounus = if board.turn == White {
-        get_value(20, 10, black_endgame)
+    terms[Term::Threats as usize] = [
+        evaluate_threats(board, &maps, White, params),
+        evaluate_threats(board, &maps, Black, params),
+    ];
+
+    terms[Term::Tempo as usize] = if board.turn == White {
+        [packed(params.tempo), 0]
     } else {
-        get_value(-20, -10, white_endgame)
+        [0, packed(params.tempo)]
     };
-    let eval = material_eval + white_mobility_score - black_mobility_score
-        + mop_eval
-        + piece_scores
-        + pawn_eval
-        + bishop_eval
-        + rooks_eval
-        + queens_eval
-        + seventh_rank_value
-        + tempo_bounus
-        + king_eval;
-    if board.turn == White {
-        return eval;
-    }
-    return -eval;
+    return terms;
 }
 
 fn mop_up_eval(
@@ -189,95 +226,78 @@ fn mop_up_eval(
     their_king: Square,
     my_material: i16,
     their_material: i16,
-    endgame: f32,
-) -> i16 {
-    let mut score: i16 = 0;
-    if my_material as f32 > (their_material as f32 + 200.0) && endgame > 0.0 {
-        score += get_distance_from_center(their_king) as i16 * 10;
-        score += (14 - get_orthogonal_distance(my_king, their_king) as i16) * 4
+    params: &EvalParams,
+) -> PackedScore {
+    let mut score = 0;
+    if my_material > their_material + 200 {
+        score += get_distance_from_center(their_king) as PackedScore * packed(params.mop_up_center);
+        score += (14 - get_orthogonal_distance(my_king, their_king) as PackedScore)
+            * packed(params.mop_up_king_distance);
     }
     return score;
 }
 
+#[inline(always)]
 fn evaluate_pawns(
-    tt: &TranspositionTable,
+    params: &EvalParams,
+    tt: Option<&TranspositionTable>,
     hash: u64,
-    endgame: (f32, f32),
-    middle_game: (f32, f32),
     wp: u64,
     bp: u64,
-) -> (i16, u8, u8) {
-    let entry = tt.lookup_pawn_structure(hash);
-    if entry.is_some() {
-        let pawn_data = entry.unwrap();
-        let score = pawn_data.eval
-            + (pawn_data.w_pst.0 as f32 * middle_game.1
-                + pawn_data.w_pst.1 as f32 * endgame.1
-                + pawn_data.b_pst.0 as f32 * middle_game.0
-                + pawn_data.b_pst.1 as f32 * endgame.0) as i16;
-        return (score, pawn_data.w_filesets, pawn_data.b_filesets);
+) -> ([PackedScore; 2], u8, u8) {
+    if let Some(pawn_data) = tt.and_then(|tt| tt.lookup_pawn_structure(hash)) {
+        return (pawn_data.eval, pawn_data.w_filesets, pawn_data.b_filesets);
     } else {
-        let w_data = get_pawn_data(wp, bp, White);
-        let b_data = get_pawn_data(bp, wp, Black);
-        let score = w_data.0 - b_data.0
-            + (w_data.1 as f32 * middle_game.1
-                + w_data.2 as f32 * endgame.1
-                + b_data.1 as f32 * middle_game.0
-                + b_data.2 as f32 * endgame.0) as i16;
+        let w_data = get_pawn_data(wp, bp, White, params);
+        let b_data = get_pawn_data(bp, wp, Black, params);
         let e = PawnEntry{
             key: (hash >> 48) as u16,
             w_filesets: w_data.1,
             b_filesets: b_data.1,
-            b_pst: (b_data.2, b_data.3),
-            w_pst: (w_data.2, w_data.3),
-            eval: score,
+            eval: [w_data.0, b_data.0],
         };
-        tt.store_pawn_structure(
-            hash,
-            e
-        );
-        return (score, w_data.1, b_data.1);
+        if let Some(tt) = tt {
+            tt.store_pawn_structure(hash, e);
+        }
+        return ([w_data.0, b_data.0], w_data.1, b_data.1);
     };
 }
-fn get_pawn_data(pawns: BitBoard, enemy_pawns: BitBoard, color: Color) -> (i16, u8, i16, i16) {
+fn get_pawn_data(
+    pawns: BitBoard,
+    enemy_pawns: BitBoard,
+    color: Color,
+    params: &EvalParams,
+) -> (PackedScore, u8) {
     let mut score = 0;
     let mut p = pawns;
     let mut fileset: u8 = 0;
-    let mut middle_game = 0;
-    let mut endgame = 0;
+    let unhealthy = packed(params.unhealthy_pawn);
+    let open_unhealthy = packed(params.open_unhealthy_pawn);
     while p != 0 {
         let i = p.pop_lsb();
         let file = (i & 7) as usize;
-        endgame += get_pawn_endgame_value(color, i);
-        middle_game += get_pst_value(color, Piece::Pawn, i);
         let front_span = get_front_span(color, i) & enemy_pawns;
         let is_open = front_span & FILE_BITBOARDS[file] == 0;
         if ((fileset >> file) & 1) == 1 {
             //doubled pawn
-            score -= if is_open {
-                OPEN_UNHEALTHY_PAWN_PENALTY
-            } else {
-                UNHEALTHY_PAWN_PENALTY
-            };
+            score -= if is_open { open_unhealthy } else { unhealthy };
         } else {
             fileset |= 1 << file;
         }
         if front_span == 0 {
             //passer
             let rank = (i >> 3) as usize;
-            score += PASSED_PAWN_VALUES[if color == White { 7 - rank } else { rank }]
+            score += packed(params.passed_pawn[if color == White { 7 - rank } else { rank }])
         }
         if (get_adjacent_files(file) & pawns) == 0 {
             //isolated pawn
-            score -= if is_open {
-                OPEN_UNHEALTHY_PAWN_PENALTY
-            } else {
-                UNHEALTHY_PAWN_PENALTY
-            };
+            score -= if is_open { open_unhealthy } else { unhealthy };
         }
     }
-    return (score, fileset, middle_game, endgame);
+    return (score, fileset);
 }
+#[inline(always)]
+#[allow(clippy::too_many_arguments)]
 fn evaluate_rooks(
     wr: BitBoard,
     br: BitBoard,
@@ -287,147 +307,103 @@ fn evaluate_rooks(
     closed: BitBoard,
     wk: usize,
     bk: usize,
-    endgame: (f32, f32),
-) -> i16 {
-    let mut score = 0;
+    params: &EvalParams,
+) -> [PackedScore; 2] {
+    let mut score = [0; 2];
     let w_adjacent = get_adjacent_files(wk & 7) & br;
     let b_adjacent = get_adjacent_files(bk & 7) & wr;
     let w_file = FILE_BITBOARDS[wk & 7] & br;
     let b_file = FILE_BITBOARDS[bk & 7] & wr;
-    //closed files: -10
-    score -= (closed & wr).count_ones() as i16 * 10 - (closed & br).count_ones() as i16 * 10;
+    let closed_file = packed(params.rook_closed_file);
+    let open_file = packed(params.rook_open_file);
+    let open_adjacent = packed(params.rook_open_king_adjacent);
+    let open_king_file = packed(params.rook_open_king_file);
+    let semi_open_adjacent = packed(params.rook_semi_open_king_adjacent);
+    let semi_open_king_file = packed(params.rook_semi_open_king_file);
+
+    score[0] -= count(closed & wr) * closed_file;
+    score[1] -= count(closed & br) * closed_file;
 
     //open file
-    score += (open & wr).count_ones() as i16 * 10
-        + (open & b_adjacent).count_ones() as i16 * get_value(20, 10, endgame.1)
-        + (open & b_file).count_ones() as i16 * get_value(30, 10, endgame.1);
+    score[0] += count(open & wr) * open_file
+        + count(open & b_adjacent) * open_adjacent
+        + count(open & b_file) * open_king_file;
 
-    score -= (open & br).count_ones() as i16 * 10
-        + (open & w_adjacent).count_ones() as i16 * get_value(20, 10, endgame.0)
-        + (open & w_file).count_ones() as i16 * get_value(20, 10, endgame.0);
+    score[1] += count(open & br) * open_file
+        + count(open & w_adjacent) * open_adjacent
+        + count(open & w_file) * open_king_file;
 
-    if endgame.1 == 0.0 {
-        score += (semi_open_white & b_adjacent).count_ones() as i16 * 10
-            + (semi_open_white & b_file).count_ones() as i16 * 20;
-    }
-    if endgame.0 == 0.0 {
-        score -= (semi_open_black & w_adjacent).count_ones() as i16 * 10
-            + (semi_open_black & w_file).count_ones() as i16 * 20;
-    }
+    score[0] += count(semi_open_white & b_adjacent) * semi_open_adjacent
+        + count(semi_open_white & b_file) * semi_open_king_file;
+    score[1] += count(semi_open_black & w_adjacent) * semi_open_adjacent
+        + count(semi_open_black & w_file) * semi_open_king_file;
     return score;
 }
-fn evaluate_bishop_pair(bishops: u64) -> i16 {
+fn evaluate_bishop_pair(bishops: u64, params: &EvalParams) -> PackedScore {
     if ((bishops & LIGHT_SQUARES) != 0) && ((bishops & DARK_SQUARES) != 0) {
-        return BISHOP_PAIR_VALUE;
+        return packed(params.bishop_pair);
     }
     return 0;
 }
-fn evaluate_queens(mut queens: BitBoard, their_king: Square) -> i16 {
+fn evaluate_queens(mut queens: BitBoard, their_king: Square, params: &EvalParams) -> PackedScore {
     let mut score = 0;
     while queens != 0 {
-        score += 10 - get_orthogonal_distance(queens.to_sq(), their_king) as i16;
+        let distance = get_orthogonal_distance(queens.to_sq(), their_king) as i16;
+        score += packed(params.queen_proximity) - s(distance, distance);
         queens &= queens - 1;
     }
     return score;
 }
-fn seventh_rank_bounus(queens: BitBoard, rooks: BitBoard, endgame: f32) -> i16 {
-    return (rooks.count_ones() * get_value(10, 30, endgame)
-        + queens.count_ones() * get_value(10, 20, endgame)) as i16;
+fn seventh_rank_bounus(queens: BitBoard, rooks: BitBoard, params: &EvalParams) -> PackedScore {
+    return count(rooks) * packed(params.seventh_rank_rook) + count(queens) * packed(params.seventh_rank_queen);
 }
-fn evaluate_mobility(board: &Board, targets: u64, color: Color, endgame: f32) -> (i16, u32, f32) {
-    let mut attacking_piece_count = 0;
-    let mut attacking_piece_values = 0.0;
-    let blockers = board.combined;
-
+#[inline(always)]
+fn evaluate_mobility(maps: &AttackMaps, color: Color, params: &EvalParams) -> PackedScore {
+    let [knight_moves, bishop_moves, rook_moves] = maps.moves[color as usize];
+    let [knight_offset, bishop_offset, rook_offset] = params.mobility_offsets.map(|v| v as PackedScore);
     let mut score = 0;
-
-    let mut knights = board.get_piece_bitboard(Piece::Knight, color);
-    let mut knight_moves = 0;
-    while knights != 0 {
-        let moves = get_knight_moves(knights.to_sq());
-        knight_moves += (moves & !blockers).count_ones() as i16;
-        if moves & targets != 0 {
-            attacking_piece_count += 1;
-            attacking_piece_values += 1.0;
-        }
-        knights &= knights - 1;
-    }
     if knight_moves != 0 {
-        score += (knight_moves - 4) * KNIGHT_MOBILITY_SCORE;
-    }
-
-    let mut bishops = board.get_piece_bitboard(Piece::Bishop, color);
-    let mut bishop_moves = 0;
-    while bishops != 0 {
-        let moves = get_bishop_moves(bishops.to_sq(), blockers);
-        bishop_moves += (moves & !blockers).count_ones() as i16;
-        if moves & targets != 0 {
-            attacking_piece_count += 1;
-            attacking_piece_values += 1.0;
-        }
-        bishops &= bishops - 1;
+        score += (knight_moves - knight_offset) * packed(params.knight_mobility);
     }
     if bishop_moves != 0 {
-        score += (bishop_moves - 6) * BISHOP_MOBILITY_SCORE;
-    }
-    let mut rooks = board.get_piece_bitboard(Piece::Rook, color);
-    let mut rook_moves = 0;
-    while rooks != 0 {
-        let moves = get_rook_moves(rooks.to_sq(), blockers);
-        rook_moves += (moves & !blockers).count_ones() as i16;
-        if moves & targets != 0 {
-            attacking_piece_count += 1;
-            attacking_piece_values += 2.0;
-        }
-        rooks &= rooks - 1;
+        score += (bishop_moves - bishop_offset) * packed(params.bishop_mobility);
     }
     if rook_moves != 0 {
-        score += (rook_moves - 7)
-            * get_value(
-                ROOK_MOBILITY_SCORE_MIDDLEGAME,
-                ROOK_MOBILITY_SCORE_ENDGAME,
-                endgame,
-            );
+        score += (rook_moves - rook_offset) * packed(params.rook_mobility);
     }
-
-    return (score, attacking_piece_count, attacking_piece_values);
+    return score;
 }
+#[inline(always)]
 fn evaluate_king_safety(
     my_pawns: BitBoard,
     their_pawns: BitBoard,
     king: usize,
     color: usize,
     board: &Board,
-    attacking_pieces_count: u32,
-    attacking_pieces_value: f32,
+    maps: &AttackMaps,
+    params: &EvalParams,
 ) -> i16 {
     let castling_rights = board
         .castle_rights
         .color(if color == 0 { White } else { Black });
-    let mut storm_value = evaluate_pawn_storm(their_pawns, get_adjacent_fileset(king & 7), color);
+    let mut storm_value = evaluate_pawn_storm(their_pawns, get_adjacent_fileset(king & 7), color, params);
     if castling_rights != Rights::NoRights {
         let value = if castling_rights == Rights::KingSide {
-            evaluate_pawn_storm(their_pawns, KING_SIDE_CASTLE_FILESET, color)
+            evaluate_pawn_storm(their_pawns, KING_SIDE_CASTLE_FILESET, color, params)
         } else if castling_rights == Rights::QueenSide {
-            evaluate_pawn_storm(their_pawns, QUEEN_SIDE_CASTLE_FILESET, color)
+            evaluate_pawn_storm(their_pawns, QUEEN_SIDE_CASTLE_FILESET, color, params)
         } else {
             std::cmp::max(
-                evaluate_pawn_storm(their_pawns, KING_SIDE_CASTLE_FILESET, color),
-                evaluate_pawn_storm(their_pawns, QUEEN_SIDE_CASTLE_FILESET, color),
+                evaluate_pawn_storm(their_pawns, KING_SIDE_CASTLE_FILESET, color, params),
+                evaluate_pawn_storm(their_pawns, QUEEN_SIDE_CASTLE_FILESET, color, params),
             )
         };
         storm_value = (storm_value + value) / 2;
     }
-    return evaluate_pawn_shield(my_pawns, king, color) + storm_value
-        - evaluate_piece_attacks(
-            board,
-            get_king_attacks(king as Square),
-            attacking_pieces_count,
-            attacking_pieces_value,
-            if color == 0 { Black } else { White },
-        );
+    return evaluate_pawn_shield(my_pawns, king, color, params) + storm_value
+        - evaluate_piece_attacks(maps, 1 - color, params);
 }
-fn evaluate_pawn_storm(their_pawns: u64, mut fileset: u8, color: usize) -> i16 {
+fn evaluate_pawn_storm(their_pawns: u64, mut fileset: u8, color: usize, params: &EvalParams) -> i16 {
     let mut score = 0;
     while fileset != 0 {
         let file = fileset.trailing_zeros() as usize;
@@ -439,7 +415,7 @@ fn evaluate_pawn_storm(their_pawns: u64, mut fileset: u8, color: usize) -> i16 {
             } else {
                 63 - bb.leading_zeros()
             };
-            score += PAWN_STORM_PENALTY[if color == 0 {
+            score += params.pawn_storm[if color == 0 {
                 (pawn >> 3) as usize
             } else {
                 (7 - (pawn >> 3)) as usize
@@ -448,16 +424,16 @@ fn evaluate_pawn_storm(their_pawns: u64, mut fileset: u8, color: usize) -> i16 {
     }
     return score;
 }
-fn evaluate_pawn_shield(pawns: u64, king: usize, color: usize) -> i16 {
+fn evaluate_pawn_shield(pawns: u64, king: usize, color: usize, params: &EvalParams) -> i16 {
     let mut score = 0;
     let mut fileset = get_adjacent_fileset(king & 7);
-    let king_file = king >> 3;
+    let king_file = king & 7;
     while fileset != 0 {
         let file = fileset.trailing_zeros() as usize;
         let file_bb = FILE_BITBOARDS[file] & pawns;
         fileset &= fileset - 1;
         let penalty = if file_bb == 0 {
-            36
+            params.pawn_shield
         } else {
             let pawn = if color == 0 {
                 file_bb.trailing_zeros()
@@ -469,7 +445,7 @@ fn evaluate_pawn_shield(pawns: u64, king: usize, color: usize) -> i16 {
             } else {
                 pawn >> 3
             } as i16;
-            36 - (distance_to_8 * distance_to_8)
+            params.pawn_shield - (distance_to_8 * distance_to_8)
         };
         if file == king_file {
             score -= penalty << 1;
@@ -479,36 +455,10 @@ fn evaluate_pawn_shield(pawns: u64, king: usize, color: usize) -> i16 {
     }
     return score;
 }
-fn evaluate_piece_attacks(
-    board: &Board,
-    targets: u64,
-    mut count: u32,
-    mut values: f32,
-    color: Color,
-) -> i16 {
-    let blockers = board.combined;
-    let mut queens = board.get_piece_bitboard(Piece::Queen, color);
-    while queens != 0 {
-        let q = queens.pop_lsb();
-        if (get_rook_moves(q, blockers) & targets != 0)
-            || (get_bishop_moves(q, blockers) & targets != 0)
-        {
-            count += 1;
-            values += 4.0;
-        }
-    }
-    return (20.0 * values * get_piece_attack_weight(count)) as i16;
-}
-fn get_piece_attack_weight(num: u32) -> f32 {
-    match num {
-        0 => 0.0,
-        1 => 0.0,
-        2 => 0.5,
-        3 => 0.75,
-        4 => 0.88,
-        5 => 0.94,
-        6 => 0.97,
-        7 => 0.99,
-        _ => 1.0,
-    }
+// the pieces of the attacking color around the king, weighted by their number
+#[inline(always)]
+fn evaluate_piece_attacks(maps: &AttackMaps, color: usize, params: &EvalParams) -> i16 {
+    let count = maps.king_attackers[color];
+    let values = maps.king_attack_values[color];
+    return (params.king_attack_scale as f32 * values * params.king_attack_weight(count)) as i16;
 }
@@ -0,0 +1,176 @@
+use std::sync::OnceLock;
+
+use crate::core::{
+    Board, Color, Piece,
+    bitboard::BitBoardUtil,
+    move_list::MoveList,
+    movegen::generate_all_moves,
+    r#move::{Move, MoveUtil},
+    square::{Square, SquareUtil},
+};
+
+// white has the pawn on files a to d and ranks 2 to 7, every king placement and side to move
+pub const KPK_SIZE: usize = 2 * 24 * 64 * 64;
+
+const INVALID: u8 = 0;
+const UNKNOWN: u8 = 1;
+const DRAW: u8 = 2;
+const WIN: u8 = 4;
+
+// one bit per position, set when white wins
+static BITBASE: OnceLock<Vec<u64>> = OnceLock::new();
+
+pub fn kpk_index(white_to_move: bool, white_king: Square, black_king: Square, pawn: Square) -> usize {
+    return white_king as usize
+        | (black_king as usize) << 6
+        | (!white_to_move as usize) << 12
+        | (pawn.get_file() as usize) << 13
+        | (6 - pawn.get_rank() as usize) << 15;
+}
+fn decode(index: usize) -> (bool, Square, Square, Square) {
+    let pawn = ((6 - (index >> 15)) * 8 + ((index >> 13) & 3)) as Square;
+    return (index >> 12 & 1 == 0, (index & 63) as Square, (index >> 6 & 63) as Square, pawn);
+}
+
+pub fn kpk_fen(white_to_move: bool, white_king: Square, black_king: Square, pawn: Square) -> String {
+    let mut fen = String::new();
+    for rank in (0..8).rev() {
+        let mut empty = 0;
+        for file in 0..8 {
+            let sq = rank * 8 + file;
+            let piece = if sq == white_king {
+                'K'
+            } else if sq == black_king {
+                'k'
+            } else if sq == pawn {
+                'P'
+            } else {
+                empty += 1;
+                continue;
+            };
+            if empty != 0 {
+                fen.push_str(&empty.to_string());
+                empty = 0;
+            }
+            fen.push(piece);
+        }
+        if empty != 0 {
+            fen.push_str(&empty.to_string());
+        }
+        if rank != 0 {
+            fen.push('/');
+        }
+    }
+    return format!("{} {} - - 0 1", fen, if white_to_move { 'w' } else { 'b' });
+}
+fn child_index(board: &Board) -> usize {
+    let pawn = board.get_piece_bitboard(Piece::Pawn, Color::White).to_sq();
+    return kpk_index(
+        board.turn == Color::White,
+        board.king_square(Color::White),
+        board.king_square(Color::Black),
+        pawn,
+    );
+}
+// a queen or rook that survives the promotion wins, unless black is stalemated
+fn promotion_wins(board: &mut Board, m: Move) -> bool {
+    let state = board.make_move(m);
+    let mating_material = matches!(board.piece_on(m.get_to()), Some(Piece::Queen) | Some(Piece::Rook));
+    let mut replies = MoveList::new();
+    generate_all_moves(board, &mut replies);
+    let captured = replies.into_iter().any(|r| r.get_to() == m.get_to());
+    let mated = replies.is_empty() && board.checkers != 0;
+    board.unmake_move(m, &state);
+    return mated || (mating_material && !replies.is_empty() && !captured);
+}
+// the result when it follows from the position alone, otherwise the positions its moves lead to
+fn initial(index: usize, children: &mut Vec<u32>) -> u8 {
+    let (white_to_move, white_king, black_king, pawn) = decode(index);
+    if white_king == black_king || white_king == pawn || black_king == pawn {
+        return INVALID;
+    }
+    // adjacent kings or black in check with white to move
+    let Ok(mut board) = Board::from_fen(&kpk_fen(white_to_move, white_king, black_king, pawn)) else {
+        return INVALID;
+    };
+    let mut moves = MoveList::new();
+    generate_all_moves(&board, &mut moves);
+    if moves.is_empty() {
+        // mated by the pawn or stalemated
+        return if !white_to_move && board.checkers != 0 { WIN } else { DRAW };
+    }
+    for m in &moves {
+        if m.is_promotion() {
+            if promotion_wins(&mut board, m) {
+                return WIN;
+            }
+            continue;
+        }
+        // the pawn falls
+        if m.get_to() == pawn {
+            return DRAW;
+        }
+        let state = board.make_move(m);
+        children.push(child_index(&board) as u32);
+        board.unmake_move(m, &state);
+    }
+    // only promotions that do not win
+    if children.is_empty() {
+        return DRAW;
+    }
+    return UNKNOWN;
+}
+fn classify(db: &[u8], index: usize, children: &[u32]) -> u8 {
+    let (white_to_move, ..) = decode(index);
+    let result = children.iter().fold(INVALID, |result, &child| result | db[child as usize]);
+    if white_to_move {
+        return if result & WIN != 0 {
+            WIN
+        } else if result & UNKNOWN != 0 {
+            UNKNOWN
+        } else {
+            DRAW
+        };
+    }
+    return if result & DRAW != 0 {
+        DRAW
+    } else if result & UNKNOWN != 0 {
+        UNKNOWN
+    } else {
+        WIN
+    };
+}
+// retrograde analysis on the legal moves of every position, positions are resolved from the ones
+// one move closer to the end until nothing changes, what is left can not be won
+fn generate() -> Vec<u64> {
+    let mut children: Vec<Vec<u32>> = vec![vec![]; KPK_SIZE];
+    let mut db: Vec<u8> = (0..KPK_SIZE).map(|index| initial(index, &mut children[index])).collect();
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for index in 0..KPK_SIZE {
+            if db[index] == UNKNOWN {
+                db[index] = classify(&db, index, &children[index]);
+                changed |= db[index] != UNKNOWN;
+            }
+        }
+    }
+    let mut bits = vec![0u64; KPK_SIZE / 64];
+    for (index, &result) in db.iter().enumerate() {
+        if result == WIN {
+            bits[index / 64] |= 1 << (index % 64);
+        }
+    }
+    return bits;
+}
+
+// whether the side with the pawn wins, built on the first probe
+pub fn probe_kpk(strong: Color, strong_king: Square, weak_king: Square, pawn: Square, strong_to_move: bool) -> bool {
+    let mut flip = if strong == Color::White { 0 } else { 56 };
+    if pawn.get_file() > 3 {
+        flip ^= 7;
+    }
+    let index = kpk_index(strong_to_move, strong_king ^ flip, weak_king ^ flip, pawn ^ flip);
+    let bits = BITBASE.get_or_init(generate);
+    return bits[index / 64] >> (index % 64) & 1 != 0;
+}
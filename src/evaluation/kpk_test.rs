@@ -0,0 +1,146 @@
+use crate::{
+    core::{
+        Board, Color, Piece,
+        move_list::MoveList,
+        movegen::generate_all_moves,
+        r#move::MoveUtil,
+    },
+    evaluation::kpk::{kpk_fen, probe_kpk},
+};
+
+const SIZE: usize = 2 * 64 * 64 * 64;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Result {
+    Invalid,
+    Unknown,
+    Draw,
+    Win,
+}
+
+fn index(white_to_move: bool, white_king: u8, black_king: u8, pawn: u8) -> usize {
+    return white_king as usize | (black_king as usize) << 6 | (pawn as usize) << 12 | (!white_to_move as usize) << 18;
+}
+// the game after a promotion, a queen or a rook that survives wins unless black is stalemated
+fn promotion_result(board: &mut Board, moves: &MoveList) -> Result {
+    let mut result = Result::Draw;
+    for m in moves {
+        if !m.is_promotion() {
+            continue;
+        }
+        let state = board.make_move(m);
+        let piece = board.piece_on(m.get_to());
+        let mut replies = MoveList::new();
+        generate_all_moves(board, &mut replies);
+        let captured = replies.into_iter().any(|r| r.get_to() == m.get_to());
+        let mated = replies.is_empty() && board.checkers != 0;
+        let mating_material = matches!(piece, Some(Piece::Queen) | Some(Piece::Rook));
+        if mated || (mating_material && !replies.is_empty() && !captured) {
+            result = Result::Win;
+        }
+        board.unmake_move(m, &state);
+    }
+    return result;
+}
+
+// plays out every position with the pawn on any file by the legal moves alone and compares the
+// outcome with the bitbase, for white and for black as the side with the pawn
+pub fn test_kpk() -> bool {
+    let mut results = vec![Result::Invalid; SIZE];
+    let mut children: Vec<Vec<usize>> = vec![vec![]; SIZE];
+    for pawn in 8..56u8 {
+        for white_king in 0..64u8 {
+            for black_king in 0..64u8 {
+                for white_to_move in [true, false] {
+                    if white_king == black_king || white_king == pawn || black_king == pawn {
+                        continue;
+                    }
+                    // adjacent kings or black in check with white to move
+                    let Ok(mut board) = Board::from_fen(&kpk_fen(white_to_move, white_king, black_king, pawn)) else {
+                        continue;
+                    };
+                    let mut moves = MoveList::new();
+                    generate_all_moves(&board, &mut moves);
+                    let i = index(white_to_move, white_king, black_king, pawn);
+                    results[i] = Result::Unknown;
+                    if moves.is_empty() {
+                        results[i] = if !white_to_move && board.checkers != 0 { Result::Win } else { Result::Draw };
+                        continue;
+                    }
+                    if white_to_move && promotion_result(&mut board, &moves) == Result::Win {
+                        results[i] = Result::Win;
+                        continue;
+                    }
+                    for m in &moves {
+                        let (from, to) = (m.get_from(), m.get_to());
+                        if m.is_promotion() {
+                            continue;
+                        }
+                        if to == pawn {
+                            results[i] = Result::Draw;
+                            break;
+                        }
+                        let moved = |sq: u8| if sq == from { to } else { sq };
+                        children[i].push(index(!white_to_move, moved(white_king), moved(black_king), moved(pawn)));
+                    }
+                    // only promotions that do not win
+                    if results[i] == Result::Unknown && children[i].is_empty() {
+                        results[i] = Result::Draw;
+                    }
+                }
+            }
+        }
+    }
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for i in 0..SIZE {
+            if results[i] != Result::Unknown {
+                continue;
+            }
+            let white_to_move = i >> 18 == 0;
+            let (good, bad) = if white_to_move { (Result::Win, Result::Draw) } else { (Result::Draw, Result::Win) };
+            if children[i].iter().any(|&c| results[c] == good) {
+                results[i] = good;
+            } else if children[i].iter().all(|&c| results[c] == bad) {
+                results[i] = bad;
+            } else {
+                continue;
+            }
+            changed = true;
+        }
+    }
+    let mut positions = 0;
+    let mut failed = 0;
+    for (i, &result) in results.iter().enumerate() {
+        if result == Result::Invalid {
+            continue;
+        }
+        let (white_king, black_king, pawn) = ((i & 63) as u8, (i >> 6 & 63) as u8, (i >> 12 & 63) as u8);
+        let white_to_move = i >> 18 == 0;
+        // what is still unknown can not be forced
+        let win = result == Result::Win;
+        let white = probe_kpk(Color::White, white_king, black_king, pawn, white_to_move);
+        let black = probe_kpk(Color::Black, white_king ^ 56, black_king ^ 56, pawn ^ 56, white_to_move);
+        positions += 1;
+        if white == win && black == win {
+            continue;
+        }
+        if failed == 0 {
+            println!(
+                "{} is a {}, the bitbase says {} for white and {} for black",
+                kpk_fen(white_to_move, white_king, black_king, pawn),
+                if win { "win" } else { "draw" },
+                if white { "win" } else { "draw" },
+                if black { "win" } else { "draw" }
+            );
+        }
+        failed += 1;
+    }
+    if failed != 0 {
+        println!("kpk test failed in {} of {} positions", failed, positions);
+        return false;
+    }
+    println!("kpk test completed successfully, {} positions", positions);
+    return true;
+}
@@ -1,3 +1,14 @@
 pub mod evaluate;
+pub mod endgame;
+pub mod kpk;
+pub mod kpk_test;
 pub mod tables;
-pub mod defs;
\ No newline at end of file
+pub mod nnue;
+pub mod nnue_test;
+pub mod symmetry_test;
+pub mod threats;
+pub mod trace;
+pub mod tune;
+pub mod defs;
+pub mod params;
+pub mod score;
\ No newline at end of file
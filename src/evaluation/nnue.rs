@@ -0,0 +1,378 @@
+use std::{
+    fs,
+    ptr::null_mut,
+    sync::atomic::{AtomicBool, AtomicPtr, Ordering},
+};
+
+use crate::core::{Board, Color, Piece, bitboard::BitBoardUtil, square::Square};
+
+// (768 -> HIDDEN) x 2 -> 1 with a clipped relu, the layout bullet writes for its simple
+// perspective networks: every value is a little endian i16, the feature weights row by row,
+// then the feature biases, the output weights for the side to move and the other side and
+// the output bias
+pub const HIDDEN: usize = 256;
+const INPUTS: usize = 768;
+const QA: i32 = 255;
+const QB: i32 = 64;
+const SCALE: i32 = 400;
+// stays well inside the search mate bounds
+const MAX_EVAL: i32 = 20000;
+
+#[repr(C)]
+pub struct Network {
+    feature_weights: [[i16; HIDDEN]; INPUTS],
+    feature_bias: [i16; HIDDEN],
+    output_weights: [[i16; HIDDEN]; 2],
+    output_bias: i16,
+}
+// first layer outputs from the white and the black point of view
+#[repr(C, align(64))]
+#[derive(Clone, Copy, PartialEq)]
+pub struct Accumulator {
+    pub values: [[i16; HIDDEN]; 2],
+}
+
+// a replaced network is freed in set_network, the uci loop only gets there once the search
+// threads have joined
+static NETWORK: AtomicPtr<Network> = AtomicPtr::new(null_mut());
+static ACTIVE: AtomicPtr<Network> = AtomicPtr::new(null_mut());
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+fn update_active() {
+    let network = if ENABLED.load(Ordering::Relaxed) {
+        NETWORK.load(Ordering::Acquire)
+    } else {
+        null_mut()
+    };
+    ACTIVE.store(network, Ordering::Release);
+}
+// the network evaluate and the board accumulators use, none unless one is loaded and enabled
+#[inline(always)]
+pub fn active() -> Option<&'static Network> {
+    let network = ACTIVE.load(Ordering::Acquire);
+    if network.is_null() {
+        return None;
+    }
+    // SAFETY: the pointer came from Box::into_raw and stays valid until set_network replaces it,
+    // which never happens during a search
+    return Some(unsafe { &*network });
+}
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+    update_active();
+}
+pub fn is_loaded() -> bool {
+    return !NETWORK.load(Ordering::Acquire).is_null();
+}
+pub fn set_network(network: Box<Network>) {
+    let old = NETWORK.swap(Box::into_raw(network), Ordering::AcqRel);
+    update_active();
+    if !old.is_null() {
+        // SAFETY: old came from Box::into_raw above and no search is running to read it
+        drop(unsafe { Box::from_raw(old) });
+    }
+}
+// swaps the network and the enabled flag in and gives back the previous ones
+pub fn replace_network(network: *mut Network, enabled: bool) -> (*mut Network, bool) {
+    let previous = (NETWORK.swap(network, Ordering::AcqRel), ENABLED.swap(enabled, Ordering::Relaxed));
+    update_active();
+    return previous;
+}
+pub fn load_network(path: &str) -> Result<(), String> {
+    let bytes = fs::read(path).map_err(|e| e.to_string())?;
+    set_network(Network::from_bytes(&bytes)?);
+    return Ok(());
+}
+
+// index of a piece in the 768 inputs seen from one side, the board is flipped for black
+#[inline(always)]
+fn feature(perspective: Color, piece: Piece, sq: Square, color: Color) -> usize {
+    if perspective == Color::White {
+        return (color as usize) * 384 + (piece as usize) * 64 + sq as usize;
+    }
+    return (1 - color as usize) * 384 + (piece as usize) * 64 + (sq ^ 56) as usize;
+}
+
+impl Network {
+    pub fn from_bytes(bytes: &[u8]) -> Result<Box<Network>, String> {
+        let expected = (INPUTS * HIDDEN + HIDDEN + 2 * HIDDEN + 1) * 2;
+        // trainers pad the file to a multiple of 64 bytes
+        if bytes.len() < expected || bytes.len() > expected + 64 {
+            return Err(format!(
+                "expected a {}x{} network of {} bytes, found {} bytes",
+                INPUTS,
+                HIDDEN,
+                expected,
+                bytes.len()
+            ));
+        }
+        let mut values = bytes.chunks_exact(2).map(|b| i16::from_le_bytes([b[0], b[1]]));
+        let mut network = Network::zeroed();
+        for row in network.feature_weights.iter_mut() {
+            row.fill_with(|| values.next().unwrap());
+        }
+        network.feature_bias.fill_with(|| values.next().unwrap());
+        for row in network.output_weights.iter_mut() {
+            row.fill_with(|| values.next().unwrap());
+        }
+        network.output_bias = values.next().unwrap();
+        return Ok(network);
+    }
+    // built on the heap, the weights do not fit comfortably on a thread stack
+    fn zeroed() -> Box<Network> {
+        // SAFETY: every field is an i16 or an array of them, all zero bytes is a valid network
+        return unsafe { Box::<Network>::new_zeroed().assume_init() };
+    }
+    // small deterministic weights for the self test
+    pub fn random(mut seed: u64) -> Box<Network> {
+        let mut next = || {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            return (seed % 128) as i16 - 64;
+        };
+        let mut network = Network::zeroed();
+        for row in network.feature_weights.iter_mut() {
+            row.fill_with(&mut next);
+        }
+        network.feature_bias.fill_with(&mut next);
+        for row in network.output_weights.iter_mut() {
+            row.fill_with(&mut next);
+        }
+        network.output_bias = next();
+        return network;
+    }
+    pub fn evaluate(&self, accumulator: &Accumulator, turn: Color) -> i16 {
+        let sum = simd::output(
+            &accumulator.values[turn as usize],
+            &accumulator.values[!turn as usize],
+            &self.output_weights,
+        );
+        return self.scale(sum);
+    }
+    // the same sum without simd, the self test checks both agree
+    pub fn evaluate_scalar(&self, accumulator: &Accumulator, turn: Color) -> i16 {
+        let sum = scalar::output(
+            &accumulator.values[turn as usize],
+            &accumulator.values[!turn as usize],
+            &self.output_weights,
+        );
+        return self.scale(sum);
+    }
+    #[inline(always)]
+    fn scale(&self, sum: i32) -> i16 {
+        let eval = (sum + self.output_bias as i32 * QA) * SCALE / (QA * QB);
+        return eval.clamp(-MAX_EVAL, MAX_EVAL) as i16;
+    }
+}
+
+impl Default for Accumulator {
+    fn default() -> Self {
+        return Accumulator { values: [[0; HIDDEN]; 2] };
+    }
+}
+impl Accumulator {
+    pub fn refresh(board: &Board, network: &Network) -> Accumulator {
+        return Accumulator::refresh_with(board, network, simd::add);
+    }
+    // the same sums without simd, the self test checks both agree
+    pub fn refresh_scalar(board: &Board, network: &Network) -> Accumulator {
+        return Accumulator::refresh_with(board, network, scalar::add);
+    }
+    #[inline(always)]
+    fn refresh_with(board: &Board, network: &Network, add: fn(&mut [i16; HIDDEN], &[i16; HIDDEN])) -> Accumulator {
+        let mut accumulator = Accumulator { values: [network.feature_bias; 2] };
+        let mut occupied = board.combined;
+        while occupied != 0 {
+            let sq = occupied.pop_lsb();
+            let color = if board.color_combined[0].has_sq(sq) { Color::White } else { Color::Black };
+            let piece = board.piece_on(sq).unwrap();
+            for perspective in [Color::White, Color::Black] {
+                let weights = &network.feature_weights[feature(perspective, piece, sq, color)];
+                add(&mut accumulator.values[perspective as usize], weights);
+            }
+        }
+        return accumulator;
+    }
+    #[inline(always)]
+    pub fn add(&mut self, network: &Network, piece: Piece, sq: Square, color: Color) {
+        for perspective in [Color::White, Color::Black] {
+            let weights = &network.feature_weights[feature(perspective, piece, sq, color)];
+            simd::add(&mut self.values[perspective as usize], weights);
+        }
+    }
+    #[inline(always)]
+    pub fn sub(&mut self, network: &Network, piece: Piece, sq: Square, color: Color) {
+        for perspective in [Color::White, Color::Black] {
+            let weights = &network.feature_weights[feature(perspective, piece, sq, color)];
+            simd::sub(&mut self.values[perspective as usize], weights);
+        }
+    }
+}
+
+// the reference the simd code is checked against, and what other targets run
+mod scalar {
+    use super::{HIDDEN, QA};
+
+    #[inline(always)]
+    pub fn add(values: &mut [i16; HIDDEN], weights: &[i16; HIDDEN]) {
+        for (value, weight) in values.iter_mut().zip(weights) {
+            *value = value.wrapping_add(*weight);
+        }
+    }
+    #[allow(dead_code)]
+    #[inline(always)]
+    pub fn sub(values: &mut [i16; HIDDEN], weights: &[i16; HIDDEN]) {
+        for (value, weight) in values.iter_mut().zip(weights) {
+            *value = value.wrapping_sub(*weight);
+        }
+    }
+    // clipped relu of both halves times the output weights
+    pub fn output(us: &[i16; HIDDEN], them: &[i16; HIDDEN], weights: &[[i16; HIDDEN]; 2]) -> i32 {
+        let mut sum: i32 = 0;
+        for i in 0..HIDDEN {
+            sum = sum.wrapping_add((us[i] as i32).clamp(0, QA) * weights[0][i] as i32);
+            sum = sum.wrapping_add((them[i] as i32).clamp(0, QA) * weights[1][i] as i32);
+        }
+        return sum;
+    }
+}
+#[cfg(not(target_arch = "x86_64"))]
+use scalar as simd;
+
+// avx2 when the build enables it (-C target-cpu=native), sse2 otherwise, every x86_64 cpu has it
+#[cfg(target_arch = "x86_64")]
+mod simd {
+    use super::{HIDDEN, QA};
+
+    // SAFETY: the intrinsics below are unsafe only for the cpu features they need, which the
+    // build enables for the whole crate
+    #[cfg(target_feature = "avx2")]
+    mod lanes {
+        use std::arch::x86_64::*;
+
+        pub type Vector = __m256i;
+        pub const WIDTH: usize = 16;
+
+        #[inline(always)]
+        pub fn load(row: &[i16]) -> Vector {
+            // SAFETY: callers pass WIDTH values, loadu has no alignment requirement
+            return unsafe { _mm256_loadu_si256(row.as_ptr() as *const Vector) };
+        }
+        #[inline(always)]
+        pub fn store(row: &mut [i16], v: Vector) {
+            // SAFETY: as in load
+            unsafe { _mm256_storeu_si256(row.as_mut_ptr() as *mut Vector, v) };
+        }
+        #[inline(always)]
+        pub fn add_16(a: Vector, b: Vector) -> Vector {
+            return unsafe { _mm256_add_epi16(a, b) };
+        }
+        #[inline(always)]
+        pub fn sub_16(a: Vector, b: Vector) -> Vector {
+            return unsafe { _mm256_sub_epi16(a, b) };
+        }
+        #[inline(always)]
+        pub fn clamp_16(v: Vector, max: i16) -> Vector {
+            return unsafe { _mm256_min_epi16(_mm256_max_epi16(v, _mm256_setzero_si256()), _mm256_set1_epi16(max)) };
+        }
+        // products of neighbouring i16 pairs summed into i32 lanes
+        #[inline(always)]
+        pub fn madd_16(a: Vector, b: Vector) -> Vector {
+            return unsafe { _mm256_madd_epi16(a, b) };
+        }
+        #[inline(always)]
+        pub fn add_32(a: Vector, b: Vector) -> Vector {
+            return unsafe { _mm256_add_epi32(a, b) };
+        }
+        #[inline(always)]
+        pub fn zero() -> Vector {
+            return unsafe { _mm256_setzero_si256() };
+        }
+        #[inline(always)]
+        pub fn sum_32(v: Vector) -> i32 {
+            unsafe {
+                let v = _mm_add_epi32(_mm256_castsi256_si128(v), _mm256_extracti128_si256(v, 1));
+                let v = _mm_add_epi32(v, _mm_shuffle_epi32(v, 0b01_00_11_10));
+                let v = _mm_add_epi32(v, _mm_shuffle_epi32(v, 0b10_11_00_01));
+                return _mm_cvtsi128_si32(v);
+            }
+        }
+    }
+    #[cfg(not(target_feature = "avx2"))]
+    mod lanes {
+        use std::arch::x86_64::*;
+
+        pub type Vector = __m128i;
+        pub const WIDTH: usize = 8;
+
+        #[inline(always)]
+        pub fn load(row: &[i16]) -> Vector {
+            // SAFETY: callers pass WIDTH values, loadu has no alignment requirement
+            return unsafe { _mm_loadu_si128(row.as_ptr() as *const Vector) };
+        }
+        #[inline(always)]
+        pub fn store(row: &mut [i16], v: Vector) {
+            // SAFETY: as in load
+            unsafe { _mm_storeu_si128(row.as_mut_ptr() as *mut Vector, v) };
+        }
+        #[inline(always)]
+        pub fn add_16(a: Vector, b: Vector) -> Vector {
+            return unsafe { _mm_add_epi16(a, b) };
+        }
+        #[inline(always)]
+        pub fn sub_16(a: Vector, b: Vector) -> Vector {
+            return unsafe { _mm_sub_epi16(a, b) };
+        }
+        #[inline(always)]
+        pub fn clamp_16(v: Vector, max: i16) -> Vector {
+            return unsafe { _mm_min_epi16(_mm_max_epi16(v, _mm_setzero_si128()), _mm_set1_epi16(max)) };
+        }
+        // products of neighbouring i16 pairs summed into i32 lanes
+        #[inline(always)]
+        pub fn madd_16(a: Vector, b: Vector) -> Vector {
+            return unsafe { _mm_madd_epi16(a, b) };
+        }
+        #[inline(always)]
+        pub fn add_32(a: Vector, b: Vector) -> Vector {
+            return unsafe { _mm_add_epi32(a, b) };
+        }
+        #[inline(always)]
+        pub fn zero() -> Vector {
+            return unsafe { _mm_setzero_si128() };
+        }
+        #[inline(always)]
+        pub fn sum_32(v: Vector) -> i32 {
+            unsafe {
+                let v = _mm_add_epi32(v, _mm_shuffle_epi32(v, 0b01_00_11_10));
+                let v = _mm_add_epi32(v, _mm_shuffle_epi32(v, 0b10_11_00_01));
+                return _mm_cvtsi128_si32(v);
+            }
+        }
+    }
+    use lanes::*;
+
+    // i16 lanes wrap like the scalar wrapping_add and wrapping_sub
+    #[inline(always)]
+    pub fn add(values: &mut [i16; HIDDEN], weights: &[i16; HIDDEN]) {
+        for (value, weight) in values.chunks_exact_mut(WIDTH).zip(weights.chunks_exact(WIDTH)) {
+            store(value, add_16(load(value), load(weight)));
+        }
+    }
+    #[inline(always)]
+    pub fn sub(values: &mut [i16; HIDDEN], weights: &[i16; HIDDEN]) {
+        for (value, weight) in values.chunks_exact_mut(WIDTH).zip(weights.chunks_exact(WIDTH)) {
+            store(value, sub_16(load(value), load(weight)));
+        }
+    }
+    // a clipped value is at most QA so a madd pair stays far inside i32
+    pub fn output(us: &[i16; HIDDEN], them: &[i16; HIDDEN], weights: &[[i16; HIDDEN]; 2]) -> i32 {
+        let mut sum = zero();
+        for (half, weights) in [us, them].into_iter().zip(weights) {
+            for (value, weight) in half.chunks_exact(WIDTH).zip(weights.chunks_exact(WIDTH)) {
+                sum = add_32(sum, madd_16(clamp_16(load(value), QA as i16), load(weight)));
+            }
+        }
+        return sum_32(sum);
+    }
+}
@@ -0,0 +1,70 @@
+use crate::{
+    core::{Board, fen::START_POSITION_FEN, move_list::MoveList, movegen::generate_all_moves, r#move::MoveUtil},
+    evaluation::nnue::{self, Accumulator, Network},
+};
+
+// castling, en passant and promotions in both colors, chess960 castling included
+const POSITIONS: [&str; 6] = [
+    START_POSITION_FEN,
+    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+    "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+    "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+    "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+    "1nbbnrkr/p1p1ppp1/3p4/1p3P1p/3Pq2P/8/PPP1P1P1/QNBBNRKR w HFhf - 0 9",
+];
+// the incrementally updated accumulator must equal a full refresh at every node and be
+// restored exactly by unmake_move, and the simd code must agree with the scalar code
+fn verify_accumulators(board: &mut Board, depth: usize, network: &Network) -> bool {
+    if board.accumulator != Accumulator::refresh(board, network) {
+        println!("accumulator differs from a refresh in {}", board.to_fen());
+        return false;
+    }
+    // the simd rows and output layer against plain loops
+    if board.accumulator != Accumulator::refresh_scalar(board, network) {
+        println!("simd accumulator differs from the scalar one in {}", board.to_fen());
+        return false;
+    }
+    let (simd, scalar) = (
+        network.evaluate(&board.accumulator, board.turn),
+        network.evaluate_scalar(&board.accumulator, board.turn),
+    );
+    if simd != scalar {
+        println!("simd eval {} differs from the scalar eval {} in {}", simd, scalar, board.to_fen());
+        return false;
+    }
+    if depth == 0 {
+        return true;
+    }
+    let mut moves = MoveList::new();
+    generate_all_moves(board, &mut moves);
+    for m in &moves {
+        let before = *board;
+        let state = board.make_move(m);
+        let passed = verify_accumulators(board, depth - 1, network);
+        board.unmake_move(m, &state);
+        if !passed {
+            println!("after {}", m.to_uci(board.chess960));
+            return false;
+        }
+        if before != *board {
+            println!("unmaking {} in {} did not restore the board", m.to_uci(board.chess960), board.to_fen());
+            return false;
+        }
+    }
+    return true;
+}
+pub fn test_nnue() -> bool {
+    let (previous, was_enabled) = nnue::replace_network(Box::into_raw(Network::random(0x5DEECE66D)), true);
+    let network = nnue::active().unwrap();
+    let mut passed = true;
+    for fen in POSITIONS {
+        let mut board = Board::from_fen(fen).unwrap();
+        passed &= verify_accumulators(&mut board, 2, network);
+    }
+    let (test_network, _) = nnue::replace_network(previous, was_enabled);
+    drop(unsafe { Box::from_raw(test_network) });
+    if passed {
+        println!("nnue test completed successfully");
+    }
+    return passed;
+}
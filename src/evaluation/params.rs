@@ -0,0 +1,227 @@
+use crate::{
+    core::{Board, Color, Piece, bitboard::BitBoardUtil, square::Square},
+    evaluation::score::{PackedScore, packed},
+};
+
+// every weight of the classical evaluation as a (middlegame, endgame) pair unless it is only a
+// shape parameter. tables from white's point of view with a1 first, black uses them mirrored
+// and negated. only i16 fields so the tuner can treat the struct as one flat slice
+#[repr(C)]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct EvalParams {
+    pub piece_values: [[i16; 2]; 5],
+    pub pst: [[[i16; 2]; 64]; 6],
+    pub unhealthy_pawn: [i16; 2],
+    pub open_unhealthy_pawn: [i16; 2],
+    pub passed_pawn: [[i16; 2]; 7],
+    pub bishop_pair: [i16; 2],
+    // the move counts at which knight, bishop and rook mobility is neutral
+    pub mobility_offsets: [i16; 3],
+    pub knight_mobility: [i16; 2],
+    pub bishop_mobility: [i16; 2],
+    pub rook_mobility: [i16; 2],
+    pub mop_up_center: [i16; 2],
+    pub mop_up_king_distance: [i16; 2],
+    pub rook_closed_file: [i16; 2],
+    pub rook_open_file: [i16; 2],
+    pub rook_open_king_adjacent: [i16; 2],
+    pub rook_open_king_file: [i16; 2],
+    pub rook_semi_open_king_adjacent: [i16; 2],
+    pub rook_semi_open_king_file: [i16; 2],
+    pub queen_proximity: [i16; 2],
+    pub seventh_rank_rook: [i16; 2],
+    pub seventh_rank_queen: [i16; 2],
+    pub tempo: [i16; 2],
+    // pieces attacked and not defended, pieces attacked by pawns, rooks and queens attacked by
+    // minors and queens attacked by rooks
+    pub hanging: [i16; 2],
+    pub threat_by_pawn: [i16; 2],
+    pub threat_by_minor: [i16; 2],
+    pub threat_by_rook: [i16; 2],
+    // a knight, bishop, rook or queen can check from a square the enemy does not defend
+    pub safe_checks: [[i16; 2]; 4],
+    // a bishop or rook pinning or skewering a queen or king, or a queen one onto the king
+    pub xray_threat: [i16; 2],
+    // king safety only counts in the middlegame
+    pub pawn_shield: i16,
+    pub pawn_storm: [i16; 8],
+    // knight, bishop, rook and queen
+    pub king_attacker_values: [i16; 4],
+    pub king_attack_scale: i16,
+    // percent of the attack counted for the number of attackers
+    pub king_attack_weights: [i16; 8],
+}
+
+// name and dimensions of every field in declaration order
+const FIELDS: [(&str, &[usize]); 33] = [
+    ("piece_values", &[5, 2]),
+    ("pst", &[6, 64, 2]),
+    ("unhealthy_pawn", &[2]),
+    ("open_unhealthy_pawn", &[2]),
+    ("passed_pawn", &[7, 2]),
+    ("bishop_pair", &[2]),
+    ("mobility_offsets", &[3]),
+    ("knight_mobility", &[2]),
+    ("bishop_mobility", &[2]),
+    ("rook_mobility", &[2]),
+    ("mop_up_center", &[2]),
+    ("mop_up_king_distance", &[2]),
+    ("rook_closed_file", &[2]),
+    ("rook_open_file", &[2]),
+    ("rook_open_king_adjacent", &[2]),
+    ("rook_open_king_file", &[2]),
+    ("rook_semi_open_king_adjacent", &[2]),
+    ("rook_semi_open_king_file", &[2]),
+    ("queen_proximity", &[2]),
+    ("seventh_rank_rook", &[2]),
+    ("seventh_rank_queen", &[2]),
+    ("tempo", &[2]),
+    ("hanging", &[2]),
+    ("threat_by_pawn", &[2]),
+    ("threat_by_minor", &[2]),
+    ("threat_by_rook", &[2]),
+    ("safe_checks", &[4, 2]),
+    ("xray_threat", &[2]),
+    ("pawn_shield", &[]),
+    ("pawn_storm", &[8]),
+    ("king_attacker_values", &[4]),
+    ("king_attack_scale", &[]),
+    ("king_attack_weights", &[8]),
+];
+pub const PARAM_COUNT: usize = size_of::<EvalParams>() / 2;
+const fn field_size(dims: &[usize]) -> usize {
+    let mut size = 1;
+    let mut i = 0;
+    while i < dims.len() {
+        size *= dims[i];
+        i += 1;
+    }
+    return size;
+}
+const _: () = {
+    let mut count = 0;
+    let mut i = 0;
+    while i < FIELDS.len() {
+        count += field_size(FIELDS[i].1);
+        i += 1;
+    }
+    assert!(count == PARAM_COUNT);
+};
+// the piece square tables in the flat slice, (mg, eg) for every piece and square
+pub const PST_OFFSET: usize = 10;
+pub const TABLES_END: usize = PST_OFFSET + 6 * 64 * 2;
+
+const fn mirror(table: [[i16; 2]; 64]) -> [PackedScore; 64] {
+    let mut mirrored = [0; 64];
+    let mut sq = 0;
+    while sq < 64 {
+        mirrored[sq] = -packed(table[sq ^ 56]);
+        sq += 1;
+    }
+    return mirrored;
+}
+
+impl EvalParams {
+    pub fn values(&self) -> &[i16] {
+        return unsafe { std::slice::from_raw_parts(self as *const EvalParams as *const i16, PARAM_COUNT) };
+    }
+    pub fn values_mut(&mut self) -> &mut [i16] {
+        return unsafe { std::slice::from_raw_parts_mut(self as *mut EvalParams as *mut i16, PARAM_COUNT) };
+    }
+    // the piece square tables of both colors for the board's incremental pst
+    pub const fn color_pst(&self) -> [[[PackedScore; 64]; 6]; 2] {
+        let mut tables = [[[0; 64]; 6]; 2];
+        let mut piece = 0;
+        while piece < 6 {
+            let mut sq = 0;
+            while sq < 64 {
+                tables[0][piece][sq] = packed(self.pst[piece][sq]);
+                sq += 1;
+            }
+            tables[1][piece] = mirror(self.pst[piece]);
+            piece += 1;
+        }
+        return tables;
+    }
+    #[inline(always)]
+    pub fn pst_value(&self, color: Color, piece: Piece, square: Square) -> PackedScore {
+        if color == Color::White {
+            return packed(self.pst[piece as usize][square as usize]);
+        }
+        return -packed(self.pst[piece as usize][(square ^ 56) as usize]);
+    }
+    // every piece of each color, what the board keeps incrementally
+    pub fn board_pst(&self, board: &Board) -> [PackedScore; 2] {
+        let mut value = [0; 2];
+        for color in [Color::White, Color::Black] {
+            let mut pieces = board.color_combined[color as usize];
+            while pieces != 0 {
+                let sq = pieces.pop_lsb();
+                value[color as usize] += self.pst_value(color, board.piece_on(sq).unwrap(), sq);
+            }
+        }
+        return value;
+    }
+    pub fn king_attack_weight(&self, attackers: u32) -> f32 {
+        return self.king_attack_weights[(attackers as usize).min(7)] as f32 / 100.0;
+    }
+
+    // the same layout as evaluation/defs.rs so tuned values can be pasted over it
+    pub fn rust_source(&self) -> String {
+        let mut out = String::from("use crate::evaluation::params::EvalParams;\n\n");
+        out.push_str("#[rustfmt::skip]\npub const DEFAULT_PARAMS: EvalParams = EvalParams {\n");
+        self.for_each_field(|name, dims, values| {
+            out.push_str(&format!("    {}: {},\n", name, format_values(values, dims, 4, true)));
+        });
+        out.push_str("};\n");
+        return out;
+    }
+    pub fn json(&self) -> String {
+        let mut fields = vec![];
+        self.for_each_field(|name, dims, values| {
+            fields.push(format!("  \"{}\": {}", name, format_values(values, dims, 2, false)));
+        });
+        return format!("{{\n{}\n}}\n", fields.join(",\n"));
+    }
+    fn for_each_field(&self, mut f: impl FnMut(&str, &[usize], &[i16])) {
+        let values = self.values();
+        let mut offset = 0;
+        for (name, dims) in FIELDS {
+            let size = field_size(dims);
+            f(name, dims, &values[offset..offset + size]);
+            offset += size;
+        }
+    }
+}
+
+// nested arrays, the ones of up to 16 values on one line and tables eight entries per line,
+// rust and json only differ in the trailing commas
+fn format_values(values: &[i16], dims: &[usize], indent: usize, trailing: bool) -> String {
+    if dims.is_empty() {
+        return values[0].to_string();
+    }
+    let inner = values.len() / dims[0];
+    let elements = values.chunks(inner);
+    if values.len() <= 16 {
+        let elements: Vec<String> = elements.map(|e| format_values(e, &dims[1..], 0, false)).collect();
+        return format!("[{}]", elements.join(", "));
+    }
+    let pad = " ".repeat(indent + 4);
+    let lines: Vec<String> = if inner <= 16 {
+        let elements: Vec<String> = elements.map(|e| format_padded(e, &dims[1..])).collect();
+        elements.chunks(8).map(|line| format!("{}{}", pad, line.join(", "))).collect()
+    } else {
+        elements
+            .map(|e| format!("{}{}", pad, format_values(e, &dims[1..], indent + 4, trailing)))
+            .collect()
+    };
+    let last = if trailing { "," } else { "" };
+    return format!("[\n{}{}\n{}]", lines.join(",\n"), last, " ".repeat(indent));
+}
+fn format_padded(values: &[i16], dims: &[usize]) -> String {
+    if dims.is_empty() {
+        return format!("{:>4}", values[0]);
+    }
+    let values: Vec<String> = values.iter().map(|v| format!("{:>4}", v)).collect();
+    return format!("[{}]", values.join(","));
+}
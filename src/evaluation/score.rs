@@ -0,0 +1,38 @@
+// a middlegame and an endgame value in one integer, the endgame half in the upper 16 bits, so
+// terms can be added and scaled once and blended by the phase at the end
+pub type PackedScore = i32;
+
+// phase contribution of pawns, knights, bishops, rooks, queens and kings
+pub const PHASE_WEIGHTS: [u8; 6] = [0, 1, 1, 2, 4, 0];
+// all pieces of the start position, promotions can go above it
+pub const MAX_PHASE: i32 = 24;
+
+pub const fn s(mg: i16, eg: i16) -> PackedScore {
+    return ((eg as i32) << 16) + mg as i32;
+}
+#[inline(always)]
+pub const fn packed(pair: [i16; 2]) -> PackedScore {
+    return s(pair[0], pair[1]);
+}
+
+pub trait PackedScoreUtil {
+    fn mg(self) -> i16;
+    fn eg(self) -> i16;
+    fn interpolate(self, phase: u8) -> i16;
+}
+impl PackedScoreUtil for PackedScore {
+    #[inline(always)]
+    fn mg(self) -> i16 {
+        return self as i16;
+    }
+    #[inline(always)]
+    fn eg(self) -> i16 {
+        return ((self + 0x8000) >> 16) as i16;
+    }
+    // phase MAX_PHASE is the pure middlegame and 0 the pure endgame
+    #[inline(always)]
+    fn interpolate(self, phase: u8) -> i16 {
+        let phase = (phase as i32).min(MAX_PHASE);
+        return ((self.mg() as i32 * phase + self.eg() as i32 * (MAX_PHASE - phase)) / MAX_PHASE) as i16;
+    }
+}
@@ -0,0 +1,147 @@
+use std::fs;
+
+use crate::{
+    core::{Board, fen::{START_POSITION_FEN, fen_fields}, move_list::MoveList, movegen::generate_all_moves},
+    evaluation::{
+        defs::DEFAULT_PARAMS,
+        evaluate::{TERM_COUNT, TERM_NAMES, Terms, classical_eval, evaluate, evaluate_terms},
+        score::PackedScoreUtil,
+    },
+    transposition_table::TranspositionTable,
+};
+
+// open and semi open files next to castled kings, pawn storms, passers, rooks on the seventh and
+// known endgames for both colors, every position is also walked two plies deep
+const POSITIONS: [&str; 11] = [
+    START_POSITION_FEN,
+    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+    "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+    "2kr3r/ppq2p2/2n1p1p1/2bpP1Pp/5P2/2P1BN2/PP1Q3P/R4RK1 b - - 1 17",
+    "r1b2rk1/pp3ppp/2n5/q2p4/3P4/P1PB1N2/5PPP/R2QK2R w KQ - 0 12",
+    "6k1/1R3p2/6p1/2p4p/2r4P/6P1/5PK1/8 b - - 3 41",
+    "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+    "1nbbnrkr/p1p1ppp1/3p4/1p3P1p/3Pq2P/8/PPP1P1P1/QNBBNRKR w HFhf - 0 9",
+    "8/8/8/4k3/8/8/2B5/4K1N1 w - - 0 1",
+    "8/8/4k3/8/1p6/8/3K4/6R1 w - - 0 1",
+    "8/5b2/8/3k1p2/2p5/2P1B3/3K4/8 w - - 0 1",
+];
+
+struct Asymmetry {
+    count: usize,
+    // the first position the term differs in with the white and black values of the position
+    // and the black and white values of its mirror
+    example: Option<(String, [i32; 2], [i32; 2])>,
+}
+
+fn collect_positions(board: &mut Board, depth: usize, positions: &mut Vec<Board>) {
+    positions.push(*board);
+    if depth == 0 {
+        return;
+    }
+    let mut moves = MoveList::new();
+    generate_all_moves(board, &mut moves);
+    for m in &moves {
+        let state = board.make_move(m);
+        collect_positions(board, depth - 1, positions);
+        board.unmake_move(m, &state);
+    }
+}
+fn load_positions(path: Option<&str>) -> Result<Vec<Board>, String> {
+    let mut positions = vec![];
+    let Some(path) = path else {
+        for fen in POSITIONS {
+            collect_positions(&mut Board::from_fen(fen).unwrap(), 2, &mut positions);
+        }
+        return Ok(positions);
+    };
+    let text = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        // "<fen> ;D1 20" and "<fen> [1.0]" lines
+        let (fen, _) = fen_fields(line.split(';').next().unwrap_or(""));
+        let board = Board::from_fen(&fen).map_err(|e| format!("line {}: invalid fen {}: {}", i + 1, fen, e))?;
+        positions.push(board);
+    }
+    return Ok(positions);
+}
+
+// the classical eval of every position has to equal the one of its mirror from the side to
+// move's point of view, each term is compared on its own so the broken one is named
+pub fn test_symmetry(path: Option<&str>) -> bool {
+    let positions = match load_positions(path) {
+        Ok(positions) => positions,
+        Err(e) => {
+            println!("info string could not read positions: {}", e);
+            return false;
+        }
+    };
+    let params = &DEFAULT_PARAMS;
+    let mut asymmetries: Vec<Asymmetry> = (0..TERM_COUNT).map(|_| Asymmetry { count: 0, example: None }).collect();
+    let tt = TranspositionTable::new(16);
+    let mut failed = 0;
+    let mut final_failed = 0;
+    for board in &positions {
+        let mirror = board.mirror();
+        // a chess960 position without castling rights reads back as a standard one
+        let mut parsed = Board::from_fen(&mirror.to_fen()).unwrap();
+        parsed.chess960 = mirror.chess960;
+        if parsed != mirror || mirror.mirror().to_fen() != board.to_fen() {
+            println!("mirroring {} does not give a consistent board", board.to_fen());
+            failed += 1;
+            continue;
+        }
+        let terms: Terms = evaluate_terms(board, params, None);
+        let mirrored: Terms = evaluate_terms(&mirror, params, None);
+        let mut symmetric = true;
+        for i in 0..TERM_COUNT {
+            if terms[i] == [mirrored[i][1], mirrored[i][0]] {
+                continue;
+            }
+            symmetric = false;
+            asymmetries[i].count += 1;
+            if asymmetries[i].example.is_none() {
+                asymmetries[i].example = Some((board.to_fen(), terms[i], [mirrored[i][1], mirrored[i][0]]));
+            }
+        }
+        if !symmetric || classical_eval(board, params, None) != -classical_eval(&mirror, params, None) {
+            failed += 1;
+        } else if evaluate(board, &tt) != evaluate(&mirror, &tt) {
+            // the terms agree, so a known endgame or a scale factor is asymmetric
+            if final_failed == 0 {
+                println!("the final evaluation of {} differs from its mirror", board.to_fen());
+            }
+            final_failed += 1;
+            failed += 1;
+        }
+    }
+    for (name, asymmetry) in TERM_NAMES.iter().zip(&asymmetries) {
+        let Some((fen, value, mirrored)) = &asymmetry.example else {
+            continue;
+        };
+        println!("{} is asymmetric in {} of {} positions", name, asymmetry.count, positions.len());
+        println!(
+            "  {}: white {} {} black {} {}, mirrored white {} {} black {} {}",
+            fen,
+            value[0].mg(),
+            value[0].eg(),
+            value[1].mg(),
+            value[1].eg(),
+            mirrored[0].mg(),
+            mirrored[0].eg(),
+            mirrored[1].mg(),
+            mirrored[1].eg()
+        );
+    }
+    if final_failed != 0 {
+        println!("the final evaluation is asymmetric in {} of {} positions", final_failed, positions.len());
+    }
+    if failed != 0 {
+        println!("symmetry test failed in {} of {} positions", failed, positions.len());
+        return false;
+    }
+    println!("symmetry test completed successfully, {} positions", positions.len());
+    return true;
+}
@@ -1,350 +1,145 @@
-use crate::core::{bitboard::BitBoard, square::Square, Color, Piece};
+use crate::{
+    core::{bitboard::BitBoard, square::Square, Color, Piece},
+    evaluation::{defs::DEFAULT_PARAMS, score::PackedScore},
+};
 
-#[rustfmt::skip]
-const PIECE_SQUARE_TABLES: [[[i16; 64]; 6]; 2] = [
-    [
-        [//white pawns
-            0 ,   0,   0,   0,   0,   0,   0,   0, 
-            5 ,  10,  10, -20, -20,  10,  10,   5, 
-            5 ,  -5, -10,   0,   0, -10,  -5,   5,
-            0 ,   0,   0,  20,  20,   0,   0,   0, 
-            5 ,   5,  10,  25,  25,  10,   5,   5, 
-            10,  10,  20,  30,  30,  20,  10,  10,
-            50,  50,  50,  50,  50,  50,  50,  50, 
-            0 ,   0,   0,   0,   0,   0,   0,   0,
-        ],
-        [//white knights
-            -50, -40, -30, -30, -30, -30, -40, -50, 
-            -40, -20,   0,   5,   5,   0, -20, -40, 
-            -30,   5,  10,  15,  15,  10,   5, -30, 
-            -30,   0,  15,  20,  20,  15,   0, -30, 
-            -30,   5,  15,  20,  20,  15,   5, -30,
-            -30,   0,  10,  15,  15,  10,   0, -30, 
-            -40, -20,   0,   0,   0,   0, -20, -40, 
-            -50, -40, -30, -30, -30, -30, -40, -50,
-        ],
-        [//white bishops
-            -20, -10, -10, -10, -10, -10, -10, -20, 
-            -10,   5,   0,   0,   0,   0,   5, -10, 
-            -10,  10,  10,  10,  10,  10,  10, -10, 
-            -10,   0,  10,  10,  10,  10,   0, -10, 
-            -10,   5,   5,  10,  10,   5,   5, -10, 
-            -10,   0,   5,  10,  10,   5,   0, -10, 
-            -10,   0,   0,   0,   0,   0,   0, -10, 
-            -20, -10, -10, -10, -10, -10, -10, -20,
-        ],
-        [//white rooks
-            0,  0,  0,  5,  5,  2,  0,  0, 
-           -5,  0,  0,  0,  0,  0,  0, -5, 
-           -5,  0,  0,  0,  0,  0,  0, -5, 
-           -5,  0,  0,  0,  0,  0,  0, -5, 
-           -5,  0,  0,  0,  0,  0,  0, -5, 
-           -5,  0,  0,  0,  0,  0,  0, -5, 
-            5, 10, 10, 10, 10, 10, 10,  5, 
-            0,  0,  0,  0,  0,  0,  0,  0,
-        ],
-        [//white queen
-            -20, -10, -10,  -5,  -5, -10, -10, -20, 
-            -10,   0,   0,   0,   0,   5,   0, -10, 
-            -10,   0,   5,   5,   5,   5,   5, -10, 
-            -5,    0,   5,   5,   5,   5,   0,   0, 
-            -5,    0,   5,   5,   5,   5,   0,  -5, 
-            -10,   0,   5,   5,   5,   5,   0, -10,
-            -10,   0,   0,   0,   0,   0,   0, -10, 
-            -20, -10, -10,  -5,  -5, -10, -10, -20,
-        ],
-        [
-             20,  30,  10,   0,   0,  10,  30,  20, 
-             20,  20,  -5,  -5,  -5,  -5,  20,  20, 
-            -10, -20, -20, -20, -20, -20, -20, -10, 
-            -20, -30, -30, -40, -40, -30, -30, -20, 
-            -30, -40, -40, -50, -50, -40, -40, -30, 
-            -40, -50, -50, -60, -60, -50, -50, -40, 
-            -60, -60, -60, -60, -60, -60, -60, -60, 
-            -80, -70, -70, -70, -70, -70, -70, -80,
-        ],
-    ],
-    [
-        [//black pawns
-              0,   0,   0,   0,   0,   0,   0,   0, 
-            -50, -50, -50, -50, -50, -50, -50, -50, 
-            -10, -10, -20, -30, -30, -20, -10, -10, 
-             -5,  -5, -10, -25, -25, -10,  -5,  -5, 
-              0,   0,   0, -20, -20,   0,   0,   0, 
-             -5,   5,  10,   0,   0,  10,   5,  -5,
-             -5, -10, -10,  20,  20, -10, -10,  -5, 
-              0,   0,   0,   0,   0,   0,   0,   0,
-        ],
-        [//black knights
-             50,  40,  30,  30,  30,  30,  40,  50, 
-             40,  20,   0,   0,   0,   0,  20,  40, 
-             30,   0, -10, -15, -15, -10,   0,  30, 
-             30,  -5, -15, -20, -20, -15,  -5,  30, 
-             30,   0, -15, -20, -20, -15,   0,  30,
-             30,  -5, -10, -15, -15, -10,  -5,  30, 
-             40,  20,   0,  -5,  -5,   0,  20,  40, 
-             50,  40,  30,  30,  30,  30,  40,  50,
-        ],
-        [//black bishops
-             20,  10,  10,  10,  10,  10,  10,  20, 
-             10,   0,   0,   0,   0,   0,   0,  10, 
-             10,   0,  -5, -10, -10,  -5,   0,  10, 
-             10,  -5,  -5, -10, -10,  -5,  -5,  10, 
-             10,   0, -10, -10, -10, -10,   0,  10, 
-             10, -10, -10, -10, -10, -10, -10,  10, 
-             10,  -5,   0,   0,   0,   0,  -5,  10, 
-             20,  10,  10,  10,  10,  10,  10,  20,
-        ],
-        [//black rooks
-              0,   0,   0,   0,   0,   0,   0,   0, 
-             -5, -10, -10, -10, -10, -10, -10,  -5, 
-              5,   0,   0,   0,   0,   0,   0,   5, 
-              5,   0,   0,   0,   0,   0,   0,   5, 
-              5,   0,   0,   0,   0,   0,   0,   5, 
-              5,   0,   0,   0,   0,   0,   0,   5, 
-              5,   0,   0,   0,   0,   0,   0,   5, 
-              0,   0,   0,  -5,  -5,  -2,   0,   0,
-        ],
-        [//black queens
-             20,  10,  10,   5,   5,  10,  10,  20, 
-             10,   0,   0,   0,   0,   0,   0,  10, 
-             10,   0,  -5,  -5,  -5,  -5,   0,  10, 
-              5,   0,  -5,  -5,  -5,  -5,   0,   5,  
-              0,   0,  -5,  -5,  -5,  -5,   0,   5, 
-             10,  -5,  -5,  -5,  -5,  -5,   0,  10, 
-             10,   0,  -5,   0,   0,   0,   0,  10, 
-             20,  10,  10,   5,   5,  10,  10,  20,
-        ],
-        [//black king
-             80,  70,  70,  70,  70,  70,  70,  80, 
-             60,  60,  60,  60,  60,  60,  60,  60, 
-             40,  50,  50,  60,  60,  50,  50,  40, 
-             30,  40,  40,  50,  50,  40,  40,  30, 
-             20,  30,  30,  40,  40,  30,  30,  20,  
-             10,  20,  20,  20,  20,  20,  20,  10, 
-            -20, -20,   5,   5,   5,   5, -20, -20, 
-            -20, -30, -10,   0,   0, -10, -30, -20,
-        ],
-    ],
-];
-#[rustfmt::skip]
-const KING_SQUARE_TABLES: [[i16; 64]; 2] = [
-    [
-        -50, -30, -30, -30, -30, -30, -30, -50, 
-        -30, -25,   0,   0,   0,   0, -25, -30, 
-        -25, -20,  20,  25,  25,  20, -20, -25, 
-        -20, -15,  30,  40,  40,  30, -15, -20, 
-        -15, -10,  35,  45,  45,  35, -10, -15, 
-        -10,  -5,  20,  30,  30,  20,  -5, -10, 
-         -5,   0,   5,   5,   5,   5,   0,  -5, 
-        -20, -10, -10, -10, -10, -10, -10, -20,
-    ],
-    [
-        20,  10,   10,  10,  10,  10,  10,  20, 
-         5,   0,   -5,  -5,  -5,  -5,   0,   5, 
-        10,   5,  -20, -30, -30, -20,   5,  10, 
-        15,  10,  -35, -45, -45, -35,  10,  15, 
-        20,  15,  -30, -40, -40, -30,  15,  20, 
-        25,  20,  -20, -25, -25, -20,  20,  25, 
-        30,  25,    0,   0,   0,   0,  25,  30, 
-        50,  30,   30,  30,  30,  30,  30,  50,
-    ],
-];
-#[rustfmt::skip]
-const PAWN_SQUARE_TABLES: [[i16; 64]; 2] = [
-    [
-         0,  0,  0,  0,  0,  0,  0,  0, 
-        10, 10, 10, 10, 10, 10, 10, 10, 
-        10, 10, 10, 10, 10, 10, 10, 10, 
-        20, 20, 20, 20, 20, 20, 20, 20, 
-        30, 30, 30, 30, 30, 30, 30, 30, 
-        50, 50, 50, 50, 50, 50, 50, 50,
-        80, 80, 80, 80, 80, 80, 80, 80, 
-         0,  0,  0,  0,  0,  0,  0,  0,
-    ],
-    [
-          0,   0,   0,   0,   0,   0,   0,   0, 
-        -80, -80, -80, -80, -80, -80, -80, -80, 
-        -50, -50, -50, -50, -50, -50, -50, -50, 
-        -30, -30, -30, -30, -30, -30, -30, -30, 
-        -20, -20, -20, -20, -20, -20, -20, -20, 
-        -10, -10, -10, -10, -10, -10, -10, -10, 
-        -10, -10, -10, -10, -10, -10, -10, -10,
-         0 ,   0,   0,   0,   0,   0,   0,   0,
-    ],
-];
-#[rustfmt::skip]
-const DISTANCE_FROM_CENTER: [u8; 64] = [
-    6, 5, 4, 3, 3, 4, 5, 6, 
-    5, 4, 3, 2, 2, 3, 4, 5, 
-    4, 3, 2, 1, 1, 2, 3, 4, 
-    3, 2, 1, 0, 0, 1, 2, 3, 
-    3, 2, 1, 0, 0, 1, 2, 3, 
-    4, 3, 2, 1, 1, 2, 3, 4, 
-    5, 4, 3, 2, 2, 3, 4, 5, 
-    6, 5, 4, 3, 3, 4, 5, 6
-];
-#[rustfmt::skip]
-const ORTHOGONAL_DISTANCE : [[u8; 64]; 64] = [
-  [0, 1, 2, 3, 4, 5, 6, 7, 1, 2, 3, 4, 5, 6, 7, 8, 2, 3, 4, 5, 6, 7, 8, 9, 3, 4, 5, 6, 7, 8, 9, 10, 4, 5, 6, 7, 8, 9, 10, 11, 5, 6, 7, 8, 9, 10, 11, 12, 6, 7, 8, 9, 10, 11, 12, 13, 7, 8, 9, 10, 11, 12, 13, 14],
-  [1, 0, 1, 2, 3, 4, 5, 6, 2, 1, 2, 3, 4, 5, 6, 7, 3, 2, 3, 4, 5, 6, 7, 8, 4, 3, 4, 5, 6, 7, 8, 9, 5, 4, 5, 6, 7, 8, 9, 10, 6, 5, 6, 7, 8, 9, 10, 11, 7, 6, 7, 8, 9, 10, 11, 12, 8, 7, 8, 9, 10, 11, 12, 13],
-  [2, 1, 0, 1, 2, 3, 4, 5, 3, 2, 1, 2, 3, 4, 5, 6, 4, 3, 2, 3, 4, 5, 6, 7, 5, 4, 3, 4, 5, 6, 7, 8, 6, 5, 4, 5, 6, 7, 8, 9, 7, 6, 5, 6, 7, 8, 9, 10, 8, 7, 6, 7, 8, 9, 10, 11, 9, 8, 7, 8, 9, 10, 11, 12],
-  [3, 2, 1, 0, 1, 2, 3, 4, 4, 3, 2, 1, 2, 3, 4, 5, 5, 4, 3, 2, 3, 4, 5, 6, 6, 5, 4, 3, 4, 5, 6, 7, 7, 6, 5, 4, 5, 6, 7, 8, 8, 7, 6, 5, 6, 7, 8, 9, 9, 8, 7, 6, 7, 8, 9, 10, 10, 9, 8, 7, 8, 9, 10, 11],
-  [4, 3, 2, 1, 0, 1, 2, 3, 5, 4, 3, 2, 1, 2, 3, 4, 6, 5, 4, 3, 2, 3, 4, 5, 7, 6, 5, 4, 3, 4, 5, 6, 8, 7, 6, 5, 4, 5, 6, 7, 9, 8, 7, 6, 5, 6, 7, 8, 10, 9, 8, 7, 6, 7, 8, 9, 11, 10, 9, 8, 7, 8, 9, 10],
-  [5, 4, 3, 2, 1, 0, 1, 2, 6, 5, 4, 3, 2, 1, 2, 3, 7, 6, 5, 4, 3, 2, 3, 4, 8, 7, 6, 5, 4, 3, 4, 5, 9, 8, 7, 6, 5, 4, 5, 6, 10, 9, 8, 7, 6, 5, 6, 7, 11, 10, 9, 8, 7, 6, 7, 8, 12, 11, 10, 9, 8, 7, 8, 9],
-  [6, 5, 4, 3, 2, 1, 0, 1, 7, 6, 5, 4, 3, 2, 1, 2, 8, 7, 6, 5, 4, 3, 2, 3, 9, 8, 7, 6, 5, 4, 3, 4, 10, 9, 8, 7, 6, 5, 4, 5, 11, 10, 9, 8, 7, 6, 5, 6, 12, 11, 10, 9, 8, 7, 6, 7, 13, 12, 11, 10, 9, 8, 7, 8],
-  [7, 6, 5, 4, 3, 2, 1, 0, 8, 7, 6, 5, 4, 3, 2, 1, 9, 8, 7, 6, 5, 4, 3, 2, 10, 9, 8, 7, 6, 5, 4, 3, 11, 10, 9, 8, 7, 6, 5, 4, 12, 11, 10, 9, 8, 7, 6, 5, 13, 12, 11, 10, 9, 8, 7, 6, 14, 13, 12, 11, 10, 9, 8, 7],
-  [1, 2, 3, 4, 5, 6, 7, 8, 0, 1, 2, 3, 4, 5, 6, 7, 1, 2, 3, 4, 5, 6, 7, 8, 2, 3, 4, 5, 6, 7, 8, 9, 3, 4, 5, 6, 7, 8, 9, 10, 4, 5, 6, 7, 8, 9, 10, 11, 5, 6, 7, 8, 9, 10, 11, 12, 6, 7, 8, 9, 10, 11, 12, 13],
-  [2, 1, 2, 3, 4, 5, 6, 7, 1, 0, 1, 2, 3, 4, 5, 6, 2, 1, 2, 3, 4, 5, 6, 7, 3, 2, 3, 4, 5, 6, 7, 8, 4, 3, 4, 5, 6, 7, 8, 9, 5, 4, 5, 6, 7, 8, 9, 10, 6, 5, 6, 7, 8, 9, 10, 11, 7, 6, 7, 8, 9, 10, 11, 12],
-  [3, 2, 1, 2, 3, 4, 5, 6, 2, 1, 0, 1, 2, 3, 4, 5, 3, 2, 1, 2, 3, 4, 5, 6, 4, 3, 2, 3, 4, 5, 6, 7, 5, 4, 3, 4, 5, 6, 7, 8, 6, 5, 4, 5, 6, 7, 8, 9, 7, 6, 5, 6, 7, 8, 9, 10, 8, 7, 6, 7, 8, 9, 10, 11],
-  [4, 3, 2, 1, 2, 3, 4, 5, 3, 2, 1, 0, 1, 2, 3, 4, 4, 3, 2, 1, 2, 3, 4, 5, 5, 4, 3, 2, 3, 4, 5, 6, 6, 5, 4, 3, 4, 5, 6, 7, 7, 6, 5, 4, 5, 6, 7, 8, 8, 7, 6, 5, 6, 7, 8, 9, 9, 8, 7, 6, 7, 8, 9, 10],
-  [5, 4, 3, 2, 1, 2, 3, 4, 4, 3, 2, 1, 0, 1, 2, 3, 5, 4, 3, 2, 1, 2, 3, 4, 6, 5, 4, 3, 2, 3, 4, 5, 7, 6, 5, 4, 3, 4, 5, 6, 8, 7, 6, 5, 4, 5, 6, 7, 9, 8, 7, 6, 5, 6, 7, 8, 10, 9, 8, 7, 6, 7, 8, 9],
-  [6, 5, 4, 3, 2, 1, 2, 3, 5, 4, 3, 2, 1, 0, 1, 2, 6, 5, 4, 3, 2, 1, 2, 3, 7, 6, 5, 4, 3, 2, 3, 4, 8, 7, 6, 5, 4, 3, 4, 5, 9, 8, 7, 6, 5, 4, 5, 6, 10, 9, 8, 7, 6, 5, 6, 7, 11, 10, 9, 8, 7, 6, 7, 8],
-  [7, 6, 5, 4, 3, 2, 1, 2, 6, 5, 4, 3, 2, 1, 0, 1, 7, 6, 5, 4, 3, 2, 1, 2, 8, 7, 6, 5, 4, 3, 2, 3, 9, 8, 7, 6, 5, 4, 3, 4, 10, 9, 8, 7, 6, 5, 4, 5, 11, 10, 9, 8, 7, 6, 5, 6, 12, 11, 10, 9, 8, 7, 6, 7],
-  [8, 7, 6, 5, 4, 3, 2, 1, 7, 6, 5, 4, 3, 2, 1, 0, 8, 7, 6, 5, 4, 3, 2, 1, 9, 8, 7, 6, 5, 4, 3, 2, 10, 9, 8, 7, 6, 5, 4, 3, 11, 10, 9, 8, 7, 6, 5, 4, 12, 11, 10, 9, 8, 7, 6, 5, 13, 12, 11, 10, 9, 8, 7, 6],
-  [2, 3, 4, 5, 6, 7, 8, 9, 1, 2, 3, 4, 5, 6, 7, 8, 0, 1, 2, 3, 4, 5, 6, 7, 1, 2, 3, 4, 5, 6, 7, 8, 2, 3, 4, 5, 6, 7, 8, 9, 3, 4, 5, 6, 7, 8, 9, 10, 4, 5, 6, 7, 8, 9, 10, 11, 5, 6, 7, 8, 9, 10, 11, 12],
-  [3, 2, 3, 4, 5, 6, 7, 8, 2, 1, 2, 3, 4, 5, 6, 7, 1, 0, 1, 2, 3, 4, 5, 6, 2, 1, 2, 3, 4, 5, 6, 7, 3, 2, 3, 4, 5, 6, 7, 8, 4, 3, 4, 5, 6, 7, 8, 9, 5, 4, 5, 6, 7, 8, 9, 10, 6, 5, 6, 7, 8, 9, 10, 11],
-  [4, 3, 2, 3, 4, 5, 6, 7, 3, 2, 1, 2, 3, 4, 5, 6, 2, 1, 0, 1, 2, 3, 4, 5, 3, 2, 1, 2, 3, 4, 5, 6, 4, 3, 2, 3, 4, 5, 6, 7, 5, 4, 3, 4, 5, 6, 7, 8, 6, 5, 4, 5, 6, 7, 8, 9, 7, 6, 5, 6, 7, 8, 9, 10],
-  [5, 4, 3, 2, 3, 4, 5, 6, 4, 3, 2, 1, 2, 3, 4, 5, 3, 2, 1, 0, 1, 2, 3, 4, 4, 3, 2, 1, 2, 3, 4, 5, 5, 4, 3, 2, 3, 4, 5, 6, 6, 5, 4, 3, 4, 5, 6, 7, 7, 6, 5, 4, 5, 6, 7, 8, 8, 7, 6, 5, 6, 7, 8, 9],
-  [6, 5, 4, 3, 2, 3, 4, 5, 5, 4, 3, 2, 1, 2, 3, 4, 4, 3, 2, 1, 0, 1, 2, 3, 5, 4, 3, 2, 1, 2, 3, 4, 6, 5, 4, 3, 2, 3, 4, 5, 7, 6, 5, 4, 3, 4, 5, 6, 8, 7, 6, 5, 4, 5, 6, 7, 9, 8, 7, 6, 5, 6, 7, 8],
-  [7, 6, 5, 4, 3, 2, 3, 4, 6, 5, 4, 3, 2, 1, 2, 3, 5, 4, 3, 2, 1, 0, 1, 2, 6, 5, 4, 3, 2, 1, 2, 3, 7, 6, 5, 4, 3, 2, 3, 4, 8, 7, 6, 5, 4, 3, 4, 5, 9, 8, 7, 6, 5, 4, 5, 6, 10, 9, 8, 7, 6, 5, 6, 7],
-  [8, 7, 6, 5, 4, 3, 2, 3, 7, 6, 5, 4, 3, 2, 1, 2, 6, 5, 4, 3, 2, 1, 0, 1, 7, 6, 5, 4, 3, 2, 1, 2, 8, 7, 6, 5, 4, 3, 2, 3, 9, 8, 7, 6, 5, 4, 3, 4, 10, 9, 8, 7, 6, 5, 4, 5, 11, 10, 9, 8, 7, 6, 5, 6],
-  [9, 8, 7, 6, 5, 4, 3, 2, 8, 7, 6, 5, 4, 3, 2, 1, 7, 6, 5, 4, 3, 2, 1, 0, 8, 7, 6, 5, 4, 3, 2, 1, 9, 8, 7, 6, 5, 4, 3, 2, 10, 9, 8, 7, 6, 5, 4, 3, 11, 10, 9, 8, 7, 6, 5, 4, 12, 11, 10, 9, 8, 7, 6, 5],
-  [3, 4, 5, 6, 7, 8, 9, 10, 2, 3, 4, 5, 6, 7, 8, 9, 1, 2, 3, 4, 5, 6, 7, 8, 0, 1, 2, 3, 4, 5, 6, 7, 1, 2, 3, 4, 5, 6, 7, 8, 2, 3, 4, 5, 6, 7, 8, 9, 3, 4, 5, 6, 7, 8, 9, 10, 4, 5, 6, 7, 8, 9, 10, 11],
-  [4, 3, 4, 5, 6, 7, 8, 9, 3, 2, 3, 4, 5, 6, 7, 8, 2, 1, 2, 3, 4, 5, 6, 7, 1, 0, 1, 2, 3, 4, 5, 6, 2, 1, 2, 3, 4, 5, 6, 7, 3, 2, 3, 4, 5, 6, 7, 8, 4, 3, 4, 5, 6, 7, 8, 9, 5, 4, 5, 6, 7, 8, 9, 10],
-  [5, 4, 3, 4, 5, 6, 7, 8, 4, 3, 2, 3, 4, 5, 6, 7, 3, 2, 1, 2, 3, 4, 5, 6, 2, 1, 0, 1, 2, 3, 4, 5, 3, 2, 1, 2, 3, 4, 5, 6, 4, 3, 2, 3, 4, 5, 6, 7, 5, 4, 3, 4, 5, 6, 7, 8, 6, 5, 4, 5, 6, 7, 8, 9],
-  [6, 5, 4, 3, 4, 5, 6, 7, 5, 4, 3, 2, 3, 4, 5, 6, 4, 3, 2, 1, 2, 3, 4, 5, 3, 2, 1, 0, 1, 2, 3, 4, 4, 3, 2, 1, 2, 3, 4, 5, 5, 4, 3, 2, 3, 4, 5, 6, 6, 5, 4, 3, 4, 5, 6, 7, 7, 6, 5, 4, 5, 6, 7, 8],
-  [7, 6, 5, 4, 3, 4, 5, 6, 6, 5, 4, 3, 2, 3, 4, 5, 5, 4, 3, 2, 1, 2, 3, 4, 4, 3, 2, 1, 0, 1, 2, 3, 5, 4, 3, 2, 1, 2, 3, 4, 6, 5, 4, 3, 2, 3, 4, 5, 7, 6, 5, 4, 3, 4, 5, 6, 8, 7, 6, 5, 4, 5, 6, 7],
-  [8, 7, 6, 5, 4, 3, 4, 5, 7, 6, 5, 4, 3, 2, 3, 4, 6, 5, 4, 3, 2, 1, 2, 3, 5, 4, 3, 2, 1, 0, 1, 2, 6, 5, 4, 3, 2, 1, 2, 3, 7, 6, 5, 4, 3, 2, 3, 4, 8, 7, 6, 5, 4, 3, 4, 5, 9, 8, 7, 6, 5, 4, 5, 6],
-  [9, 8, 7, 6, 5, 4, 3, 4, 8, 7, 6, 5, 4, 3, 2, 3, 7, 6, 5, 4, 3, 2, 1, 2, 6, 5, 4, 3, 2, 1, 0, 1, 7, 6, 5, 4, 3, 2, 1, 2, 8, 7, 6, 5, 4, 3, 2, 3, 9, 8, 7, 6, 5, 4, 3, 4, 10, 9, 8, 7, 6, 5, 4, 5],
-  [10, 9, 8, 7, 6, 5, 4, 3, 9, 8, 7, 6, 5, 4, 3, 2, 8, 7, 6, 5, 4, 3, 2, 1, 7, 6, 5, 4, 3, 2, 1, 0, 8, 7, 6, 5, 4, 3, 2, 1, 9, 8, 7, 6, 5, 4, 3, 2, 10, 9, 8, 7, 6, 5, 4, 3, 11, 10, 9, 8, 7, 6, 5, 4],
-  [4, 5, 6, 7, 8, 9, 10, 11, 3, 4, 5, 6, 7, 8, 9, 10, 2, 3, 4, 5, 6, 7, 8, 9, 1, 2, 3, 4, 5, 6, 7, 8, 0, 1, 2, 3, 4, 5, 6, 7, 1, 2, 3, 4, 5, 6, 7, 8, 2, 3, 4, 5, 6, 7, 8, 9, 3, 4, 5, 6, 7, 8, 9, 10],
-  [5, 4, 5, 6, 7, 8, 9, 10, 4, 3, 4, 5, 6, 7, 8, 9, 3, 2, 3, 4, 5, 6, 7, 8, 2, 1, 2, 3, 4, 5, 6, 7, 1, 0, 1, 2, 3, 4, 5, 6, 2, 1, 2, 3, 4, 5, 6, 7, 3, 2, 3, 4, 5, 6, 7, 8, 4, 3, 4, 5, 6, 7, 8, 9],
-  [6, 5, 4, 5, 6, 7, 8, 9, 5, 4, 3, 4, 5, 6, 7, 8, 4, 3, 2, 3, 4, 5, 6, 7, 3, 2, 1, 2, 3, 4, 5, 6, 2, 1, 0, 1, 2, 3, 4, 5, 3, 2, 1, 2, 3, 4, 5, 6, 4, 3, 2, 3, 4, 5, 6, 7, 5, 4, 3, 4, 5, 6, 7, 8],
-  [7, 6, 5, 4, 5, 6, 7, 8, 6, 5, 4, 3, 4, 5, 6, 7, 5, 4, 3, 2, 3, 4, 5, 6, 4, 3, 2, 1, 2, 3, 4, 5, 3, 2, 1, 0, 1, 2, 3, 4, 4, 3, 2, 1, 2, 3, 4, 5, 5, 4, 3, 2, 3, 4, 5, 6, 6, 5, 4, 3, 4, 5, 6, 7],
-  [8, 7, 6, 5, 4, 5, 6, 7, 7, 6, 5, 4, 3, 4, 5, 6, 6, 5, 4, 3, 2, 3, 4, 5, 5, 4, 3, 2, 1, 2, 3, 4, 4, 3, 2, 1, 0, 1, 2, 3, 5, 4, 3, 2, 1, 2, 3, 4, 6, 5, 4, 3, 2, 3, 4, 5, 7, 6, 5, 4, 3, 4, 5, 6],
-  [9, 8, 7, 6, 5, 4, 5, 6, 8, 7, 6, 5, 4, 3, 4, 5, 7, 6, 5, 4, 3, 2, 3, 4, 6, 5, 4, 3, 2, 1, 2, 3, 5, 4, 3, 2, 1, 0, 1, 2, 6, 5, 4, 3, 2, 1, 2, 3, 7, 6, 5, 4, 3, 2, 3, 4, 8, 7, 6, 5, 4, 3, 4, 5],
-  [10, 9, 8, 7, 6, 5, 4, 5, 9, 8, 7, 6, 5, 4, 3, 4, 8, 7, 6, 5, 4, 3, 2, 3, 7, 6, 5, 4, 3, 2, 1, 2, 6, 5, 4, 3, 2, 1, 0, 1, 7, 6, 5, 4, 3, 2, 1, 2, 8, 7, 6, 5, 4, 3, 2, 3, 9, 8, 7, 6, 5, 4, 3, 4],
-  [11, 10, 9, 8, 7, 6, 5, 4, 10, 9, 8, 7, 6, 5, 4, 3, 9, 8, 7, 6, 5, 4, 3, 2, 8, 7, 6, 5, 4, 3, 2, 1, 7, 6, 5, 4, 3, 2, 1, 0, 8, 7, 6, 5, 4, 3, 2, 1, 9, 8, 7, 6, 5, 4, 3, 2, 10, 9, 8, 7, 6, 5, 4, 3],
-  [5, 6, 7, 8, 9, 10, 11, 12, 4, 5, 6, 7, 8, 9, 10, 11, 3, 4, 5, 6, 7, 8, 9, 10, 2, 3, 4, 5, 6, 7, 8, 9, 1, 2, 3, 4, 5, 6, 7, 8, 0, 1, 2, 3, 4, 5, 6, 7, 1, 2, 3, 4, 5, 6, 7, 8, 2, 3, 4, 5, 6, 7, 8, 9],
-  [6, 5, 6, 7, 8, 9, 10, 11, 5, 4, 5, 6, 7, 8, 9, 10, 4, 3, 4, 5, 6, 7, 8, 9, 3, 2, 3, 4, 5, 6, 7, 8, 2, 1, 2, 3, 4, 5, 6, 7, 1, 0, 1, 2, 3, 4, 5, 6, 2, 1, 2, 3, 4, 5, 6, 7, 3, 2, 3, 4, 5, 6, 7, 8],
-  [7, 6, 5, 6, 7, 8, 9, 10, 6, 5, 4, 5, 6, 7, 8, 9, 5, 4, 3, 4, 5, 6, 7, 8, 4, 3, 2, 3, 4, 5, 6, 7, 3, 2, 1, 2, 3, 4, 5, 6, 2, 1, 0, 1, 2, 3, 4, 5, 3, 2, 1, 2, 3, 4, 5, 6, 4, 3, 2, 3, 4, 5, 6, 7],
-  [8, 7, 6, 5, 6, 7, 8, 9, 7, 6, 5, 4, 5, 6, 7, 8, 6, 5, 4, 3, 4, 5, 6, 7, 5, 4, 3, 2, 3, 4, 5, 6, 4, 3, 2, 1, 2, 3, 4, 5, 3, 2, 1, 0, 1, 2, 3, 4, 4, 3, 2, 1, 2, 3, 4, 5, 5, 4, 3, 2, 3, 4, 5, 6],
-  [9, 8, 7, 6, 5, 6, 7, 8, 8, 7, 6, 5, 4, 5, 6, 7, 7, 6, 5, 4, 3, 4, 5, 6, 6, 5, 4, 3, 2, 3, 4, 5, 5, 4, 3, 2, 1, 2, 3, 4, 4, 3, 2, 1, 0, 1, 2, 3, 5, 4, 3, 2, 1, 2, 3, 4, 6, 5, 4, 3, 2, 3, 4, 5],
-  [10, 9, 8, 7, 6, 5, 6, 7, 9, 8, 7, 6, 5, 4, 5, 6, 8, 7, 6, 5, 4, 3, 4, 5, 7, 6, 5, 4, 3, 2, 3, 4, 6, 5, 4, 3, 2, 1, 2, 3, 5, 4, 3, 2, 1, 0, 1, 2, 6, 5, 4, 3, 2, 1, 2, 3, 7, 6, 5, 4, 3, 2, 3, 4],
-  [11, 10, 9, 8, 7, 6, 5, 6, 10, 9, 8, 7, 6, 5, 4, 5, 9, 8, 7, 6, 5, 4, 3, 4, 8, 7, 6, 5, 4, 3, 2, 3, 7, 6, 5, 4, 3, 2, 1, 2, 6, 5, 4, 3, 2, 1, 0, 1, 7, 6, 5, 4, 3, 2, 1, 2, 8, 7, 6, 5, 4, 3, 2, 3],
-  [12, 11, 10, 9, 8, 7, 6, 5, 11, 10, 9, 8, 7, 6, 5, 4, 10, 9, 8, 7, 6, 5, 4, 3, 9, 8, 7, 6, 5, 4, 3, 2, 8, 7, 6, 5, 4, 3, 2, 1, 7, 6, 5, 4, 3, 2, 1, 0, 8, 7, 6, 5, 4, 3, 2, 1, 9, 8, 7, 6, 5, 4, 3, 2],
-  [6, 7, 8, 9, 10, 11, 12, 13, 5, 6, 7, 8, 9, 10, 11, 12, 4, 5, 6, 7, 8, 9, 10, 11, 3, 4, 5, 6, 7, 8, 9, 10, 2, 3, 4, 5, 6, 7, 8, 9, 1, 2, 3, 4, 5, 6, 7, 8, 0, 1, 2, 3, 4, 5, 6, 7, 1, 2, 3, 4, 5, 6, 7, 8],
-  [7, 6, 7, 8, 9, 10, 11, 12, 6, 5, 6, 7, 8, 9, 10, 11, 5, 4, 5, 6, 7, 8, 9, 10, 4, 3, 4, 5, 6, 7, 8, 9, 3, 2, 3, 4, 5, 6, 7, 8, 2, 1, 2, 3, 4, 5, 6, 7, 1, 0, 1, 2, 3, 4, 5, 6, 2, 1, 2, 3, 4, 5, 6, 7],
-  [8, 7, 6, 7, 8, 9, 10, 11, 7, 6, 5, 6, 7, 8, 9, 10, 6, 5, 4, 5, 6, 7, 8, 9, 5, 4, 3, 4, 5, 6, 7, 8, 4, 3, 2, 3, 4, 5, 6, 7, 3, 2, 1, 2, 3, 4, 5, 6, 2, 1, 0, 1, 2, 3, 4, 5, 3, 2, 1, 2, 3, 4, 5, 6],
-  [9, 8, 7, 6, 7, 8, 9, 10, 8, 7, 6, 5, 6, 7, 8, 9, 7, 6, 5, 4, 5, 6, 7, 8, 6, 5, 4, 3, 4, 5, 6, 7, 5, 4, 3, 2, 3, 4, 5, 6, 4, 3, 2, 1, 2, 3, 4, 5, 3, 2, 1, 0, 1, 2, 3, 4, 4, 3, 2, 1, 2, 3, 4, 5],
-  [10, 9, 8, 7, 6, 7, 8, 9, 9, 8, 7, 6, 5, 6, 7, 8, 8, 7, 6, 5, 4, 5, 6, 7, 7, 6, 5, 4, 3, 4, 5, 6, 6, 5, 4, 3, 2, 3, 4, 5, 5, 4, 3, 2, 1, 2, 3, 4, 4, 3, 2, 1, 0, 1, 2, 3, 5, 4, 3, 2, 1, 2, 3, 4],
-  [11, 10, 9, 8, 7, 6, 7, 8, 10, 9, 8, 7, 6, 5, 6, 7, 9, 8, 7, 6, 5, 4, 5, 6, 8, 7, 6, 5, 4, 3, 4, 5, 7, 6, 5, 4, 3, 2, 3, 4, 6, 5, 4, 3, 2, 1, 2, 3, 5, 4, 3, 2, 1, 0, 1, 2, 6, 5, 4, 3, 2, 1, 2, 3],
-  [12, 11, 10, 9, 8, 7, 6, 7, 11, 10, 9, 8, 7, 6, 5, 6, 10, 9, 8, 7, 6, 5, 4, 5, 9, 8, 7, 6, 5, 4, 3, 4, 8, 7, 6, 5, 4, 3, 2, 3, 7, 6, 5, 4, 3, 2, 1, 2, 6, 5, 4, 3, 2, 1, 0, 1, 7, 6, 5, 4, 3, 2, 1, 2],
-  [13, 12, 11, 10, 9, 8, 7, 6, 12, 11, 10, 9, 8, 7, 6, 5, 11, 10, 9, 8, 7, 6, 5, 4, 10, 9, 8, 7, 6, 5, 4, 3, 9, 8, 7, 6, 5, 4, 3, 2, 8, 7, 6, 5, 4, 3, 2, 1, 7, 6, 5, 4, 3, 2, 1, 0, 8, 7, 6, 5, 4, 3, 2, 1],
-  [7, 8, 9, 10, 11, 12, 13, 14, 6, 7, 8, 9, 10, 11, 12, 13, 5, 6, 7, 8, 9, 10, 11, 12, 4, 5, 6, 7, 8, 9, 10, 11, 3, 4, 5, 6, 7, 8, 9, 10, 2, 3, 4, 5, 6, 7, 8, 9, 1, 2, 3, 4, 5, 6, 7, 8, 0, 1, 2, 3, 4, 5, 6, 7],
-  [8, 7, 8, 9, 10, 11, 12, 13, 7, 6, 7, 8, 9, 10, 11, 12, 6, 5, 6, 7, 8, 9, 10, 11, 5, 4, 5, 6, 7, 8, 9, 10, 4, 3, 4, 5, 6, 7, 8, 9, 3, 2, 3, 4, 5, 6, 7, 8, 2, 1, 2, 3, 4, 5, 6, 7, 1, 0, 1, 2, 3, 4, 5, 6],
-  [9, 8, 7, 8, 9, 10, 11, 12, 8, 7, 6, 7, 8, 9, 10, 11, 7, 6, 5, 6, 7, 8, 9, 10, 6, 5, 4, 5, 6, 7, 8, 9, 5, 4, 3, 4, 5, 6, 7, 8, 4, 3, 2, 3, 4, 5, 6, 7, 3, 2, 1, 2, 3, 4, 5, 6, 2, 1, 0, 1, 2, 3, 4, 5],
-  [10, 9, 8, 7, 8, 9, 10, 11, 9, 8, 7, 6, 7, 8, 9, 10, 8, 7, 6, 5, 6, 7, 8, 9, 7, 6, 5, 4, 5, 6, 7, 8, 6, 5, 4, 3, 4, 5, 6, 7, 5, 4, 3, 2, 3, 4, 5, 6, 4, 3, 2, 1, 2, 3, 4, 5, 3, 2, 1, 0, 1, 2, 3, 4],
-  [11, 10, 9, 8, 7, 8, 9, 10, 10, 9, 8, 7, 6, 7, 8, 9, 9, 8, 7, 6, 5, 6, 7, 8, 8, 7, 6, 5, 4, 5, 6, 7, 7, 6, 5, 4, 3, 4, 5, 6, 6, 5, 4, 3, 2, 3, 4, 5, 5, 4, 3, 2, 1, 2, 3, 4, 4, 3, 2, 1, 0, 1, 2, 3],
-  [12, 11, 10, 9, 8, 7, 8, 9, 11, 10, 9, 8, 7, 6, 7, 8, 10, 9, 8, 7, 6, 5, 6, 7, 9, 8, 7, 6, 5, 4, 5, 6, 8, 7, 6, 5, 4, 3, 4, 5, 7, 6, 5, 4, 3, 2, 3, 4, 6, 5, 4, 3, 2, 1, 2, 3, 5, 4, 3, 2, 1, 0, 1, 2],
-  [13, 12, 11, 10, 9, 8, 7, 8, 12, 11, 10, 9, 8, 7, 6, 7, 11, 10, 9, 8, 7, 6, 5, 6, 10, 9, 8, 7, 6, 5, 4, 5, 9, 8, 7, 6, 5, 4, 3, 4, 8, 7, 6, 5, 4, 3, 2, 3, 7, 6, 5, 4, 3, 2, 1, 2, 6, 5, 4, 3, 2, 1, 0, 1],
-  [14, 13, 12, 11, 10, 9, 8, 7, 13, 12, 11, 10, 9, 8, 7, 6, 12, 11, 10, 9, 8, 7, 6, 5, 11, 10, 9, 8, 7, 6, 5, 4, 10, 9, 8, 7, 6, 5, 4, 3, 9, 8, 7, 6, 5, 4, 3, 2, 8, 7, 6, 5, 4, 3, 2, 1, 7, 6, 5, 4, 3, 2, 1, 0]
-];
-
-#[rustfmt::skip]
-const FRONT_SPANS: [[BitBoard; 64]; 2] = [[217020518514230016, 506381209866536704, 1012762419733073408, 2025524839466146816, 4051049678932293632, 8102099357864587264, 16204198715729174528, 13889313184910721024, 217020518514229248, 506381209866534912, 1012762419733069824, 2025524839466139648, 4051049678932279296, 8102099357864558592, 16204198715729117184, 13889313184910671872, 217020518514032640, 506381209866076160, 1012762419732152320, 2025524839464304640, 4051049678928609280, 8102099357857218560, 16204198715714437120, 13889313184898088960, 217020518463700992, 506381209748635648, 1012762419497271296, 2025524838994542592, 4051049677989085184, 8102099355978170368, 16204198711956340736, 13889313181676863488, 217020505578799104, 506381179683864576, 1012762359367729152, 2025524718735458304, 4051049437470916608, 8102098874941833216, 16204197749883666432, 13889312357043142656, 217017207043915776, 506373483102470144, 1012746966204940288, 2025493932409880576, 4050987864819761152, 8101975729639522304, 16203951459279044608, 13889101250810609664, 216172782113783808, 504403158265495552, 1008806316530991104, 2017612633061982208, 4035225266123964416, 8070450532247928832, 16140901064495857664, 13835058055282163712, 0, 0, 0, 0, 0, 0, 0, 0], [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 768, 1792, 3584, 7168, 14336, 28672, 57344, 49152, 197376, 460544, 921088, 1842176, 3684352, 7368704, 14737408, 12632064, 50529024, 117901056, 235802112, 471604224, 943208448, 1886416896, 3772833792, 3233857536, 12935430912, 30182672128, 60365344256, 120730688512, 241461377024, 482922754048, 965845508096, 827867578368, 3311470314240, 7726764066560, 15453528133120, 30907056266240, 61814112532480, 123628225064960, 247256450129920, 211934100111360, 847736400446208, 1978051601041152, 3956103202082304, 7912206404164608, 15824412808329216, 31648825616658432, 63297651233316864, 54255129628557312]];
-#[rustfmt::skip]
-const ADJACENT_FILES: [BitBoard; 8 ] = [144680345676153346, 361700864190383365, 723401728380766730, 1446803456761533460, 2893606913523066920, 5787213827046133840, 11574427654092267680, 4629771061636907072];
-#[rustfmt::skip]
-const FILESETS: [BitBoard; 256] = [0, 72340172838076673, 144680345676153346, 217020518514230019, 289360691352306692, 361700864190383365, 434041037028460038, 506381209866536711, 578721382704613384, 651061555542690057, 723401728380766730, 795741901218843403, 868082074056920076, 940422246894996749, 1012762419733073422, 1085102592571150095, 1157442765409226768, 1229782938247303441, 1302123111085380114, 1374463283923456787, 1446803456761533460, 1519143629599610133, 1591483802437686806, 1663823975275763479, 1736164148113840152, 1808504320951916825, 1880844493789993498, 1953184666628070171, 2025524839466146844, 2097865012304223517, 2170205185142300190, 2242545357980376863, 2314885530818453536, 2387225703656530209, 2459565876494606882, 2531906049332683555, 2604246222170760228, 2676586395008836901, 2748926567846913574, 2821266740684990247, 2893606913523066920, 2965947086361143593, 3038287259199220266, 3110627432037296939, 3182967604875373612, 3255307777713450285, 3327647950551526958, 3399988123389603631, 3472328296227680304, 3544668469065756977, 3617008641903833650, 3689348814741910323, 3761688987579986996, 3834029160418063669, 3906369333256140342, 3978709506094217015, 4051049678932293688, 4123389851770370361, 4195730024608447034, 4268070197446523707, 4340410370284600380, 4412750543122677053, 4485090715960753726, 4557430888798830399, 4629771061636907072, 4702111234474983745, 4774451407313060418, 4846791580151137091, 4919131752989213764, 4991471925827290437, 5063812098665367110, 5136152271503443783, 5208492444341520456, 5280832617179597129, 5353172790017673802, 5425512962855750475, 5497853135693827148, 5570193308531903821, 5642533481369980494, 5714873654208057167, 5787213827046133840, 5859553999884210513, 5931894172722287186, 6004234345560363859, 6076574518398440532, 6148914691236517205, 6221254864074593878, 6293595036912670551, 6365935209750747224, 6438275382588823897, 6510615555426900570, 6582955728264977243, 6655295901103053916, 6727636073941130589, 6799976246779207262, 6872316419617283935, 6944656592455360608, 7016996765293437281, 7089336938131513954, 7161677110969590627, 7234017283807667300, 7306357456645743973, 7378697629483820646, 7451037802321897319, 7523377975159973992, 7595718147998050665, 7668058320836127338, 7740398493674204011, 7812738666512280684, 7885078839350357357, 7957419012188434030, 8029759185026510703, 8102099357864587376, 8174439530702664049, 8246779703540740722, 8319119876378817395, 8391460049216894068, 8463800222054970741, 8536140394893047414, 8608480567731124087, 8680820740569200760, 8753160913407277433, 8825501086245354106, 8897841259083430779, 8970181431921507452, 9042521604759584125, 9114861777597660798, 9187201950435737471, 9259542123273814144, 9331882296111890817, 9404222468949967490, 9476562641788044163, 9548902814626120836, 9621242987464197509, 9693583160302274182, 9765923333140350855, 9838263505978427528, 9910603678816504201, 9982943851654580874, 10055284024492657547, 10127624197330734220, 10199964370168810893, 10272304543006887566, 10344644715844964239, 10416984888683040912, 10489325061521117585, 10561665234359194258, 10634005407197270931, 10706345580035347604, 10778685752873424277, 10851025925711500950, 10923366098549577623, 10995706271387654296, 11068046444225730969, 11140386617063807642, 11212726789901884315, 11285066962739960988, 11357407135578037661, 11429747308416114334, 11502087481254191007, 11574427654092267680, 11646767826930344353, 11719107999768421026, 11791448172606497699, 11863788345444574372, 11936128518282651045, 12008468691120727718, 12080808863958804391, 12153149036796881064, 12225489209634957737, 12297829382473034410, 12370169555311111083, 12442509728149187756, 12514849900987264429, 12587190073825341102, 12659530246663417775, 12731870419501494448, 12804210592339571121, 12876550765177647794, 12948890938015724467, 13021231110853801140, 13093571283691877813, 13165911456529954486, 13238251629368031159, 13310591802206107832, 13382931975044184505, 13455272147882261178, 13527612320720337851, 13599952493558414524, 13672292666396491197, 13744632839234567870, 13816973012072644543, 13889313184910721216, 13961653357748797889, 14033993530586874562, 14106333703424951235, 14178673876263027908, 14251014049101104581, 14323354221939181254, 14395694394777257927, 14468034567615334600, 14540374740453411273, 14612714913291487946, 14685055086129564619, 14757395258967641292, 14829735431805717965, 14902075604643794638, 14974415777481871311, 15046755950319947984, 15119096123158024657, 15191436295996101330, 15263776468834178003, 15336116641672254676, 15408456814510331349, 15480796987348408022, 15553137160186484695, 15625477333024561368, 15697817505862638041, 15770157678700714714, 15842497851538791387, 15914838024376868060, 15987178197214944733, 16059518370053021406, 16131858542891098079, 16204198715729174752, 16276538888567251425, 16348879061405328098, 16421219234243404771, 16493559407081481444, 16565899579919558117, 16638239752757634790, 16710579925595711463, 16782920098433788136, 16855260271271864809, 16927600444109941482, 16999940616948018155, 17072280789786094828, 17144620962624171501, 17216961135462248174, 17289301308300324847, 17361641481138401520, 17433981653976478193, 17506321826814554866, 17578661999652631539, 17651002172490708212, 17723342345328784885, 17795682518166861558, 17868022691004938231, 17940362863843014904, 18012703036681091577, 18085043209519168250, 18157383382357244923, 18229723555195321596, 18302063728033398269, 18374403900871474942, 18446744073709551615];
-const ADJACENT_FILESETS: [u8; 8] = [
-    0b11, 0b111, 0b1110, 0b11100, 0b111000, 0b1110000, 0b11100000, 0b11000000,
-];
+// both colors for the board's incremental pst, black mirrored and negated
+const PIECE_SQUARE_TABLES: [[[PackedScore; 64]; 6]; 2] = DEFAULT_PARAMS.color_pst();
+// manhattan distance to the closest of the four center squares
+const DISTANCE_FROM_CENTER: [u8; 64] = {
+    let mut distance = [0; 64];
+    let mut sq = 0;
+    while sq < 64 {
+        let (file, rank) = (sq % 8, sq / 8);
+        let file_distance = if file < 4 { 3 - file } else { file - 4 };
+        let rank_distance = if rank < 4 { 3 - rank } else { rank - 4 };
+        distance[sq] = (file_distance + rank_distance) as u8;
+        sq += 1;
+    }
+    distance
+};
+const ORTHOGONAL_DISTANCE: [[u8; 64]; 64] = {
+    let mut distance = [[0; 64]; 64];
+    let mut a = 0;
+    while a < 64 {
+        let mut b = 0;
+        while b < 64 {
+            let files = (a % 8) as i32 - (b % 8) as i32;
+            let ranks = (a / 8) as i32 - (b / 8) as i32;
+            distance[a][b] = (files.abs() + ranks.abs()) as u8;
+            b += 1;
+        }
+        a += 1;
+    }
+    distance
+};
+// the number of king moves between two squares
+const DISTANCE: [[u8; 64]; 64] = {
+    let mut distance = [[0; 64]; 64];
+    let mut a = 0;
+    while a < 64 {
+        let mut b = 0;
+        while b < 64 {
+            let files = ((a % 8) as i32 - (b % 8) as i32).abs();
+            let ranks = ((a / 8) as i32 - (b / 8) as i32).abs();
+            distance[a][b] = (if files > ranks { files } else { ranks }) as u8;
+            b += 1;
+        }
+        a += 1;
+    }
+    distance
+};
+// own and adjacent files ahead of a square, black spans stop short of the first rank
+const FRONT_SPANS: [[BitBoard; 64]; 2] = {
+    let mut spans = [[0; 64]; 2];
+    let mut sq = 0;
+    while sq < 64 {
+        let (file, rank) = (sq % 8, sq / 8);
+        let files = FILESETS[ADJACENT_FILESETS[file] as usize];
+        let mut r = 1;
+        while r < 8 {
+            if r > rank {
+                spans[0][sq] |= files & (0xFF << (r * 8));
+            }
+            if r < rank {
+                spans[1][sq] |= files & (0xFF << (r * 8));
+            }
+            r += 1;
+        }
+        sq += 1;
+    }
+    spans
+};
+const ADJACENT_FILES: [BitBoard; 8] = {
+    let mut files = [0; 8];
+    let mut file = 0;
+    while file < 8 {
+        files[file] = FILESETS[ADJACENT_FILESETS[file] as usize] & !FILESETS[1 << file];
+        file += 1;
+    }
+    files
+};
+// every file whose bit is set in the index
+const FILESETS: [BitBoard; 256] = {
+    let mut filesets = [0; 256];
+    let mut set = 0;
+    while set < 256 {
+        let mut file = 0;
+        while file < 8 {
+            if set & (1 << file) != 0 {
+                filesets[set] |= 0x0101010101010101 << file;
+            }
+            file += 1;
+        }
+        set += 1;
+    }
+    filesets
+};
+// a file and its neighbours as a fileset
+const ADJACENT_FILESETS: [u8; 8] = {
+    let mut sets = [0; 8];
+    let mut file = 0;
+    while file < 8 {
+        sets[file] = ((0b111u32 << file) >> 1) as u8;
+        file += 1;
+    }
+    sets
+};
 pub const KING_SIDE_CASTLE_FILESET: u8 = ADJACENT_FILESETS[6];
 pub const QUEEN_SIDE_CASTLE_FILESET: u8 = ADJACENT_FILESETS[2];
-const KING_ATTACKS_BITBOARD: [u64; 64] = [
-    460551,
-    986895,
-    2039583,
-    4079166,
-    8158332,
-    16316664,
-    15790320,
-    14737632,
-    117901063,
-    252645135,
-    522133279,
-    1044266558,
-    2088533116,
-    4177066232,
-    4042322160,
-    3772834016,
-    30182672135,
-    64677154575,
-    133666119455,
-    267332238910,
-    534664477820,
-    1069328955640,
-    1034834473200,
-    965845508320,
-    7726764066560,
-    16557351571200,
-    34218526580480,
-    68437053160960,
-    136874106321920,
-    273748212643840,
-    264917625139200,
-    247256450129920,
-    1978051601039360,
-    4238682002227200,
-    8759942804602880,
-    17519885609205760,
-    35039771218411520,
-    70079542436823040,
-    67818912035635200,
-    63297651233259520,
-    506381209866076160,
-    1085102592570163200,
-    2242545357978337280,
-    4485090715956674560,
-    8970181431913349120,
-    17940362863826698240,
-    17361641481122611200,
-    16204198715714437120,
-    506381209748635648,
-    1085102592318504960,
-    2242545357458243584,
-    4485090714916487168,
-    8970181429832974336,
-    17940362859665948672,
-    17361641477096079360,
-    16204198711956340736,
-    506381179683864576,
-    1085102527893995520,
-    2242545224314257408,
-    4485090448628514816,
-    8970180897257029632,
-    17940361794514059264,
-    17361640446303928320,
-    16204197749883666432,
-];
-pub fn get_pst_value(color: Color, piece: Piece, square: Square) -> i16 {
+// the five by five box around the king
+const KING_ATTACKS_BITBOARD: [u64; 64] = {
+    let mut zones = [0; 64];
+    let mut sq = 0;
+    while sq < 64 {
+        let (file, rank) = ((sq % 8) as i32, (sq / 8) as i32);
+        let mut r = rank - 2;
+        while r <= rank + 2 {
+            let mut f = file - 2;
+            while f <= file + 2 {
+                if f >= 0 && f < 8 && r >= 0 && r < 8 {
+                    zones[sq] |= 1 << (r * 8 + f);
+                }
+                f += 1;
+            }
+            r += 1;
+        }
+        sq += 1;
+    }
+    zones
+};
+pub fn get_pst_value(color: Color, piece: Piece, square: Square) -> PackedScore {
     return PIECE_SQUARE_TABLES[color as usize][piece as usize][square as usize];
 }
-pub fn get_pawn_endgame_value(color: Color, square: Square) -> i16 {
-    return PAWN_SQUARE_TABLES[color as usize][square as usize];
-}
-pub fn calc_king_pst(color: usize, square: Square, endgame: f32, middle_game: f32) -> i16 {
-    return (PIECE_SQUARE_TABLES[color][5][square as usize] as f32 * middle_game
-        + KING_SQUARE_TABLES[color][square as usize] as f32 * endgame) as i16;
-}
 pub fn get_distance_from_center(square: Square) -> u8 {
     return DISTANCE_FROM_CENTER[square as usize];
 }
 pub fn get_orthogonal_distance(sq1: Square, sq2: Square) -> u8 {
     return ORTHOGONAL_DISTANCE[sq1 as usize][sq2 as usize];
 }
+pub fn get_distance(sq1: Square, sq2: Square) -> u8 {
+    return DISTANCE[sq1 as usize][sq2 as usize];
+}
 pub fn get_front_span(color: Color, square: Square) -> BitBoard {
     return FRONT_SPANS[color as usize][square as usize];
 }
@@ -0,0 +1,126 @@
+use crate::{
+    core::{
+        Board, Color, Piece,
+        bitboard::{BitBoard, BitBoardUtil},
+        tables::magics::{get_bishop_moves, get_king_moves, get_knight_moves, get_pawn_controlled_bb, get_rook_moves},
+    },
+    evaluation::{
+        params::EvalParams,
+        score::{PackedScore, packed},
+        tables::get_king_attacks,
+    },
+};
+
+// what every piece of both colors attacks, built once per eval and shared by mobility, king
+// safety and threats
+pub struct AttackMaps {
+    // [color][piece]
+    pub by_piece: [[BitBoard; 6]; 2],
+    // the board keeps the union of each color's attacks up to date on every move, so it is
+    // taken from there and only the per piece maps are built here
+    pub all: [BitBoard; 2],
+    // squares attacked by at least two pieces
+    pub double: [BitBoard; 2],
+    // squares a slider attacks through one enemy piece, [color][piece]
+    pub xray: [[BitBoard; 6]; 2],
+    // moves to empty squares of the knights, bishops and rooks
+    pub moves: [[PackedScore; 3]; 2],
+    // knights, bishops, rooks and queens attacking the squares around the enemy king
+    pub king_attackers: [u32; 2],
+    pub king_attack_values: [f32; 2],
+}
+
+impl AttackMaps {
+    pub fn new(board: &Board, params: &EvalParams) -> AttackMaps {
+        let mut maps = AttackMaps {
+            by_piece: [[0; 6]; 2],
+            all: board.attacks,
+            double: [0; 2],
+            xray: [[0; 6]; 2],
+            moves: [[0; 3]; 2],
+            king_attackers: [0; 2],
+            king_attack_values: [0.0; 2],
+        };
+        let blockers = board.combined;
+        for color in [Color::White, Color::Black] {
+            let c = color as usize;
+            // the attacks of the pieces added so far
+            let mut seen = 0;
+            let enemies = board.color_combined[!color as usize];
+            let king_zone = get_king_attacks(board.king_square(!color));
+            let pawn_attacks = get_pawn_controlled_bb(board.get_piece_bitboard(Piece::Pawn, color), color);
+            maps.add(&mut seen, c, Piece::Pawn, pawn_attacks);
+            maps.add(&mut seen, c, Piece::King, get_king_moves(board.king_square(color)));
+            for piece in [Piece::Knight, Piece::Bishop, Piece::Rook, Piece::Queen] {
+                let mut pieces = board.get_piece_bitboard(piece, color);
+                while pieces != 0 {
+                    let sq = pieces.pop_lsb();
+                    let slider_moves = |blockers: BitBoard| -> BitBoard {
+                        return match piece {
+                            Piece::Bishop => get_bishop_moves(sq, blockers),
+                            Piece::Rook => get_rook_moves(sq, blockers),
+                            _ => get_bishop_moves(sq, blockers) | get_rook_moves(sq, blockers),
+                        };
+                    };
+                    let attacks = if piece == Piece::Knight { get_knight_moves(sq) } else { slider_moves(blockers) };
+                    maps.add(&mut seen, c, piece, attacks);
+                    if piece != Piece::Knight {
+                        maps.xray[c][piece as usize] |= slider_moves(blockers ^ (attacks & enemies)) & !attacks;
+                    }
+                    if piece != Piece::Queen {
+                        maps.moves[c][piece as usize - 1] += (attacks & !blockers).count_ones() as PackedScore;
+                    }
+                    if attacks & king_zone != 0 {
+                        maps.king_attackers[c] += 1;
+                        maps.king_attack_values[c] += params.king_attacker_values[piece as usize - 1] as f32;
+                    }
+                }
+            }
+        }
+        return maps;
+    }
+    fn add(&mut self, seen: &mut BitBoard, color: usize, piece: Piece, attacks: BitBoard) {
+        self.by_piece[color][piece as usize] |= attacks;
+        self.double[color] |= *seen & attacks;
+        *seen |= attacks;
+    }
+}
+
+// pieces that can be taken for free or by something cheaper, safe checks and pins and skewers
+// onto the queen or the king
+pub fn evaluate_threats(board: &Board, maps: &AttackMaps, color: Color, params: &EvalParams) -> PackedScore {
+    let (us, them) = (color as usize, !color as usize);
+    let mut score = 0;
+    let knights = board.get_piece_bitboard(Piece::Knight, !color);
+    let bishops = board.get_piece_bitboard(Piece::Bishop, !color);
+    let rooks = board.get_piece_bitboard(Piece::Rook, !color);
+    let queens = board.get_piece_bitboard(Piece::Queen, !color);
+    let king = board.king_square(!color);
+    let pieces = knights | bishops | rooks | queens;
+    let attacks = &maps.by_piece[us];
+
+    let hanging = pieces & maps.all[us] & !maps.all[them];
+    score += hanging.count_ones() as PackedScore * packed(params.hanging);
+    score += (pieces & attacks[Piece::Pawn as usize]).count_ones() as PackedScore * packed(params.threat_by_pawn);
+    let minor_attacks = attacks[Piece::Knight as usize] | attacks[Piece::Bishop as usize];
+    score += ((rooks | queens) & minor_attacks).count_ones() as PackedScore * packed(params.threat_by_minor);
+    score += (queens & attacks[Piece::Rook as usize]).count_ones() as PackedScore * packed(params.threat_by_rook);
+
+    // squares to check from that no enemy piece or pawn defends
+    let safe = !maps.all[them] & !board.color_combined[us];
+    let bishop_checks = get_bishop_moves(king, board.combined);
+    let rook_checks = get_rook_moves(king, board.combined);
+    let checks = [get_knight_moves(king), bishop_checks, rook_checks, bishop_checks | rook_checks];
+    for (i, check_squares) in checks.into_iter().enumerate() {
+        if check_squares & attacks[i + 1] & safe != 0 {
+            score += packed(params.safe_checks[i]);
+        }
+    }
+
+    let king_bb = 1u64 << king;
+    let xray = &maps.xray[us];
+    let xray_targets = ((xray[Piece::Bishop as usize] | xray[Piece::Rook as usize]) & (queens | king_bb))
+        | (xray[Piece::Queen as usize] & king_bb);
+    score += xray_targets.count_ones() as PackedScore * packed(params.xray_threat);
+    return score;
+}
@@ -0,0 +1,97 @@
+use crate::{
+    core::{Board, Color},
+    evaluation::{
+        defs::DEFAULT_PARAMS,
+        evaluate::{TERM_COUNT, TERM_NAMES, classical_eval, evaluate, evaluate_terms},
+        score::{MAX_PHASE, PackedScoreUtil},
+        nnue,
+    },
+    transposition_table::TranspositionTable,
+};
+
+// one term for white and black from their own point of view, value is the middlegame and
+// endgame values blended by the phase of the position
+#[derive(Clone, Copy, Default)]
+pub struct TermTrace {
+    pub mg: [i16; 2],
+    pub eg: [i16; 2],
+    pub value: [i16; 2],
+}
+pub struct EvalTrace {
+    pub terms: [TermTrace; TERM_COUNT],
+    // MAX_PHASE is a full middlegame, 0 a pawn endgame
+    pub phase: u8,
+    // classical eval from white's point of view
+    pub eval: i16,
+    // network eval from white's point of view when one is active
+    pub nnue: Option<i16>,
+    // what the search uses from white's point of view, known endgames and scale factors applied
+    pub final_eval: i16,
+}
+
+impl TermTrace {
+    pub fn total(&self) -> i16 {
+        return self.value[0] - self.value[1];
+    }
+}
+impl EvalTrace {
+    pub fn new(board: &Board, tt: &TranspositionTable) -> EvalTrace {
+        let params = &DEFAULT_PARAMS;
+        let packed = evaluate_terms(board, params, Some(tt));
+        let mut terms = [TermTrace::default(); TERM_COUNT];
+        for (term, score) in terms.iter_mut().zip(packed) {
+            *term = TermTrace {
+                mg: score.map(|s| s.mg()),
+                eg: score.map(|s| s.eg()),
+                value: score.map(|s| s.interpolate(board.phase)),
+            };
+        }
+        let nnue = nnue::active().map(|network| {
+            let eval = network.evaluate(&board.accumulator, board.turn);
+            if board.turn == Color::White { eval } else { -eval }
+        });
+        let final_eval = evaluate(board, tt);
+        return EvalTrace {
+            terms,
+            phase: board.phase,
+            // blended once, the sum of the rounded terms can be off by a few centipawns
+            eval: classical_eval(board, params, Some(tt)),
+            nnue,
+            final_eval: if board.turn == Color::White { final_eval } else { -final_eval },
+        };
+    }
+    pub fn print(&self) {
+        println!("      Term    |    White    |    Black    |    Total    |     Final");
+        println!("              |   MG    EG  |   MG    EG  |   MG    EG  |");
+        println!(" -------------+-------------+-------------+-------------+----------");
+        for (name, term) in TERM_NAMES.iter().zip(self.terms.iter()) {
+            println!(
+                " {:>12} | {:>5} {:>5} | {:>5} {:>5} | {:>5} {:>5} | {:>9}",
+                name,
+                term.mg[0],
+                term.eg[0],
+                term.mg[1],
+                term.eg[1],
+                term.mg[0] - term.mg[1],
+                term.eg[0] - term.eg[1],
+                term.total()
+            );
+        }
+        println!(" -------------+-------------+-------------+-------------+----------");
+        println!(
+            " {:>12} | {:>11} | {:>11} | {:>11} | {:>9}",
+            "Total",
+            "",
+            "",
+            "",
+            self.eval
+        );
+        println!();
+        println!("Phase: {} of {}", self.phase, MAX_PHASE);
+        println!("Classical evaluation: {} (white side)", self.eval);
+        if let Some(eval) = self.nnue {
+            println!("NNUE evaluation: {} (white side)", eval);
+        }
+        println!("Final evaluation: {} (white side)", self.final_eval);
+    }
+}
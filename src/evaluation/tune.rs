@@ -0,0 +1,270 @@
+use std::{fs, thread, time::Instant};
+
+use crate::{
+    core::{Board, Color, bitboard::BitBoardUtil, fen::fen_fields},
+    evaluation::{
+        defs::DEFAULT_PARAMS,
+        endgame::evaluate_endgame,
+        evaluate::classical_eval,
+        params::{EvalParams, PARAM_COUNT, PST_OFFSET, TABLES_END},
+    },
+    search::{defs::ThreadData, quiescence::quiescence},
+    transposition_table::TranspositionTable,
+};
+
+// the eval is linearised around the current params every this many epochs
+const RELINEARIZE_EPOCHS: usize = 100;
+// central differences of this size, the eval truncates to whole centipawns
+const STEP: i16 = 8;
+const LEARNING_RATE: f64 = 1.0;
+
+pub struct TuneEntry {
+    pub board: Board,
+    // 1 for a white win, 0.5 for a draw
+    pub result: f64,
+}
+// the eval of one position around the params it was linearised at
+struct Linear {
+    eval: f64,
+    coefficients: Vec<(u16, f32)>,
+}
+
+fn parse_result(text: &str) -> Option<f64> {
+    for (token, result) in [
+        ("1/2-1/2", 0.5),
+        ("1-0", 1.0),
+        ("0-1", 0.0),
+        ("[0.5]", 0.5),
+        ("[1.0]", 1.0),
+        ("[0.0]", 0.0),
+        ("[1]", 1.0),
+        ("[0]", 0.0),
+    ] {
+        if text.contains(token) {
+            return Some(result);
+        }
+    }
+    return None;
+}
+// "<fen> [1.0]", "<fen> c9 \"1-0\";" and the like
+pub fn parse_tune_line(line: &str) -> Result<TuneEntry, String> {
+    let (fen, rest) = fen_fields(line);
+    let board = Board::from_fen(&fen).map_err(|e| format!("invalid fen {}: {}", fen, e))?;
+    let Some(result) = parse_result(&rest) else {
+        return Err(format!("no game result after {}", fen));
+    };
+    return Ok(TuneEntry { board, result });
+}
+pub fn load_dataset(path: &str) -> Result<Vec<TuneEntry>, String> {
+    let text = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let mut entries = vec![];
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        entries.push(parse_tune_line(line).map_err(|e| format!("line {}: {}", i + 1, e))?);
+    }
+    return Ok(entries);
+}
+// keeps the positions where quiescence agrees with the classical eval the params are tuned
+// for, so nothing is hanging, the side to move is not in check and the position is not one of
+// the known endgames that never reach that eval
+pub fn quiet_positions(entries: Vec<TuneEntry>) -> Vec<TuneEntry> {
+    let tt = TranspositionTable::new(16);
+    let mut thread_data = ThreadData::new();
+    return entries
+        .into_iter()
+        .filter(|entry| {
+            let mut board = entry.board;
+            if board.checkers != 0 || evaluate_endgame(&board).is_some() {
+                return false;
+            }
+            let q = quiescence(&mut board, -i16::MAX, i16::MAX, &tt, &mut thread_data);
+            let eval = classical_eval(&board, &DEFAULT_PARAMS, Some(&tt));
+            return q == if board.turn == Color::White { eval } else { -eval };
+        })
+        .collect();
+}
+
+fn eval_with(board: &mut Board, params: &EvalParams) -> f64 {
+    board.pst_value = params.board_pst(board);
+    return classical_eval(board, params, None) as f64;
+}
+// indices of the params the eval of this board can depend on, table entries of empty squares
+// are left out
+fn relevant_params(board: &Board) -> Vec<usize> {
+    let mut indices: Vec<usize> = (0..PST_OFFSET).chain(TABLES_END..PARAM_COUNT).collect();
+    let mut occupied = board.combined;
+    while occupied != 0 {
+        let sq = occupied.pop_lsb();
+        let piece = board.piece_on(sq).unwrap() as usize;
+        let white_sq = if board.color_combined[Color::White as usize].has_sq(sq) {
+            sq as usize
+        } else {
+            (sq ^ 56) as usize
+        };
+        let index = PST_OFFSET + (piece * 64 + white_sq) * 2;
+        indices.extend([index, index + 1]);
+    }
+    indices.sort_unstable();
+    indices.dedup();
+    return indices;
+}
+fn linearize(entries: &[TuneEntry], params: &EvalParams, threads: usize) -> Vec<Linear> {
+    let chunk = entries.len().div_ceil(threads.max(1)).max(1);
+    return thread::scope(|scope| {
+        let handles: Vec<_> = entries
+            .chunks(chunk)
+            .map(|entries| {
+                scope.spawn(move || {
+                    let mut params = *params;
+                    let mut linear = Vec::with_capacity(entries.len());
+                    for entry in entries {
+                        let mut board = entry.board;
+                        let eval = eval_with(&mut board, &params);
+                        let mut coefficients = vec![];
+                        for i in relevant_params(&board) {
+                            let value = params.values()[i];
+                            params.values_mut()[i] = value + STEP;
+                            let up = eval_with(&mut board, &params);
+                            params.values_mut()[i] = value - STEP;
+                            let down = eval_with(&mut board, &params);
+                            params.values_mut()[i] = value;
+                            if up != down {
+                                coefficients.push((i as u16, ((up - down) / (2 * STEP) as f64) as f32));
+                            }
+                        }
+                        linear.push(Linear { eval, coefficients });
+                    }
+                    return linear;
+                })
+            })
+            .collect();
+        return handles.into_iter().flat_map(|h| h.join().unwrap()).collect();
+    });
+}
+
+fn sigmoid(eval: f64, k: f64) -> f64 {
+    return 1.0 / (1.0 + 10f64.powf(-k * eval / 400.0));
+}
+fn linear_eval(linear: &Linear, shift: &[f64]) -> f64 {
+    let mut eval = linear.eval;
+    for &(i, c) in &linear.coefficients {
+        eval += c as f64 * shift[i as usize];
+    }
+    return eval;
+}
+fn error(entries: &[TuneEntry], linear: &[Linear], shift: &[f64], k: f64) -> f64 {
+    let mut sum = 0.0;
+    for (entry, linear) in entries.iter().zip(linear) {
+        let e = entry.result - sigmoid(linear_eval(linear, shift), k);
+        sum += e * e;
+    }
+    return sum / entries.len() as f64;
+}
+// the scaling constant that fits the starting params best, a golden section search
+fn fit_k(entries: &[TuneEntry], linear: &[Linear]) -> f64 {
+    let shift = vec![0.0; PARAM_COUNT];
+    let (mut lo, mut hi) = (0.1, 4.0);
+    let ratio = (5f64.sqrt() - 1.0) / 2.0;
+    for _ in 0..40 {
+        let a = hi - ratio * (hi - lo);
+        let b = lo + ratio * (hi - lo);
+        if error(entries, linear, &shift, a) < error(entries, linear, &shift, b) {
+            hi = b;
+        } else {
+            lo = a;
+        }
+    }
+    return (lo + hi) / 2.0;
+}
+fn gradient(entries: &[TuneEntry], linear: &[Linear], shift: &[f64], k: f64) -> Vec<f64> {
+    let mut gradient = vec![0.0; PARAM_COUNT];
+    let scale = k * 10f64.ln() / 400.0;
+    for (entry, linear) in entries.iter().zip(linear) {
+        let s = sigmoid(linear_eval(linear, shift), k);
+        let d = 2.0 * (s - entry.result) * s * (1.0 - s) * scale;
+        for &(i, c) in &linear.coefficients {
+            gradient[i as usize] += d * c as f64;
+        }
+    }
+    for g in gradient.iter_mut() {
+        *g /= entries.len() as f64;
+    }
+    return gradient;
+}
+
+// adam on the mean squared error between the results and the sigmoid of the eval, the eval
+// is linearised by finite differences and refreshed every RELINEARIZE_EPOCHS epochs
+pub fn tune(entries: &[TuneEntry], start: &EvalParams, epochs: usize, threads: usize) -> EvalParams {
+    let mut params = *start;
+    let mut weights: Vec<f64> = params.values().iter().map(|&v| v as f64).collect();
+    let (mut m, mut v) = (vec![0.0; PARAM_COUNT], vec![0.0; PARAM_COUNT]);
+    let (beta1, beta2) = (0.9, 0.999);
+    let mut linear = linearize(entries, &params, threads);
+    let k = fit_k(entries, &linear);
+    let mut base = weights.clone();
+    println!(
+        "info string {} positions, k {:.3}, error {:.6}",
+        entries.len(),
+        k,
+        error(entries, &linear, &vec![0.0; PARAM_COUNT], k)
+    );
+    for epoch in 1..=epochs {
+        let shift: Vec<f64> = weights.iter().zip(&base).map(|(w, b)| w - b).collect();
+        let g = gradient(entries, &linear, &shift, k);
+        for i in 0..PARAM_COUNT {
+            m[i] = beta1 * m[i] + (1.0 - beta1) * g[i];
+            v[i] = beta2 * v[i] + (1.0 - beta2) * g[i] * g[i];
+            let m_hat = m[i] / (1.0 - beta1.powi(epoch as i32));
+            let v_hat = v[i] / (1.0 - beta2.powi(epoch as i32));
+            weights[i] -= LEARNING_RATE * m_hat / (v_hat.sqrt() + 1e-8);
+        }
+        if epoch % RELINEARIZE_EPOCHS == 0 || epoch == epochs {
+            for (value, w) in params.values_mut().iter_mut().zip(&weights) {
+                *value = w.round().clamp(i16::MIN as f64, i16::MAX as f64) as i16;
+            }
+            linear = linearize(entries, &params, threads);
+            base = params.values().iter().map(|&v| v as f64).collect();
+            println!(
+                "info string epoch {} error {:.6}",
+                epoch,
+                error(entries, &linear, &vec![0.0; PARAM_COUNT], k)
+            );
+        }
+    }
+    return params;
+}
+pub fn start_tune(path: &str, epochs: usize, threads: usize, output: &str) -> bool {
+    let start = Instant::now();
+    let entries = match load_dataset(path) {
+        Ok(entries) => entries,
+        Err(e) => {
+            println!("info string could not read tuning data {}: {}", path, e);
+            return false;
+        }
+    };
+    let total = entries.len();
+    let entries = quiet_positions(entries);
+    println!("info string {} of {} positions are quiet", entries.len(), total);
+    if entries.is_empty() {
+        return false;
+    }
+    let params = tune(&entries, &DEFAULT_PARAMS, epochs, threads);
+    let text = if output.ends_with(".json") { params.json() } else { params.rust_source() };
+    if let Err(e) = fs::write(output, text) {
+        println!("info string could not write {}: {}", output, e);
+        return false;
+    }
+    let changed = (0..PARAM_COUNT)
+        .filter(|&i| params.values()[i] != DEFAULT_PARAMS.values()[i])
+        .count();
+    println!(
+        "info string wrote {} with {} changed parameters in {:?}",
+        output,
+        changed,
+        start.elapsed()
+    );
+    return true;
+}
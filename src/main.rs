@@ -1,13 +1,19 @@
 #![allow(static_mut_refs)]
+// explicit returns are the style of the whole code base
+#![allow(clippy::needless_return)]
 
+mod book;
 mod core;
 mod uci;
 mod transposition_table;
 mod search;
+mod syzygy;
 mod evaluation;
 mod uci_options;
 
 fn main() {
+    #[cfg(all(feature = "pext", target_arch = "x86_64"))]
+    core::tables::magics::pext::init();
     let mut uci_class = uci::Uci::new();
     uci_class.connect_to_terminal();
 }
@@ -1,12 +1,18 @@
-use crate::{core::{Board, r#move::MoveUtil, movegen::generate_all_moves}, search::{defs::{MATE_SCORE, Score, ThreadData}, moves::{MoveType, sort_all_moves}, quiescence::quiescence}, transposition_table::{Flag, TTEntry, TranspositionTable}};
+use crate::{core::{Board, r#move::MoveUtil, move_list::{MoveList, MAX_MOVES}, movegen::generate_all_moves}, evaluation::endgame::kpk_score, search::{defs::{MATE_SCORE, Score, TB_WIN_SCORE, ThreadData}, moves::{MoveType, sort_all_moves}, quiescence::quiescence}, syzygy::probe as syzygy, transposition_table::{Flag, TTEntry, TranspositionTable}};
 
 pub fn alpha_beta(
-    board: &Board,
+    board: &mut Board,
     mut alpha: Score,
     beta: Score,
     tt: &TranspositionTable,
     thread_data: &mut ThreadData
 ) -> Score {
+    if board.is_draw(&thread_data.states) {
+        return 0;
+    }
+    if let Some(score) = kpk_score(board) {
+        return score;
+    }
     if thread_data.depth <= 0 {
         return quiescence(board, alpha, beta, tt, thread_data);
     }
@@ -26,10 +32,54 @@ pub fn alpha_beta(
                 _ => {}
             }
         }
-        tt_move = e.best_move;
+        if board.is_pseudo_legal(e.best_move) && board.is_legal(e.best_move) {
+            tt_move = e.best_move;
+        }
+    }
+
+    let alpha_orig = alpha;
+    let mut best = i16::MIN + 10;
+    // what the tables say when they do not settle the node, the search stays inside it
+    let mut max_value = Score::MAX;
+
+    // tablebases after a capture or a pawn move, the only time the counter is known to be zero. the
+    // largest tables only from the probe depth on
+    let pieces = board.combined.count_ones() as usize;
+    if let Some(tablebases) = syzygy::active()
+        && board.halfmove_clock == 0
+        && board.castle_rights == 0
+        && (pieces < tablebases.max_pieces || (pieces == tablebases.max_pieces && thread_data.depth >= syzygy::probe_depth()))
+        && let Some(wdl) = tablebases.probe_wdl(board)
+    {
+        thread_data.tb_hits += 1;
+        // without the 50 move rule cursed wins and blessed losses are plain ones
+        let draw_score = syzygy::use_rule_50() as i32;
+        let (value, flag) = match wdl as i32 {
+            w if w < -draw_score => (-TB_WIN_SCORE + thread_data.ply as Score, Flag::UPPER),
+            w if w > draw_score => (TB_WIN_SCORE - thread_data.ply as Score, Flag::LOWER),
+            w => ((2 * w * draw_score) as Score, Flag::EXACT),
+        };
+        if flag == Flag::EXACT || (flag == Flag::LOWER && value >= beta) || (flag == Flag::UPPER && value <= alpha) {
+            let entry = TTEntry {
+                key: (key >> 48) as u16,
+                depth: thread_data.depth,
+                flag,
+                eval: value,
+                best_move: 0,
+                age: 0,
+            };
+            tt.store_position(key, entry);
+            return value;
+        }
+        if flag == Flag::LOWER {
+            best = value;
+            alpha = alpha.max(value);
+        } else {
+            max_value = value;
+        }
     }
 
-    let mut movelist = Vec::with_capacity(100);
+    let mut movelist = MoveList::new();
 
     generate_all_moves(board, &mut movelist);
     
@@ -41,12 +91,10 @@ pub fn alpha_beta(
         }
     }
 
-    let mut move_types = vec![MoveType::BadCapture; movelist.len()];
+    let mut move_types = [MoveType::BadCapture; MAX_MOVES];
 
     sort_all_moves(board, thread_data, tt_move, thread_data.ply, &mut movelist, &mut move_types);
 
-    let alpha_orig = alpha;
-    let mut best = i16::MIN + 10;
     let mut best_move = 0;
 
     thread_data.ply += 1;
@@ -55,9 +103,13 @@ pub fn alpha_beta(
         let mv = movelist[i];
         let move_type = move_types[i];
 
-        let new_board = board.make_move_new(mv);
+        let state = board.make_move(mv);
+        thread_data.states.push(state);
+
+        let score = -alpha_beta(board, -beta, -alpha, tt, thread_data);
 
-        let score = -alpha_beta(&new_board, -beta, -alpha, tt, thread_data);
+        thread_data.states.pop();
+        board.unmake_move(mv, &state);
 
         if score > best {
             best = score;
@@ -78,6 +130,7 @@ pub fn alpha_beta(
     }
     thread_data.depth += 1;
     thread_data.ply -= 1;
+    best = best.min(max_value);
 
     let flag = if best <= alpha_orig {
         Flag::UPPER
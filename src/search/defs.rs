@@ -1,10 +1,12 @@
 use std::time::{Duration, Instant};
 
-use crate::{core::{Color, Piece, r#move::{Move, MoveUtil}}, search::moves::MoveType};
+use crate::{core::{Color, Piece, board::StateInfo, r#move::{Move, MoveUtil}}, search::moves::MoveType};
 
 
 pub const MATE_SCORE: i16 = -30000;
 const MATE_THRESHOLD: i16 = MATE_SCORE + 100;
+// tablebase wins score below any mate, less for every ply it takes to reach the table
+pub const TB_WIN_SCORE: i16 = 25000;
 
 pub type Depth = i8;
 pub type Score = i16;
@@ -19,6 +21,7 @@ pub struct SearchResult {
     pub q_nodes: u64,   // quiescence nodes searched
     pub beta_cuttoffs: u64,
     pub tt_hits: u64,
+    pub tb_hits: u64,   // tablebase probes that gave a result
     pub pv: Vec<Move>, // Principal Variation
     pub start_time: Instant,
 }
@@ -34,6 +37,7 @@ impl SearchResult {
             pv: vec![],
             beta_cuttoffs: 0,
             tt_hits: 0,
+            tb_hits: 0,
             q_nodes: 0,
             start_time: Instant::now(),
         };
@@ -49,6 +53,7 @@ impl SearchResult {
             pv: vec![],
             beta_cuttoffs: 0,
             tt_hits: 0,
+            tb_hits: 0,
             q_nodes: 0,
             start_time: Instant::now(),
         };
@@ -64,6 +69,7 @@ impl SearchResult {
             pv: vec![],
             beta_cuttoffs: 0,
             tt_hits: 0,
+            tb_hits: 0,
             q_nodes: 0,
             start_time: Instant::now(),
         };
@@ -73,6 +79,7 @@ impl SearchResult {
         self.nodes += thread_data.nodes;
         self.q_nodes += thread_data.q_nodes;
         self.tt_hits += thread_data.tt_hits;
+        self.tb_hits += thread_data.tb_hits;
         self.depth = thread_data.depth;
     }
     pub fn timer_elapsed(&self) -> u128{
@@ -104,6 +111,7 @@ impl SearchResult {
 #[derive(PartialEq, Copy, Clone)]
 pub enum SearchMode {
     MoveTime, // Run until 'time per move' is used up.
+    #[allow(unused)]
     GameTime, // Search determines when to quit, depending on available time.
     Infinite, // Run forever, until the 'stop' command is received.
     Nothing,  // No search mode has been defined.
@@ -151,6 +159,7 @@ pub struct SearchInfo {
     pub quiet: bool,           // No intermediate search stats updates
 }
 
+#[allow(unused)]
 impl SearchInfo {
     pub fn default() -> Self {
         return SearchInfo {
@@ -204,11 +213,13 @@ pub struct ThreadData {
     //current search data
     pub ply: i8,                       // Number of plys from the root
     pub depth: i8,                     // Depth currently being searched
+    pub states: Vec<StateInfo>,        // Undo stack of the game and the current line
 
     //search summary info
     pub nodes: u64,                    // Nodes searched
     pub q_nodes: u64,                  // Number of quescence nodes
     pub tt_hits: u64,                  // Number of transposition table hits
+    pub tb_hits: u64,                  // Number of tablebase hits
     pub beta_cutoffs: u64,             // Number of beta cutoffs
 }
 
@@ -222,9 +233,11 @@ impl ThreadData{
             move_types: [MoveType::BadCapture; 65536],
             ply: 0, 
             depth: 0,
+            states: Vec::with_capacity(256),
             nodes: 0, 
             q_nodes: 0,
             tt_hits: 0,
+            tb_hits: 0,
             beta_cutoffs: 0,
         };
     }
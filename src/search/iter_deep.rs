@@ -1,9 +1,10 @@
 use std::sync::Arc;
 
-use crate::{core::{Board, r#move::Move}, search::{defs::SearchInfo, search::search_root_parallel}, transposition_table::TranspositionTable, uci::Uci};
+use crate::{core::{Board, board::StateInfo, r#move::Move}, search::{defs::SearchInfo, search::search_root_parallel}, transposition_table::TranspositionTable, uci::Uci};
 
 pub fn start_iterative_deepening_search(
     board: &Board,
+    history: &[StateInfo],
     tt: Arc<TranspositionTable>,
     search_info: &mut SearchInfo,
     threads: usize,
@@ -14,6 +15,7 @@ pub fn start_iterative_deepening_search(
     for depth in 1..=search_info.max_depth {
         let mut result = search_root_parallel(
             board,
+            history,
             depth,
             Arc::clone(&tt),
             threads,
@@ -21,8 +23,8 @@ pub fn start_iterative_deepening_search(
 
         best_move = Some(result.best_move);
 
-        tt.calculate_pv(board.clone(), &mut result.pv);
-        Uci::send_info(&result);
+        tt.calculate_pv(*board, &mut result.pv);
+        Uci::send_info(&result, board.chess960);
     }
 
     best_move.unwrap()
@@ -1,5 +1,6 @@
 pub mod defs;
 pub mod alpha_beta;
+#[allow(clippy::module_inception)]
 pub mod search;
 pub mod iter_deep;
 pub mod quiescence;
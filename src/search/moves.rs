@@ -1,8 +1,6 @@
-use std::i32;
-
 use crate::{
     core::{
-        Board, Color, Piece, bitboard::BitBoardUtil, r#move::{Move, MoveUtil}
+        Board, Color, Piece, bitboard::BitBoardUtil, move_list::MoveList, r#move::{Move, MoveUtil}
     },
     search::{defs::{Depth, ThreadData}, tables::get_sort_tabel_value},
 };
@@ -52,8 +50,8 @@ fn move_type(
     if is_promo {
         return MoveType::Promotion;
     }
-    if piece_at_end.is_some() {
-        if is_controled && piece_value(piece_at_start) > piece_value(piece_at_end.unwrap()) {
+    if let Some(piece_at_end) = piece_at_end {
+        if is_controled && piece_value(piece_at_start) > piece_value(piece_at_end) {
             return MoveType::BadCapture;
         }
         return MoveType::GoodCapture;
@@ -63,6 +61,7 @@ fn move_type(
     }
     return MoveType::QuietMove;
 }
+#[allow(clippy::too_many_arguments)]
 fn set_move_value(
     m: Move,
     piece_at_start: Piece,
@@ -83,9 +82,9 @@ fn set_move_value(
         m.is_promotion(),
     );
     let mut value = 0;
-    if piece_at_end.is_some() {
+    if let Some(piece_at_end) = piece_at_end {
         //captures sorted with MVV_LVA
-        value += MVV_LVA[piece_at_end.unwrap() as usize][piece_at_start as usize];
+        value += MVV_LVA[piece_at_end as usize][piece_at_start as usize];
     } else {
         //quiets sorter with history heuristic
         if is_controled && piece_at_start != Piece::Pawn {
@@ -109,8 +108,8 @@ pub fn sort_all_moves(
     thread_data: &mut ThreadData,
     tt_move: Move,
     ply: Depth,
-    moves: &mut Vec<Move>,
-    move_types: &mut Vec<MoveType>,
+    moves: &mut MoveList,
+    move_types: &mut [MoveType],
 ) {
     let killers = thread_data.get_killers(ply);
     let pawns = board.get_piece_bitboard(Piece::Pawn, !board.turn);
@@ -119,13 +118,12 @@ pub fn sort_all_moves(
     } else {
         (pawns << 7 & NOT_FILE_H_BB) | (pawns << 9 & NOT_FILE_A_BB)
     };
-    for i in 0..moves.len() {
-        let mv = moves[i];
+    for &mv in moves.iter() {
         let p = board.piece_on(mv.get_from()).unwrap();
         set_move_value(
             mv,
             p,
-            board.piece_on(mv.get_to()),
+            board.captured_piece(mv),
             controled.has_sq(mv.get_to()),
             board.turn,
             mv == tt_move,
@@ -145,17 +143,16 @@ pub fn sort_all_moves(
         move_types[i] = thread_data.move_types[moves[i] as usize];
     }
 }
-fn set_capture_value(mv: Move, piece: Piece, captured: Piece, is_controled: bool, thread_data: &mut ThreadData) {
+fn capture_value(mv: Move, piece: Piece, captured: Piece, is_controled: bool) -> Score {
     let mut value = MVV_LVA[captured as usize][piece as usize];
     if is_controled && piece != Piece::Pawn && piece_value(piece) > piece_value(captured) {
         value -= 50;
     } else if mv.is_promotion() {
         value += PROMOTION_VALUES[mv.get_sp() as usize];
     }
-    thread_data.move_values[mv as usize] = value;
-    
+    return value;
 }
-pub fn sort_captures(board: &Board, moves: &mut Vec<Move>,thread_data: &mut ThreadData) {
+pub fn sort_captures(board: &Board, moves: &mut MoveList) {
     let pawns = board.get_piece_bitboard(Piece::Pawn, !board.turn);
     let controled = if board.turn == Color::White {
         (pawns >> 9 & NOT_FILE_H_BB) | (pawns >> 7 & NOT_FILE_A_BB)
@@ -164,16 +161,17 @@ pub fn sort_captures(board: &Board, moves: &mut Vec<Move>,thread_data: &mut Thre
     };
     for i in 0..moves.len() {
         let mv = moves[i];
-        set_capture_value(
+        let value = capture_value(
             mv,
             board.piece_on(mv.get_from()).unwrap(),
             board.piece_on(mv.get_to()).unwrap_or(Piece::Pawn),
             controled.has_sq(mv.get_to()),
-            thread_data
         );
+        moves.set_score(i, value);
     }
-    moves.sort_by(|b, a| thread_data.move_values[*a as usize].cmp(&thread_data.move_values[*b as usize]));
+    moves.sort_by_score();
 }
+#[allow(clippy::too_many_arguments)]
 pub fn set_root_move_value(
     m: Move,
     piece_at_start: Piece,
@@ -181,8 +179,8 @@ pub fn set_root_move_value(
     is_controled: bool,
     color: Color,
     is_tt_move: bool,
-    move_values: &mut Vec<Score>,
-    move_types: &mut Vec<MoveType>,
+    move_values: &mut [Score],
+    move_types: &mut [MoveType],
 ){
     let mt = move_type(
         piece_at_start,
@@ -193,9 +191,9 @@ pub fn set_root_move_value(
         m.is_promotion(),
     );
     let mut value = 0;
-    if piece_at_end.is_some() {
+    if let Some(piece_at_end) = piece_at_end {
         //captures sorted with MVV_LVA
-        value += MVV_LVA[piece_at_end.unwrap() as usize][piece_at_start as usize];
+        value += MVV_LVA[piece_at_end as usize][piece_at_start as usize];
     } else {
         if is_controled && piece_at_start != Piece::Pawn {
             value -= 1000000;
@@ -212,7 +210,7 @@ pub fn set_root_move_value(
     move_types[m as usize] = mt;
 
 }
-pub fn sort_root_moves(board: &Board, tt_move: Move, moves: &mut Vec<Move>){
+pub fn sort_root_moves(board: &Board, tt_move: Move, moves: &mut MoveList){
     let mut move_types = vec![MoveType::BadCapture; Move::MAX as usize];
     let mut move_values = vec![0; Move::MAX as usize];
 
@@ -222,13 +220,12 @@ pub fn sort_root_moves(board: &Board, tt_move: Move, moves: &mut Vec<Move>){
     } else {
         (pawns << 7 & NOT_FILE_H_BB) | (pawns << 9 & NOT_FILE_A_BB)
     };
-    for i in 0..moves.len() {
-        let mv = moves[i];
+    for &mv in moves.iter() {
         let p = board.piece_on(mv.get_from()).unwrap();
         set_root_move_value(
             mv,
             p,
-            board.piece_on(mv.get_to()),
+            board.captured_piece(mv),
             controled.has_sq(mv.get_to()),
             board.turn,
             mv == tt_move,
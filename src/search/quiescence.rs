@@ -1,7 +1,7 @@
-use crate::{core::{Board, movegen::generate_quiescence_moves}, evaluation::evaluate::evaluate, search::{defs::ThreadData, moves::sort_captures}, transposition_table::TranspositionTable};
+use crate::{core::{Board, move_list::MoveList, movegen::{GenType, generate_moves}}, evaluation::evaluate::evaluate, search::{defs::ThreadData, moves::sort_captures}, transposition_table::TranspositionTable};
 
 pub fn quiescence(
-    board: &Board,
+    board: &mut Board,
     alpha: i16,
     beta: i16,
     tt: &TranspositionTable,
@@ -17,11 +17,18 @@ pub fn quiescence(
     if alpha < stand_pat {
         alpha = stand_pat;
     }
-    let mut moves = Vec::with_capacity(20);
-    generate_quiescence_moves(board, &mut moves);
-    sort_captures(board, &mut moves, thread_data);
-    for mv in moves {
-        let score = -quiescence(&board.make_move_new(mv), -beta, -alpha, tt, thread_data);
+    let mut moves = MoveList::new();
+    let gen_type = if board.checkers == 0 {
+        GenType::Captures
+    } else {
+        GenType::Evasions
+    };
+    generate_moves(board, gen_type, &mut moves);
+    sort_captures(board, &mut moves);
+    for mv in &moves {
+        let state = board.make_move(mv);
+        let score = -quiescence(board, -beta, -alpha, tt, thread_data);
+        board.unmake_move(mv, &state);
         if score >= beta {
             return beta;
         }
@@ -1,20 +1,23 @@
 use std::sync::{Arc, Mutex};
 use std::thread;
 
-use crate::core::Board;
+use crate::core::{Board, board::StateInfo};
+use crate::core::move_list::MoveList;
 use crate::core::movegen::generate_all_moves;
 use crate::search::alpha_beta::alpha_beta;
 use crate::search::defs::{Depth, SearchResult, Score, ThreadData};
 use crate::search::moves::sort_root_moves;
+use crate::syzygy::probe as syzygy;
 use crate::transposition_table::{Flag, TTEntry, TranspositionTable};
 
 pub fn search_root_parallel(
     board: &Board,
+    history: &[StateInfo],
     depth: Depth,
     tt: Arc<TranspositionTable>,
     threads: usize,
 ) -> SearchResult {
-    let mut moves = Vec::with_capacity(218);
+    let mut moves = MoveList::new();
     generate_all_moves(board, &mut moves);
 
     if moves.is_empty(){
@@ -24,40 +27,58 @@ pub fn search_root_parallel(
         return SearchResult::checkmate();
     }
 
-    sort_root_moves(board, tt.lookup_position(board.hash).unwrap_or(TTEntry::default()).best_move, &mut moves);
+    // only the moves that keep the tablebase result, the search picks among them
+    let mut tb_score = None;
+    let mut tb_hits = 0;
+    if let Some(tablebases) = syzygy::active().filter(|t| t.covers(board)) {
+        let mut copy = *board;
+        if let Some((kept, dtz)) = tablebases.filter_root_moves(&mut copy, &moves, board.is_repetition(history)) {
+            tb_hits = moves.len() as u64;
+            tb_score = Some(syzygy::root_score(dtz, board.halfmove_clock as i32));
+            if !kept.is_empty() {
+                moves = kept;
+            }
+        }
+    }
+
+    sort_root_moves(board, tt.lookup_position(board.hash).unwrap_or_default().best_move, &mut moves);
     // Shared best result
     let best = Arc::new(Mutex::new(SearchResult::inital()));
     // Split root moves into chunks
-    let chunk_size = (moves.len() + threads - 1) / threads;
+    let chunk_size = moves.len().div_ceil(threads);
 
     let mut handles = Vec::new();
 
     for chunk in moves.chunks(chunk_size) {
-        let board = board.clone();
+        let board = *board;
         let tt = Arc::clone(&tt);
         let best = Arc::clone(&best);
         best.lock().unwrap().depth = depth;
         
         let chunk = chunk.to_vec();
+        let history = history.to_vec();
 
         handles.push(thread::spawn(move || {
             let mut thread_data = ThreadData::new();
             thread_data.depth = depth;
             thread_data.ply = 1;
+            thread_data.states = history;
 
             let mut local_best = SearchResult::inital();
 
             for mv in chunk {
-                let mut p = board.clone();
-                p.make_move(mv);
+                let mut p = board;
+                let state = p.make_move(mv);
+                thread_data.states.push(state);
 
                 let score = -alpha_beta(
-                    &p,
+                    &mut p,
                     local_best.eval,
                     Score::MAX,
                     &tt,
                     &mut thread_data,
                 );
+                thread_data.states.pop();
                 if score > local_best.eval {
                     local_best.eval = score;
                     local_best.best_move = mv;
@@ -75,11 +96,16 @@ pub fn search_root_parallel(
         h.join().unwrap();
     }
     let mut final_result = best.lock().unwrap();
+    final_result.tb_hits += tb_hits;
+    // the tables know better than the search unless it found a mate
+    if let Some(score) = tb_score && final_result.mate == 0 {
+        final_result.set_eval(score);
+    }
     let entry = TTEntry{
         age: 0,
         key: (board.hash >> 48) as u16,
         best_move: final_result.best_move,
-        depth: depth,
+        depth,
         eval: final_result.eval,
         flag: Flag::EXACT,
     };
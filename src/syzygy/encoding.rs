@@ -0,0 +1,359 @@
+use std::sync::OnceLock;
+
+use crate::{
+    core::{
+        Board, Color, Piece,
+        bitboard::BitBoardUtil,
+        square::{Square, SquareUtil},
+        tables::magics::get_king_moves,
+    },
+    evaluation::endgame::{MaterialKey, signature_key},
+};
+
+// the most pieces a table can have
+pub const TB_PIECES: usize = 7;
+// positions with the leading pieces in the a1-d1-d4 triangle, and with only two kings
+const UNIQUE_LEAD_SIZE: u64 = 31332;
+const KINGS_LEAD_SIZE: u64 = 462;
+
+// pieces in the files are 1 to 6 for the white pawn to king, black ones have 8 added
+pub fn piece_code(piece: Piece, color: Color) -> u8 {
+    return piece as u8 + 1 + 8 * color as u8;
+}
+
+// what a table holds, the first side of the name is white in key and black in key2
+pub struct Material {
+    pub name: String,
+    pub key: MaterialKey,
+    pub key2: MaterialKey,
+    pub piece_count: usize,
+    pub has_pawns: bool,
+    pub has_unique_pieces: bool,
+    // pawns of the leading color first, that is the side with fewer of them
+    pub pawn_count: [usize; 2],
+}
+impl Material {
+    // "KRPvKR" and the like, both sides start with the king
+    pub fn from_name(name: &str) -> Option<Material> {
+        let (strong, weak) = name.split_once('v')?;
+        let valid = |side: &str| side.starts_with('K') && side[1..].chars().all(|c| "QRBNP".contains(c));
+        if !valid(strong) || !valid(weak) || strong.len() + weak.len() > TB_PIECES {
+            return None;
+        }
+        let code = format!("{}{}", strong, weak);
+        let counts = |side: &str, c: char| side.chars().filter(|&p| p == c).count();
+        let pawns = [counts(strong, 'P'), counts(weak, 'P')];
+        // the side with the pawns when only one has them, else the one with fewer
+        let white_leads = pawns[1] == 0 || (pawns[0] != 0 && pawns[1] >= pawns[0]);
+        let has_unique_pieces = "QRBNP".chars().any(|c| counts(strong, c) == 1 || counts(weak, c) == 1);
+        return Some(Material {
+            name: name.to_string(),
+            key: signature_key(&code, Color::White),
+            key2: signature_key(&code, Color::Black),
+            piece_count: code.len(),
+            has_pawns: pawns[0] + pawns[1] > 0,
+            has_unique_pieces,
+            pawn_count: if white_leads { [pawns[0], pawns[1]] } else { [pawns[1], pawns[0]] },
+        });
+    }
+}
+
+// the tables that turn squares into parts of an index
+struct Indexing {
+    // a2-h7 to 0..47, the leading pawn is the one with the highest value
+    map_pawns: [usize; 64],
+    // squares below the a1-h8 diagonal to 0..27
+    map_b1h1h7: [u64; 64],
+    // the a1-d1-d4 triangle to 0..9, the diagonal last
+    map_a1d1d4: [usize; 64],
+    // two kings with the first one in the triangle to 0..461
+    map_kk: [[u64; 64]; 10],
+    // binomial[k][n] ways to choose k of n
+    binomial: [[u64; 64]; 6],
+    lead_pawn_idx: [[u64; 64]; 6],
+    lead_pawns_size: [[u64; 4]; 6],
+}
+static INDEXING: OnceLock<Indexing> = OnceLock::new();
+
+// rank minus file, 0 on the a1-h8 diagonal and negative below it
+fn off_diagonal(sq: Square) -> i32 {
+    return sq.get_rank() as i32 - sq.get_file() as i32;
+}
+fn indexing() -> &'static Indexing {
+    return INDEXING.get_or_init(|| {
+        let mut t = Indexing {
+            map_pawns: [0; 64],
+            map_b1h1h7: [0; 64],
+            map_a1d1d4: [0; 64],
+            map_kk: [[0; 64]; 10],
+            binomial: [[0; 64]; 6],
+            lead_pawn_idx: [[0; 64]; 6],
+            lead_pawns_size: [[0; 4]; 6],
+        };
+        let mut code = 0;
+        for sq in 0..64 {
+            if off_diagonal(sq) < 0 {
+                t.map_b1h1h7[sq as usize] = code;
+                code += 1;
+            }
+        }
+        let mut code = 0;
+        let mut diagonal = vec![];
+        for sq in 0..28u8 {
+            if off_diagonal(sq) < 0 && sq.get_file() <= 3 {
+                t.map_a1d1d4[sq as usize] = code;
+                code += 1;
+            } else if off_diagonal(sq) == 0 && sq.get_file() <= 3 {
+                diagonal.push(sq);
+            }
+        }
+        for sq in diagonal {
+            t.map_a1d1d4[sq as usize] = code;
+            code += 1;
+        }
+        let mut code = 0;
+        let mut both_on_diagonal = vec![];
+        for idx in 0..10 {
+            // b1 is the only square of the triangle mapped to 0
+            for s1 in (0..28u8).filter(|&s| t.map_a1d1d4[s as usize] == idx && (idx != 0 || s == 1)) {
+                for s2 in 0..64u8 {
+                    if (get_king_moves(s1) | 1 << s1).has_sq(s2) {
+                        continue;
+                    }
+                    if off_diagonal(s1) == 0 && off_diagonal(s2) > 0 {
+                        continue;
+                    }
+                    if off_diagonal(s1) == 0 && off_diagonal(s2) == 0 {
+                        both_on_diagonal.push((idx, s2));
+                    } else {
+                        t.map_kk[idx][s2 as usize] = code;
+                        code += 1;
+                    }
+                }
+            }
+        }
+        for (idx, sq) in both_on_diagonal {
+            t.map_kk[idx][sq as usize] = code;
+            code += 1;
+        }
+        t.binomial[0][0] = 1;
+        for n in 1..64 {
+            for k in 0..6.min(n + 1) {
+                t.binomial[k][n] = if k > 0 { t.binomial[k - 1][n - 1] } else { 0 }
+                    + if k < n { t.binomial[k][n - 1] } else { 0 };
+            }
+        }
+        let mut available = 47;
+        for lead_count in 1..6 {
+            for file in 0..4u8 {
+                let mut idx = 0;
+                for rank in 1..7u8 {
+                    let sq = rank * 8 + file;
+                    if lead_count == 1 {
+                        t.map_pawns[sq as usize] = available;
+                        t.map_pawns[(sq ^ 7) as usize] = available - 1;
+                        available = available.saturating_sub(2);
+                    }
+                    t.lead_pawn_idx[lead_count][sq as usize] = idx;
+                    idx += t.binomial[lead_count - 1][t.map_pawns[sq as usize]];
+                }
+                t.lead_pawns_size[lead_count][file as usize] = idx;
+            }
+        }
+        return t;
+    });
+}
+
+// how the pieces of one side to move and file are grouped into an index, the order in the file
+#[derive(Clone, Copy, Default)]
+pub struct Layout {
+    pub pieces: [u8; TB_PIECES],
+    pub group_len: [usize; TB_PIECES + 1],
+    pub group_idx: [u64; TB_PIECES + 1],
+}
+impl Layout {
+    // order[0] is where the leading group comes in the index and order[1] the other pawns, 15 for none
+    pub fn new(material: &Material, pieces: [u8; TB_PIECES], order: [u8; 2], file: usize) -> Layout {
+        let t = indexing();
+        let mut layout = Layout { pieces, group_len: [0; TB_PIECES + 1], group_idx: [0; TB_PIECES + 1] };
+        let mut first_len: i32 = if material.has_pawns {
+            0
+        } else if material.has_unique_pieces {
+            3
+        } else {
+            2
+        };
+        let mut n = 0;
+        layout.group_len[0] = 1;
+        for i in 1..material.piece_count {
+            first_len -= 1;
+            if first_len > 0 || pieces[i] == pieces[i - 1] {
+                layout.group_len[n] += 1;
+            } else {
+                n += 1;
+                layout.group_len[n] = 1;
+            }
+        }
+        n += 1;
+        // the other color's pawns are the second group
+        let pp = material.has_pawns && material.pawn_count[1] > 0;
+        let mut next = if pp { 2 } else { 1 };
+        let mut free = 64 - layout.group_len[0] - if pp { layout.group_len[1] } else { 0 };
+        let mut idx: u64 = 1;
+        let mut k = 0;
+        while next < n || k == order[0] || k == order[1] {
+            if k == order[0] {
+                layout.group_idx[0] = idx;
+                idx *= if material.has_pawns {
+                    t.lead_pawns_size[layout.group_len[0]][file]
+                } else if material.has_unique_pieces {
+                    UNIQUE_LEAD_SIZE
+                } else {
+                    KINGS_LEAD_SIZE
+                };
+            } else if k == order[1] {
+                layout.group_idx[1] = idx;
+                idx *= t.binomial[layout.group_len[1]][48 - layout.group_len[0]];
+            } else {
+                layout.group_idx[next] = idx;
+                idx *= t.binomial[layout.group_len[next]][free];
+                free -= layout.group_len[next];
+                next += 1;
+            }
+            k += 1;
+        }
+        layout.group_idx[n] = idx;
+        return layout;
+    }
+    // the number of indices
+    pub fn size(&self) -> u64 {
+        let groups = self.group_len.iter().position(|&len| len == 0).unwrap_or(TB_PIECES);
+        return self.group_idx[groups];
+    }
+}
+
+// where a position is in a table: the side to move and the file of the leading pawn as the table
+// sees them, and the index
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Encoded {
+    pub stm: usize,
+    pub file: usize,
+    pub index: u64,
+}
+
+// the pieces of a position by ascending square, it is what the encoding works on
+pub fn board_pieces(board: &Board) -> Vec<(u8, Square)> {
+    let mut pieces = Vec::with_capacity(TB_PIECES);
+    let mut occupied = board.combined;
+    while occupied != 0 {
+        let sq = occupied.pop_lsb();
+        let color = if board.color_combined[Color::White as usize].has_sq(sq) { Color::White } else { Color::Black };
+        pieces.push((piece_code(board.piece_on(sq).unwrap(), color), sq));
+    }
+    return pieces;
+}
+
+// the index of a position given its pieces by ascending square, layouts are by side to move and
+// file and a table that stores a single side has it first
+pub fn encode(
+    material: &Material,
+    layouts: &[[Layout; 4]; 2],
+    single_side: bool,
+    key: MaterialKey,
+    black_to_move: bool,
+    pieces: &[(u8, Square)],
+) -> Encoded {
+    let t = indexing();
+    // the strong side is white in the table, symmetric tables only have white to move
+    let flip = (material.key == material.key2 && black_to_move) || key != material.key;
+    let (flip_color, flip_squares) = if flip { (8, 56) } else { (0, 0) };
+    let stm = (flip ^ black_to_move) as usize;
+    let mut squares = [0u8; TB_PIECES];
+    let mut codes = [0u8; TB_PIECES];
+    let mut size = 0;
+    let mut file = 0;
+    let lead = layouts[0][0].pieces[0] ^ flip_color;
+    if material.has_pawns {
+        for &(code, sq) in pieces.iter().filter(|&&(code, _)| code == lead) {
+            squares[size] = sq ^ flip_squares;
+            codes[size] = code ^ flip_color;
+            size += 1;
+        }
+        let max = (0..size).max_by_key(|&i| t.map_pawns[squares[i] as usize]).unwrap_or(0);
+        squares.swap(0, max);
+        file = (squares[0].get_file() as usize).min(7 - squares[0].get_file() as usize);
+    }
+    let lead_count = size;
+    for &(code, sq) in pieces.iter().filter(|&&(code, _)| !material.has_pawns || code != lead) {
+        squares[size] = sq ^ flip_squares;
+        codes[size] = code ^ flip_color;
+        size += 1;
+    }
+    let layout = &layouts[if single_side { 0 } else { stm }][file];
+    // the same order as the layout
+    for i in lead_count..size.saturating_sub(1) {
+        if let Some(j) = (i + 1..size).find(|&j| layout.pieces[i] == codes[j]) {
+            codes.swap(i, j);
+            squares.swap(i, j);
+        }
+    }
+    // the leading piece goes to the a to d files
+    if squares[0].get_file() > 3 {
+        squares[..size].iter_mut().for_each(|sq| *sq ^= 7);
+    }
+    let mut idx;
+    if material.has_pawns {
+        idx = t.lead_pawn_idx[lead_count][squares[0] as usize];
+        squares[1..lead_count].sort_by_key(|&sq| t.map_pawns[sq as usize]);
+        for (i, &sq) in squares.iter().enumerate().take(lead_count).skip(1) {
+            idx += t.binomial[i][t.map_pawns[sq as usize]];
+        }
+    } else {
+        // then below the fifth rank and below the diagonal
+        if squares[0].get_rank() > 3 {
+            squares[..size].iter_mut().for_each(|sq| *sq ^= 56);
+        }
+        if let Some(i) = (0..layout.group_len[0]).find(|&i| off_diagonal(squares[i]) != 0)
+            && off_diagonal(squares[i]) > 0
+        {
+            squares[i..size].iter_mut().for_each(|sq| *sq = ((*sq >> 3) | (*sq << 3)) & 63);
+        }
+        idx = if material.has_unique_pieces {
+            let (s0, s1, s2) = (squares[0], squares[1], squares[2]);
+            let adjust1 = (s1 > s0) as u64;
+            let adjust2 = (s2 > s0) as u64 + (s2 > s1) as u64;
+            let rank = |sq: Square| sq.get_rank() as u64;
+            if off_diagonal(s0) != 0 {
+                (t.map_a1d1d4[s0 as usize] as u64 * 63 + (s1 as u64 - adjust1)) * 62 + s2 as u64 - adjust2
+            } else if off_diagonal(s1) != 0 {
+                (6 * 63 + rank(s0) * 28 + t.map_b1h1h7[s1 as usize]) * 62 + s2 as u64 - adjust2
+            } else if off_diagonal(s2) != 0 {
+                6 * 63 * 62 + 4 * 28 * 62 + rank(s0) * 7 * 28 + (rank(s1) - adjust1) * 28 + t.map_b1h1h7[s2 as usize]
+            } else {
+                6 * 63 * 62 + 4 * 28 * 62 + 4 * 7 * 28 + rank(s0) * 7 * 6 + (rank(s1) - adjust1) * 6 + (rank(s2) - adjust2)
+            }
+        } else {
+            t.map_kk[t.map_a1d1d4[squares[0] as usize]][squares[1] as usize]
+        };
+    }
+    idx *= layout.group_idx[0];
+    // the other groups by ascending squares, counting only the squares the earlier groups left free
+    let mut start = layout.group_len[0];
+    let mut remaining_pawns = material.has_pawns && material.pawn_count[1] > 0;
+    let mut next = 1;
+    while layout.group_len[next] != 0 {
+        let len = layout.group_len[next];
+        squares[start..start + len].sort();
+        let mut n = 0;
+        for i in 0..len {
+            let sq = squares[start + i];
+            let adjust = squares[..start].iter().filter(|&&s| sq > s).count();
+            n += t.binomial[i + 1][sq as usize - adjust - if remaining_pawns { 8 } else { 0 }];
+        }
+        remaining_pawns = false;
+        idx += n * layout.group_idx[next];
+        start += len;
+        next += 1;
+    }
+    return Encoded { stm, file, index: idx };
+}
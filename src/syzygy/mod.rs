@@ -0,0 +1,5 @@
+pub mod encoding;
+pub mod table;
+pub mod probe;
+pub mod writer;
+pub mod syzygy_test;
@@ -0,0 +1,356 @@
+use std::{
+    collections::HashMap,
+    env, fs,
+    path::PathBuf,
+    ptr::null_mut,
+    sync::{
+        OnceLock,
+        atomic::{AtomicBool, AtomicI8, AtomicPtr, Ordering},
+    },
+};
+
+use crate::{
+    core::{
+        Board, Color, Piece,
+        move_list::MoveList,
+        movegen::generate_all_moves,
+        r#move::{Move, MoveUtil},
+    },
+    evaluation::endgame::MaterialKey,
+    search::defs::{Depth, Score, TB_WIN_SCORE},
+    syzygy::{
+        encoding::{Material, board_pieces, encode},
+        table::{Kind, TableFile},
+    },
+};
+
+// the result for the side to move, cursed wins and blessed losses are draws by the 50 move rule
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Wdl {
+    Loss = -2,
+    BlessedLoss = -1,
+    Draw = 0,
+    CursedWin = 1,
+    Win = 2,
+}
+impl Wdl {
+    fn from_value(value: i32) -> Wdl {
+        return match value {
+            ..=-2 => Wdl::Loss,
+            -1 => Wdl::BlessedLoss,
+            0 => Wdl::Draw,
+            1 => Wdl::CursedWin,
+            _ => Wdl::Win,
+        };
+    }
+    pub fn negate(self) -> Wdl {
+        return Wdl::from_value(-(self as i32));
+    }
+    pub fn to_str(self) -> &'static str {
+        return match self {
+            Wdl::Loss => "loss",
+            Wdl::BlessedLoss => "blessed loss",
+            Wdl::Draw => "draw",
+            Wdl::CursedWin => "cursed win",
+            Wdl::Win => "win",
+        };
+    }
+}
+
+// what a dtz table says about a position, it may only store the other side to move
+enum Probe {
+    Value(i32),
+    OtherSide,
+}
+
+// a table found on disk, its files are read the first time a position needs them
+struct Table {
+    material: Material,
+    wdl_path: PathBuf,
+    dtz_path: Option<PathBuf>,
+    wdl: OnceLock<Option<TableFile>>,
+    dtz: OnceLock<Option<TableFile>>,
+}
+impl Table {
+    fn file(&self, kind: Kind) -> Option<&TableFile> {
+        let (lock, path) = match kind {
+            Kind::Wdl => (&self.wdl, Some(&self.wdl_path)),
+            Kind::Dtz => (&self.dtz, self.dtz_path.as_ref()),
+        };
+        return lock
+            .get_or_init(|| {
+                let path = path?;
+                let file = fs::read(path)
+                    .map_err(|e| e.to_string())
+                    .and_then(|bytes| TableFile::from_bytes(bytes, &self.material, kind));
+                if let Err(e) = &file {
+                    println!("info string could not read {}: {}", path.display(), e);
+                }
+                return file.ok();
+            })
+            .as_ref();
+    }
+}
+
+pub struct Tablebases {
+    tables: Vec<Table>,
+    by_key: HashMap<MaterialKey, usize>,
+    pub max_pieces: usize,
+}
+
+// zeroing moves reset the 50 move counter
+fn is_zeroing(board: &Board, m: Move) -> bool {
+    return board.captured_piece(m).is_some() || m.is_ep() || board.piece_on(m.get_from()) == Some(Piece::Pawn);
+}
+fn is_capture(board: &Board, m: Move) -> bool {
+    return board.captured_piece(m).is_some() || m.is_ep();
+}
+fn is_mate(board: &Board) -> bool {
+    let mut moves = MoveList::new();
+    generate_all_moves(board, &mut moves);
+    return board.checkers != 0 && moves.is_empty();
+}
+// the dtz of a position whose best move zeroes the counter
+fn dtz_before_zeroing(wdl: Wdl) -> i32 {
+    return match wdl {
+        Wdl::Win => 1,
+        Wdl::CursedWin => 101,
+        Wdl::BlessedLoss => -101,
+        Wdl::Loss => -1,
+        Wdl::Draw => 0,
+    };
+}
+fn sign(value: i32) -> i32 {
+    return value.signum();
+}
+
+impl Tablebases {
+    // every <name>.rtbw in the directories of the path, separated like the PATH variable, with its
+    // .rtbz when there is one
+    pub fn open(paths: &str) -> Result<Tablebases, String> {
+        let mut tablebases = Tablebases { tables: vec![], by_key: HashMap::new(), max_pieces: 0 };
+        for dir in env::split_paths(paths) {
+            let entries = fs::read_dir(&dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+            let mut paths = entries.filter_map(|e| e.ok()).map(|e| e.path()).collect::<Vec<PathBuf>>();
+            paths.sort();
+            for wdl_path in paths {
+                if wdl_path.extension().is_none_or(|e| e != Kind::Wdl.extension()) {
+                    continue;
+                }
+                let Some(material) = wdl_path.file_stem().and_then(|s| s.to_str()).and_then(Material::from_name) else {
+                    continue;
+                };
+                if tablebases.by_key.contains_key(&material.key) {
+                    continue;
+                }
+                let dtz_path = wdl_path.with_extension(Kind::Dtz.extension());
+                tablebases.by_key.insert(material.key, tablebases.tables.len());
+                tablebases.by_key.insert(material.key2, tablebases.tables.len());
+                tablebases.max_pieces = tablebases.max_pieces.max(material.piece_count);
+                tablebases.tables.push(Table {
+                    material,
+                    dtz_path: if dtz_path.is_file() { Some(dtz_path) } else { None },
+                    wdl_path,
+                    wdl: OnceLock::new(),
+                    dtz: OnceLock::new(),
+                });
+            }
+        }
+        return Ok(tablebases);
+    }
+    pub fn len(&self) -> usize {
+        return self.tables.len();
+    }
+    // tables know nothing about castling
+    pub fn covers(&self, board: &Board) -> bool {
+        return board.castle_rights == 0 && board.combined.count_ones() as usize <= self.max_pieces;
+    }
+    // en passant is not in the tables, the searches below take care of it
+    fn probe_table(&self, board: &Board, kind: Kind, wdl: i32) -> Option<Probe> {
+        if board.combined.count_ones() == 2 {
+            return Some(Probe::Value(0));
+        }
+        let table = &self.tables[*self.by_key.get(&board.material_key)?];
+        let file = table.file(kind)?;
+        let pieces = board_pieces(board);
+        let encoded =
+            encode(&table.material, &file.layouts, kind == Kind::Dtz, board.material_key, board.turn == Color::Black, &pieces);
+        let symmetric = table.material.key == table.material.key2 && !table.material.has_pawns;
+        if kind == Kind::Dtz && !file.has_side(&encoded) && !symmetric {
+            return Some(Probe::OtherSide);
+        }
+        return Some(Probe::Value(file.probe(&encoded, wdl)));
+    }
+    // the best of the captures, and of the pawn moves when asked, against what the table says. also
+    // whether the best move zeroes the counter
+    fn search(&self, board: &mut Board, check_zeroing: bool) -> Option<(Wdl, bool)> {
+        let mut moves = MoveList::new();
+        generate_all_moves(board, &mut moves);
+        let mut best = Wdl::Loss;
+        let mut count = 0;
+        for m in &moves {
+            if !is_capture(board, m) && (!check_zeroing || board.piece_on(m.get_from()) != Some(Piece::Pawn)) {
+                continue;
+            }
+            count += 1;
+            let state = board.make_move(m);
+            let value = self.search(board, false);
+            board.unmake_move(m, &state);
+            let value = value?.0.negate();
+            if value > best {
+                best = value;
+                if value >= Wdl::Win {
+                    return Some((value, true));
+                }
+            }
+        }
+        // when every move was searched the table is not needed
+        let no_more_moves = count > 0 && count == moves.len();
+        let value = if no_more_moves {
+            best
+        } else {
+            match self.probe_table(board, Kind::Wdl, 0)? {
+                Probe::Value(value) => Wdl::from_value(value),
+                Probe::OtherSide => return None,
+            }
+        };
+        if best >= value {
+            return Some((best, best > Wdl::Draw || no_more_moves));
+        }
+        return Some((value, false));
+    }
+    pub fn probe_wdl(&self, board: &mut Board) -> Option<Wdl> {
+        return self.search(board, false).map(|(wdl, _)| wdl);
+    }
+    // plies to the next zeroing move with best play, positive for the side that wins and 100 more
+    // for cursed wins and blessed losses. not exact but safe to play by after a zeroing move
+    pub fn probe_dtz(&self, board: &mut Board) -> Option<i32> {
+        let (wdl, zeroing) = self.search(board, true)?;
+        if wdl == Wdl::Draw {
+            return Some(0);
+        }
+        if zeroing {
+            return Some(dtz_before_zeroing(wdl));
+        }
+        let wdl_value = wdl as i32;
+        if let Probe::Value(dtz) = self.probe_table(board, Kind::Dtz, wdl_value)? {
+            let cursed = if wdl_value.abs() == 1 { 100 } else { 0 };
+            return Some((dtz + cursed) * sign(wdl_value));
+        }
+        // the table has the other side to move, it is one ply away
+        let mut moves = MoveList::new();
+        generate_all_moves(board, &mut moves);
+        let mut min_dtz = i32::MAX;
+        for m in &moves {
+            let zeroing = is_zeroing(board, m);
+            let state = board.make_move(m);
+            let dtz = if zeroing {
+                self.search(board, false).map(|(wdl, _)| -dtz_before_zeroing(wdl))
+            } else {
+                self.probe_dtz(board).map(|dtz| -dtz)
+            };
+            let mated = dtz == Some(1) && is_mate(board);
+            board.unmake_move(m, &state);
+            let mut dtz = dtz?;
+            // a mate is a zeroing move
+            if mated {
+                min_dtz = 1;
+            }
+            if !zeroing {
+                dtz += sign(dtz);
+            }
+            if dtz < min_dtz && sign(dtz) == sign(wdl_value) {
+                min_dtz = dtz;
+            }
+        }
+        // no legal moves, mated
+        return Some(if min_dtz == i32::MAX { -1 } else { min_dtz });
+    }
+    // the root moves worth searching by their dtz and the dtz of the root. winning, only the moves
+    // that win in time, or the fastest one when none does. losing, every move unless the counter
+    // runs out, then the slowest loss. drawing, the moves that keep the draw
+    pub fn filter_root_moves(&self, board: &mut Board, moves: &MoveList, repeated: bool) -> Option<(MoveList, i32)> {
+        let dtz = self.probe_dtz(board)?;
+        let cnt50 = board.halfmove_clock as i32;
+        let mut values = Vec::with_capacity(moves.len());
+        for m in moves {
+            let state = board.make_move(m);
+            let value = if dtz > 0 && is_mate(board) {
+                Some(1)
+            } else if board.halfmove_clock != 0 {
+                self.probe_dtz(board).map(|v| -v + sign(-v))
+            } else {
+                self.probe_wdl(board).map(|wdl| dtz_before_zeroing(wdl.negate()))
+            };
+            board.unmake_move(m, &state);
+            values.push(value?);
+        }
+        let keep: Box<dyn Fn(i32) -> bool> = if dtz > 0 {
+            let best = values.iter().copied().filter(|&v| v > 0).min().unwrap_or(0);
+            let max = if !repeated && best + cnt50 <= 99 { 99 - cnt50 } else { best };
+            Box::new(move |v| v > 0 && v <= max)
+        } else if dtz < 0 {
+            let best = values.iter().copied().min().unwrap_or(0);
+            Box::new(move |v| -best * 2 + cnt50 < 100 || v == best)
+        } else {
+            Box::new(|v| v == 0)
+        };
+        let mut kept = MoveList::new();
+        for (m, &value) in moves.into_iter().zip(&values) {
+            if keep(value) {
+                kept.push(m);
+            }
+        }
+        return Some((kept, dtz));
+    }
+}
+
+// set between searches only, the uci loop runs a search to its end before it reads the next
+// command, so no search thread holds the tables when they are replaced
+static TABLEBASES: AtomicPtr<Tablebases> = AtomicPtr::new(null_mut());
+static PROBE_DEPTH: AtomicI8 = AtomicI8::new(1);
+static RULE_50: AtomicBool = AtomicBool::new(true);
+
+// the reference lasts until the next load_tablebases
+pub fn active() -> Option<&'static Tablebases> {
+    let tablebases = TABLEBASES.load(Ordering::Acquire);
+    if tablebases.is_null() {
+        return None;
+    }
+    return Some(unsafe { &*tablebases });
+}
+// no tables with an empty path
+pub fn load_tablebases(paths: &str) -> Result<usize, String> {
+    let tablebases = if paths.is_empty() || paths == "<empty>" { None } else { Some(Tablebases::open(paths)?) };
+    let count = tablebases.as_ref().map_or(0, |t| t.len());
+    let tablebases = tablebases.filter(|t| t.len() > 0).map_or(null_mut(), |t| Box::into_raw(Box::new(t)));
+    let old = TABLEBASES.swap(tablebases, Ordering::AcqRel);
+    if !old.is_null() {
+        // SAFETY: old came from Box::into_raw above and no search is running to probe it
+        drop(unsafe { Box::from_raw(old) });
+    }
+    return Ok(count);
+}
+// positions with fewer pieces than the largest tables are probed at any depth
+pub fn probe_depth() -> Depth {
+    return PROBE_DEPTH.load(Ordering::Relaxed);
+}
+pub fn set_probe_depth(depth: Depth) {
+    PROBE_DEPTH.store(depth, Ordering::Relaxed);
+}
+pub fn use_rule_50() -> bool {
+    return RULE_50.load(Ordering::Relaxed);
+}
+pub fn set_rule_50(enabled: bool) {
+    RULE_50.store(enabled, Ordering::Relaxed);
+}
+// the score of the root by its dtz, wins the counter runs out on score just above a draw and less
+// the closer they get to it
+pub fn root_score(dtz: i32, cnt50: i32) -> Score {
+    if dtz == 0 {
+        return 0;
+    }
+    let plies = dtz.abs() + cnt50;
+    let score = if !use_rule_50() || plies <= 100 { TB_WIN_SCORE } else { ((200 - plies).max(2) / 2) as Score };
+    return score * sign(dtz) as Score;
+}
@@ -0,0 +1,404 @@
+use std::{collections::VecDeque, fs, path::Path};
+
+use crate::{
+    core::{
+        Board, Color, Piece,
+        move_list::MoveList,
+        movegen::generate_all_moves,
+        r#move::MoveUtil,
+        square::{Square, SquareUtil},
+    },
+    syzygy::{
+        encoding::{Layout, Material, TB_PIECES, board_pieces, encode, piece_code},
+        probe::{Tablebases, Wdl},
+        table::{Kind, LOSS_PLIES, WIN_PLIES},
+        writer::{TableSide, write_table},
+    },
+};
+
+// the tables the test writes when none are named, each one only needs those before it
+const DEFAULT_TABLES: [&str; 5] = ["KNvK", "KBvK", "KQvK", "KRvK", "KPvK"];
+
+enum Edge {
+    // a move that stays in the table, and whether it zeroes the counter
+    Inside(usize, bool),
+    // a capture or a promotion, by the result for the side that makes it
+    Outside(Wdl),
+}
+struct Node {
+    black_to_move: bool,
+    pieces: Vec<(u8, Square)>,
+    valid: bool,
+    edges: Vec<Edge>,
+}
+
+fn to_fen(pieces: &[(u8, Square)], black_to_move: bool) -> String {
+    let mut board = [' '; 64];
+    for &(code, sq) in pieces {
+        let c = b"PNBRQK"[(code & 7) as usize - 1] as char;
+        board[sq as usize] = if code < 8 { c } else { c.to_ascii_lowercase() };
+    }
+    let mut fen = String::new();
+    for rank in (0..8).rev() {
+        let mut empty = 0;
+        for file in 0..8 {
+            let c = board[rank * 8 + file];
+            if c == ' ' {
+                empty += 1;
+                continue;
+            }
+            if empty != 0 {
+                fen.push_str(&empty.to_string());
+                empty = 0;
+            }
+            fen.push(c);
+        }
+        if empty != 0 {
+            fen.push_str(&empty.to_string());
+        }
+        if rank != 0 {
+            fen.push('/');
+        }
+    }
+    return format!("{} {} - - 0 1", fen, if black_to_move { 'b' } else { 'w' });
+}
+
+// the pieces of the table as white and black codes, the leading pawns, the other pawns and then the
+// kings first so that they are the leading group without pawns
+fn table_pieces(material: &Material) -> Vec<u8> {
+    let (strong, weak) = material.name.split_once('v').unwrap();
+    const PIECES: [Piece; 6] = [Piece::Pawn, Piece::Knight, Piece::Bishop, Piece::Rook, Piece::Queen, Piece::King];
+    let codes = |side: &str, color: Color| {
+        return side.chars().map(|c| piece_code(PIECES["PNBRQK".find(c).unwrap()], color)).collect::<Vec<u8>>();
+    };
+    let mut pieces = [codes(strong, Color::White), codes(weak, Color::Black)].concat();
+    let white_pawns = strong.chars().filter(|&c| c == 'P').count();
+    let lead = if material.pawn_count[0] == white_pawns && white_pawns > 0 {
+        piece_code(Piece::Pawn, Color::White)
+    } else {
+        piece_code(Piece::Pawn, Color::Black)
+    };
+    let rank = |code: u8| {
+        return if code == lead {
+            0
+        } else if code & 7 == 1 {
+            1
+        } else if code & 7 == 6 {
+            2
+        } else {
+            3
+        };
+    };
+    pieces.sort_by_key(|&code| (rank(code), code));
+    return pieces;
+}
+
+// a piece code and its square
+type Placed = (u8, Square);
+
+// every placement of the pieces, pawns off the first and last ranks. the first piece stays on the
+// a to d files, and in the a1-d1-d4 triangle without pawns, the others are symmetric to these
+fn place(codes: &[u8], placed: &mut Vec<Placed>, visit: &mut dyn FnMut(&[Placed])) {
+    if placed.len() == codes.len() {
+        visit(placed);
+        return;
+    }
+    let code = codes[placed.len()];
+    let pawns = codes[0] & 7 == 1;
+    for sq in 0..64u8 {
+        if placed.iter().any(|&(_, s)| s == sq) || (code & 7 == 1 && (sq.get_rank() == 0 || sq.get_rank() == 7)) {
+            continue;
+        }
+        let (file, rank) = (sq.get_file(), sq.get_rank());
+        if placed.is_empty() && (file > 3 || (!pawns && (rank > file || rank > 3))) {
+            continue;
+        }
+        placed.push((code, sq));
+        place(codes, placed, visit);
+        placed.pop();
+    }
+}
+
+struct Generated {
+    material: Material,
+    layouts: [[Layout; 4]; 2],
+    order: [u8; 2],
+    sides: usize,
+    files: usize,
+    // where each side to move and file starts among the nodes
+    offsets: [[usize; 4]; 2],
+    nodes: Vec<Node>,
+    wdl: Vec<Wdl>,
+    dtz: Vec<i32>,
+}
+impl Generated {
+    fn id(&self, black_to_move: bool, pieces: &[(u8, Square)], key: u64) -> usize {
+        let mut pieces = pieces.to_vec();
+        pieces.sort_by_key(|&(_, sq)| sq);
+        let e = encode(&self.material, &self.layouts, false, key, black_to_move, &pieces);
+        return self.offsets[e.stm][e.file] + e.index as usize;
+    }
+}
+
+// every position of the table with its moves, the results of the tables it leads to come from
+// the ones already written
+fn generate(name: &str, tablebases: Option<&Tablebases>) -> Result<Generated, String> {
+    let material = Material::from_name(name).ok_or(format!("{} is not a table name", name))?;
+    if material.has_pawns && material.pawn_count[1] > 0 {
+        return Err(String::from("en passant is not generated, only one side can have pawns"));
+    }
+    let codes = table_pieces(&material);
+    let mut pieces = [0u8; TB_PIECES];
+    pieces[..codes.len()].copy_from_slice(&codes);
+    let order = [0, 0xF];
+    let files = if material.has_pawns { 4 } else { 1 };
+    let sides = if material.key != material.key2 { 2 } else { 1 };
+    let mut layouts = [[Layout::default(); 4]; 2];
+    let mut offsets = [[0; 4]; 2];
+    let mut size = 0;
+    for side in 0..sides {
+        for f in 0..files {
+            layouts[side][f] = Layout::new(&material, pieces, order, f);
+            offsets[side][f] = size;
+            size += layouts[side][f].size() as usize;
+        }
+    }
+    let mut generated = Generated { material, layouts, order, sides, files, offsets, nodes: vec![], wdl: vec![], dtz: vec![] };
+    let mut nodes: Vec<Option<Node>> = (0..size).map(|_| None).collect();
+    let key = generated.material.key;
+    place(&codes, &mut vec![], &mut |placed| {
+        for black_to_move in [false, true] {
+            let id = generated.id(black_to_move, placed, key);
+            if nodes[id].is_none() {
+                nodes[id] = Some(Node { black_to_move, pieces: placed.to_vec(), valid: false, edges: vec![] });
+            }
+        }
+    });
+    for node in nodes.iter_mut().flatten() {
+        let Ok(mut board) = Board::from_fen(&to_fen(&node.pieces, node.black_to_move)) else {
+            continue;
+        };
+        node.valid = true;
+        let mut moves = MoveList::new();
+        generate_all_moves(&board, &mut moves);
+        for m in &moves {
+            let zeroing = board.captured_piece(m).is_some() || m.is_ep() || board.piece_on(m.get_from()) == Some(Piece::Pawn);
+            let state = board.make_move(m);
+            let edge = if board.material_key == key {
+                Edge::Inside(generated.id(board.turn == Color::Black, &board_pieces(&board), key), zeroing)
+            } else {
+                let child = tablebases.and_then(|t| t.probe_wdl(&mut board));
+                let Some(child) = child.or(if board.combined.count_ones() == 2 { Some(Wdl::Draw) } else { None }) else {
+                    return Err(format!("no table for {} after {}", board.to_fen(), m.to_uci(false)));
+                };
+                Edge::Outside(child.negate())
+            };
+            board.unmake_move(m, &state);
+            node.edges.push(edge);
+        }
+    }
+    generated.nodes = nodes
+        .into_iter()
+        .map(|n| n.unwrap_or(Node { black_to_move: false, pieces: vec![], valid: false, edges: vec![] }))
+        .collect();
+    solve(&mut generated);
+    return Ok(generated);
+}
+
+// wins and losses back from the mates and the other tables, then the plies to the next zeroing move
+// back from the zeroing moves, both in the order the results become known
+fn solve(g: &mut Generated) {
+    let size = g.nodes.len();
+    let mut parents: Vec<Vec<(usize, bool)>> = vec![vec![]; size];
+    for (id, node) in g.nodes.iter().enumerate() {
+        for edge in &node.edges {
+            if let Edge::Inside(child, zeroing) = *edge {
+                parents[child].push((id, zeroing));
+            }
+        }
+    }
+    let mut wdl: Vec<Option<Wdl>> = vec![None; size];
+    let mut escapes = vec![0; size];
+    let mut queue = VecDeque::new();
+    for (id, node) in g.nodes.iter().enumerate() {
+        if !node.valid {
+            wdl[id] = Some(Wdl::Draw);
+            continue;
+        }
+        let outside = node.edges.iter().filter_map(|e| if let Edge::Outside(w) = e { Some(*w) } else { None });
+        escapes[id] = node.edges.iter().filter(|e| matches!(e, Edge::Inside(..))).count();
+        let best = outside.max();
+        if best == Some(Wdl::Win) {
+            wdl[id] = Some(Wdl::Win);
+        } else if best == Some(Wdl::Draw) {
+            // never lost, maybe won
+            escapes[id] += 1;
+        }
+        if node.edges.is_empty() {
+            let board = Board::from_fen(&to_fen(&node.pieces, node.black_to_move)).unwrap();
+            wdl[id] = Some(if board.checkers != 0 { Wdl::Loss } else { Wdl::Draw });
+        } else if escapes[id] == 0 && wdl[id].is_none() {
+            wdl[id] = Some(Wdl::Loss);
+        }
+        if wdl[id].is_some_and(|w| w != Wdl::Draw) {
+            queue.push_back(id);
+        }
+    }
+    while let Some(id) = queue.pop_front() {
+        for &(parent, _) in &parents[id] {
+            if wdl[parent].is_some() {
+                continue;
+            }
+            if wdl[id] == Some(Wdl::Loss) {
+                wdl[parent] = Some(Wdl::Win);
+                queue.push_back(parent);
+            } else {
+                escapes[parent] -= 1;
+                if escapes[parent] == 0 {
+                    wdl[parent] = Some(Wdl::Loss);
+                    queue.push_back(parent);
+                }
+            }
+        }
+    }
+    g.wdl = wdl.into_iter().map(|w| w.unwrap_or(Wdl::Draw)).collect();
+    // a zeroing win or a mate is one ply, a loss with only zeroing moves too
+    let mut dtz = vec![0; size];
+    let mut pending = vec![0; size];
+    let mut buckets: Vec<Vec<usize>> = vec![vec![], vec![]];
+    for (id, node) in g.nodes.iter().enumerate() {
+        let wins = |e: &Edge| match *e {
+            Edge::Inside(child, zeroing) => {
+                g.wdl[child] == Wdl::Loss && (zeroing || g.nodes[child].edges.is_empty())
+            }
+            Edge::Outside(w) => w == Wdl::Win,
+        };
+        if g.wdl[id] == Wdl::Win && node.edges.iter().any(wins) {
+            dtz[id] = 1;
+            buckets[1].push(id);
+        } else if g.wdl[id] == Wdl::Loss {
+            pending[id] = node.edges.iter().filter(|e| matches!(e, Edge::Inside(_, false))).count();
+            if pending[id] == 0 {
+                dtz[id] = -1;
+                buckets[1].push(id);
+            }
+        }
+    }
+    let mut n = 1;
+    while n < buckets.len() {
+        let bucket = std::mem::take(&mut buckets[n]);
+        for id in bucket {
+            for &(parent, zeroing) in &parents[id] {
+                if zeroing || dtz[parent] != 0 {
+                    continue;
+                }
+                if g.wdl[id] == Wdl::Loss && g.wdl[parent] == Wdl::Win {
+                    dtz[parent] = n as i32 + 1;
+                } else if g.wdl[id] == Wdl::Win && g.wdl[parent] == Wdl::Loss {
+                    pending[parent] -= 1;
+                    if pending[parent] != 0 {
+                        continue;
+                    }
+                    dtz[parent] = -(n as i32 + 1);
+                } else {
+                    continue;
+                }
+                if buckets.len() <= n + 1 {
+                    buckets.push(vec![]);
+                }
+                buckets[n + 1].push(parent);
+            }
+        }
+        n += 1;
+    }
+    g.dtz = dtz;
+}
+
+// the sides of a table file from what was generated
+fn table_sides(g: &Generated, kind: Kind) -> Vec<Vec<TableSide>> {
+    let sides = if kind == Kind::Dtz { 1 } else { g.sides };
+    let mut result = vec![];
+    for side in 0..sides {
+        let mut files = vec![];
+        for f in 0..g.files {
+            let start = g.offsets[side][f];
+            let layout = g.layouts[side][f];
+            let values = (start..start + layout.size() as usize)
+                .map(|id| {
+                    if kind == Kind::Wdl {
+                        return (g.wdl[id] as i32 + 2) as u16;
+                    }
+                    return (g.dtz[id].abs() - 1).max(0) as u16;
+                })
+                .collect();
+            let flags = if kind == Kind::Dtz { WIN_PLIES | LOSS_PLIES } else { 0 };
+            files.push(TableSide { layout, order: g.order, flags, values });
+        }
+        result.push(files);
+    }
+    return result;
+}
+
+// writes the tables to the directory, generated by playing out every position, and checks that
+// probing them gives back every result, every other position with the colors swapped
+pub fn test_syzygy(dir: &str, names: &[&str]) -> bool {
+    let names = if names.is_empty() { DEFAULT_TABLES.to_vec() } else { names.to_vec() };
+    if let Err(e) = fs::create_dir_all(dir) {
+        println!("could not create {}: {}", dir, e);
+        return false;
+    }
+    let mut positions = 0;
+    let mut failed = 0;
+    for name in names {
+        let tablebases = Tablebases::open(dir).ok();
+        let g = match generate(name, tablebases.as_ref()) {
+            Ok(g) => g,
+            Err(e) => {
+                println!("could not generate {}: {}", name, e);
+                return false;
+            }
+        };
+        for kind in [Kind::Wdl, Kind::Dtz] {
+            let bytes = write_table(kind, &g.material, &table_sides(&g, kind));
+            let path = Path::new(dir).join(format!("{}.{}", name, kind.extension()));
+            if let Err(e) = fs::write(&path, bytes) {
+                println!("could not write {}: {}", path.display(), e);
+                return false;
+            }
+        }
+        let tablebases = match Tablebases::open(dir) {
+            Ok(t) => t,
+            Err(e) => {
+                println!("could not open {}: {}", dir, e);
+                return false;
+            }
+        };
+        for (id, node) in g.nodes.iter().enumerate() {
+            if !node.valid {
+                continue;
+            }
+            // every other position with the colors swapped
+            let mut board = Board::from_fen(&to_fen(&node.pieces, node.black_to_move)).unwrap();
+            if id % 2 == 1 {
+                board = board.mirror();
+            }
+            let (wdl, dtz) = (g.wdl[id], g.dtz[id]);
+            positions += 1;
+            let probed = (tablebases.probe_wdl(&mut board), tablebases.probe_dtz(&mut board));
+            if probed == (Some(wdl), Some(dtz)) {
+                continue;
+            }
+            if failed < 5 {
+                println!("{} is a {} with dtz {}, the tables say {:?}", board.to_fen(), wdl.to_str(), dtz, probed);
+            }
+            failed += 1;
+        }
+    }
+    if failed != 0 {
+        println!("syzygy test failed in {} of {} positions", failed, positions);
+        return false;
+    }
+    println!("syzygy test completed successfully, {} positions", positions);
+    return true;
+}
+
@@ -0,0 +1,305 @@
+use crate::syzygy::encoding::{Encoded, Layout, Material, TB_PIECES};
+
+pub const WDL_MAGIC: [u8; 4] = [0x71, 0xE8, 0x23, 0x5D];
+pub const DTZ_MAGIC: [u8; 4] = [0xD7, 0x66, 0x0C, 0xA5];
+// the flags byte of a table
+pub const SPLIT: u8 = 1;
+pub const HAS_PAWNS: u8 = 2;
+// the flags of one side to move and file
+pub const STM: u8 = 1;
+pub const MAPPED: u8 = 2;
+pub const WIN_PLIES: u8 = 4;
+pub const LOSS_PLIES: u8 = 8;
+pub const WIDE: u8 = 16;
+pub const SINGLE_VALUE: u8 = 128;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Wdl,
+    Dtz,
+}
+impl Kind {
+    pub fn magic(&self) -> [u8; 4] {
+        return if *self == Kind::Wdl { WDL_MAGIC } else { DTZ_MAGIC };
+    }
+    pub fn extension(&self) -> &'static str {
+        return if *self == Kind::Wdl { "rtbw" } else { "rtbz" };
+    }
+}
+
+// the values of one side to move and file, huffman coded in blocks. symbols either are a value or
+// stand for a pair of symbols, a sparse index points into the blocks every span values
+#[derive(Default)]
+struct PairsData {
+    flags: u8,
+    block_size: u64,
+    span: u64,
+    num_blocks: u64,
+    min_sym_len: u8,
+    // the first symbol of every length
+    lowest_sym: Vec<u16>,
+    btree: usize,
+    block_length: usize,
+    sparse_index: usize,
+    sparse_index_size: u64,
+    block_length_size: u64,
+    data: usize,
+    // the smallest code of every length, left aligned
+    base64: Vec<u64>,
+    // the number of values every symbol stands for, minus one
+    symlen: Vec<u8>,
+    // where the dtz values of every wdl result start in the map
+    map_idx: [usize; 4],
+}
+
+// a whole table file, offsets are into its bytes and reads past the end give zeros
+pub struct TableFile {
+    bytes: Vec<u8>,
+    kind: Kind,
+    pub layouts: [[Layout; 4]; 2],
+    // by side to move and file
+    pairs: Vec<PairsData>,
+    map: usize,
+}
+
+impl TableFile {
+    pub fn from_bytes(bytes: Vec<u8>, material: &Material, kind: Kind) -> Result<TableFile, String> {
+        if bytes.len() < 5 || bytes[0..4] != kind.magic() {
+            return Err(String::from("not a syzygy table"));
+        }
+        let mut file = TableFile { bytes, kind, layouts: [[Layout::default(); 4]; 2], pairs: vec![], map: 0 };
+        let flags = file.byte(4);
+        let split = material.key != material.key2;
+        if (flags & HAS_PAWNS != 0) != material.has_pawns || (flags & SPLIT != 0) != split {
+            return Err(String::from("the table does not match its name"));
+        }
+        let sides = if kind == Kind::Wdl && split { 2 } else { 1 };
+        let files = if material.has_pawns { 4 } else { 1 };
+        let pp = material.has_pawns && material.pawn_count[1] > 0;
+        file.pairs = (0..8).map(|_| PairsData::default()).collect();
+        let mut pos = 5;
+        for f in 0..files {
+            let second = if pp { file.byte(pos + 1) } else { 0xFF };
+            let order = [[file.byte(pos) & 0xF, second & 0xF], [file.byte(pos) >> 4, second >> 4]];
+            pos += 1 + pp as usize;
+            let mut pieces = [[0u8; TB_PIECES]; 2];
+            for (k, byte) in (pos..pos + material.piece_count).map(|p| file.byte(p)).enumerate() {
+                [pieces[0][k], pieces[1][k]] = [byte & 0xF, byte >> 4];
+            }
+            pos += material.piece_count;
+            for side in 0..sides {
+                file.layouts[side][f] = Layout::new(material, pieces[side], order[side], f);
+            }
+        }
+        pos += pos & 1;
+        for f in 0..files {
+            for side in 0..sides {
+                let size = file.layouts[side][f].size();
+                pos = file.set_sizes(side * 4 + f, size, pos)?;
+            }
+        }
+        if kind == Kind::Dtz {
+            file.map = pos;
+            for f in 0..files {
+                if file.pairs[f].flags & MAPPED == 0 {
+                    continue;
+                }
+                if file.pairs[f].flags & WIDE != 0 {
+                    pos += pos & 1;
+                    for i in 0..4 {
+                        file.pairs[f].map_idx[i] = (pos - file.map) / 2 + 1;
+                        pos += 2 * file.u16_le(pos) as usize + 2;
+                    }
+                } else {
+                    for i in 0..4 {
+                        file.pairs[f].map_idx[i] = pos - file.map + 1;
+                        pos += file.byte(pos) as usize + 1;
+                    }
+                }
+            }
+            pos += pos & 1;
+        }
+        for f in 0..files {
+            for side in 0..sides {
+                let d = &mut file.pairs[side * 4 + f];
+                d.sparse_index = pos;
+                pos += d.sparse_index_size as usize * 6;
+            }
+        }
+        for f in 0..files {
+            for side in 0..sides {
+                let d = &mut file.pairs[side * 4 + f];
+                d.block_length = pos;
+                pos += d.block_length_size as usize * 2;
+            }
+        }
+        for f in 0..files {
+            for side in 0..sides {
+                let d = &mut file.pairs[side * 4 + f];
+                pos = (pos + 63) & !63;
+                d.data = pos;
+                pos += (d.num_blocks * d.block_size) as usize;
+            }
+        }
+        if pos > file.bytes.len() {
+            return Err(format!("{} bytes are missing", pos - file.bytes.len()));
+        }
+        return Ok(file);
+    }
+    fn byte(&self, pos: usize) -> u8 {
+        return self.bytes.get(pos).copied().unwrap_or(0);
+    }
+    fn u16_le(&self, pos: usize) -> u16 {
+        return u16::from_le_bytes([self.byte(pos), self.byte(pos + 1)]);
+    }
+    fn u32_le(&self, pos: usize) -> u32 {
+        return u32::from_le_bytes([self.byte(pos), self.byte(pos + 1), self.byte(pos + 2), self.byte(pos + 3)]);
+    }
+    fn u32_be(&self, pos: usize) -> u32 {
+        return u32::from_be_bytes([self.byte(pos), self.byte(pos + 1), self.byte(pos + 2), self.byte(pos + 3)]);
+    }
+    // the left and right halves of a symbol, a leaf has 0xFFF on the right and its value on the left
+    fn pair(&self, btree: usize, sym: usize) -> (usize, usize) {
+        let (a, b, c) = (self.byte(btree + 3 * sym) as usize, self.byte(btree + 3 * sym + 1) as usize, self.byte(btree + 3 * sym + 2) as usize);
+        return (((b & 0xF) << 8) | a, (c << 4) | (b >> 4));
+    }
+    fn set_sizes(&mut self, slot: usize, tb_size: u64, mut pos: usize) -> Result<usize, String> {
+        let mut d = PairsData { flags: self.byte(pos), ..Default::default() };
+        pos += 1;
+        if d.flags & SINGLE_VALUE != 0 {
+            d.span = 1;
+            d.min_sym_len = self.byte(pos);
+            self.pairs[slot] = d;
+            return Ok(pos + 1);
+        }
+        d.block_size = 1 << self.byte(pos).min(32);
+        d.span = 1 << self.byte(pos + 1).min(32);
+        d.sparse_index_size = tb_size.div_ceil(d.span);
+        let padding = self.byte(pos + 2) as u64;
+        d.num_blocks = self.u32_le(pos + 3) as u64;
+        d.block_length_size = d.num_blocks + padding;
+        let max_sym_len = self.byte(pos + 7);
+        d.min_sym_len = self.byte(pos + 8);
+        pos += 9;
+        if d.min_sym_len == 0 || max_sym_len < d.min_sym_len || max_sym_len > 32 {
+            return Err(String::from("broken symbol lengths"));
+        }
+        let lengths = (max_sym_len - d.min_sym_len) as usize + 1;
+        d.lowest_sym = (0..lengths).map(|i| self.u16_le(pos + 2 * i)).collect();
+        let lowest = |i: usize| d.lowest_sym[i] as u64;
+        let mut base64 = vec![0u64; lengths];
+        for i in (0..lengths - 1).rev() {
+            base64[i] = base64[i + 1].wrapping_add(lowest(i)).wrapping_sub(lowest(i + 1)) / 2;
+        }
+        d.base64 = base64;
+        for (i, base) in d.base64.iter_mut().enumerate() {
+            *base = base.checked_shl(64 - i as u32 - d.min_sym_len as u32).unwrap_or(0);
+        }
+        pos += lengths * 2;
+        let symbols = self.u16_le(pos) as usize;
+        pos += 2;
+        d.btree = pos;
+        d.symlen = vec![0; symbols];
+        let mut visited = vec![false; symbols];
+        for sym in 0..symbols {
+            self.set_symlen(&mut d, &mut visited, sym);
+        }
+        self.pairs[slot] = d;
+        return Ok(pos + symbols * 3 + (symbols & 1));
+    }
+    fn set_symlen(&self, d: &mut PairsData, visited: &mut [bool], sym: usize) {
+        if visited[sym] {
+            return;
+        }
+        visited[sym] = true;
+        let (left, right) = self.pair(d.btree, sym);
+        if right == 0xFFF || left >= d.symlen.len() || right >= d.symlen.len() {
+            d.symlen[sym] = 0;
+            return;
+        }
+        self.set_symlen(d, visited, left);
+        self.set_symlen(d, visited, right);
+        d.symlen[sym] = d.symlen[left].saturating_add(d.symlen[right]).saturating_add(1);
+    }
+    fn decompress_pairs(&self, d: &PairsData, idx: u64) -> usize {
+        if d.flags & SINGLE_VALUE != 0 {
+            return d.min_sym_len as usize;
+        }
+        // the sparse entry points to the value in the middle of its span, walk to idx from there
+        let k = (idx / d.span) as usize;
+        let mut block = self.u32_le(d.sparse_index + 6 * k) as i64;
+        let mut offset = self.u16_le(d.sparse_index + 6 * k + 4) as i64;
+        offset += (idx % d.span) as i64 - (d.span / 2) as i64;
+        let block_length = |block: i64| self.u16_le(d.block_length + 2 * block as usize) as i64;
+        while offset < 0 && block > 0 {
+            block -= 1;
+            offset += block_length(block) + 1;
+        }
+        while block < d.block_length_size as i64 && offset > block_length(block) {
+            offset -= block_length(block) + 1;
+            block += 1;
+        }
+        let mut ptr = d.data + (block as u64 * d.block_size) as usize;
+        let mut buf64 = ((self.u32_be(ptr) as u64) << 32) | self.u32_be(ptr + 4) as u64;
+        ptr += 8;
+        let mut buf64_size = 64;
+        let mut sym;
+        loop {
+            let mut len = 0;
+            while len + 1 < d.base64.len() && buf64 < d.base64[len] {
+                len += 1;
+            }
+            sym = (buf64.wrapping_sub(d.base64[len]) >> (64 - len - d.min_sym_len as usize)) as usize;
+            sym += d.lowest_sym[len] as usize;
+            let symlen = d.symlen.get(sym).copied().unwrap_or(0) as i64;
+            if offset < symlen + 1 {
+                break;
+            }
+            offset -= symlen + 1;
+            len += d.min_sym_len as usize;
+            buf64 = buf64.checked_shl(len as u32).unwrap_or(0);
+            buf64_size -= len as i64;
+            if buf64_size <= 32 {
+                buf64_size += 32;
+                buf64 |= (self.u32_be(ptr) as u64) << (64 - buf64_size);
+                ptr += 4;
+            }
+        }
+        // expand the pairs down to the value
+        while d.symlen.get(sym).is_some_and(|&len| len != 0) {
+            let (left, right) = self.pair(d.btree, sym);
+            let left_len = d.symlen.get(left).copied().unwrap_or(0) as i64;
+            if offset < left_len + 1 {
+                sym = left;
+            } else {
+                offset -= left_len + 1;
+                sym = right;
+            }
+        }
+        return self.pair(d.btree, sym).0;
+    }
+    // the wdl value from -2 to 2 or the dtz value as stored, wdl tells which values a dtz table maps
+    pub fn probe(&self, encoded: &Encoded, wdl: i32) -> i32 {
+        let slot = if self.kind == Kind::Dtz { encoded.file } else { encoded.stm * 4 + encoded.file };
+        let d = &self.pairs[slot];
+        let value = self.decompress_pairs(d, encoded.index) as i32;
+        if self.kind == Kind::Wdl {
+            return value - 2;
+        }
+        let mut value = value;
+        if d.flags & MAPPED != 0 {
+            let i = d.map_idx[[1, 3, 0, 2, 0][(wdl + 2) as usize]] + value as usize;
+            value = if d.flags & WIDE != 0 { self.u16_le(self.map + 2 * i) as i32 } else { self.byte(self.map + i) as i32 };
+        }
+        // dtz tables store moves unless they say plies, cursed and blessed results always are plies
+        let plies = if wdl > 0 { WIN_PLIES } else { LOSS_PLIES };
+        if d.flags & plies == 0 || wdl.abs() == 1 {
+            value *= 2;
+        }
+        return value + 1;
+    }
+    // whether a dtz table stores this side to move
+    pub fn has_side(&self, encoded: &Encoded) -> bool {
+        return self.pairs[encoded.file].flags & STM == encoded.stm as u8;
+    }
+}
@@ -0,0 +1,187 @@
+use crate::syzygy::{
+    encoding::{Layout, Material},
+    table::{HAS_PAWNS, Kind, SINGLE_VALUE, SPLIT},
+};
+
+// small blocks and spans so that even small tables walk through several of them
+const BLOCK_SIZE_LOG: u8 = 4;
+const SPAN_LOG: u8 = 6;
+const MAX_CODE_LEN: usize = 32;
+
+// the values of one side to move and file of a table to write, with the flags they go with
+pub struct TableSide {
+    pub layout: Layout,
+    pub order: [u8; 2],
+    pub flags: u8,
+    pub values: Vec<u16>,
+}
+
+// bits are written from the most significant one on, like the tables are read
+struct BitWriter {
+    bytes: Vec<u8>,
+    bits: usize,
+}
+impl BitWriter {
+    fn push(&mut self, code: u64, len: usize) {
+        for i in (0..len).rev() {
+            if self.bits.is_multiple_of(8) {
+                self.bytes.push(0);
+            }
+            if code >> i & 1 != 0 {
+                *self.bytes.last_mut().unwrap() |= 0x80 >> (self.bits % 8);
+            }
+            self.bits += 1;
+        }
+    }
+}
+
+// huffman code lengths by symbol, all the same length when they get too long
+fn code_lengths(freqs: &[u64]) -> Vec<usize> {
+    let mut lengths = vec![0; freqs.len()];
+    // (weight, symbols under the node)
+    let mut nodes: Vec<(u64, Vec<usize>)> = freqs.iter().enumerate().map(|(i, &f)| (f, vec![i])).collect();
+    while nodes.len() > 1 {
+        nodes.sort_by_key(|n| std::cmp::Reverse(n.0));
+        let (a, b) = (nodes.pop().unwrap(), nodes.pop().unwrap());
+        for &sym in a.1.iter().chain(&b.1) {
+            lengths[sym] += 1;
+        }
+        nodes.push((a.0 + b.0, [a.1, b.1].concat()));
+    }
+    if lengths.iter().any(|&len| len > MAX_CODE_LEN) {
+        let len = (usize::BITS - (freqs.len() - 1).leading_zeros()) as usize;
+        return vec![len; freqs.len()];
+    }
+    return lengths;
+}
+
+// the pairs data of one side to move and file: its part of the header, its sparse index, its block
+// lengths and its blocks. there are no pairs, every symbol is a value
+fn compress(side: &TableSide) -> [Vec<u8>; 4] {
+    let mut symbols = side.values.clone();
+    symbols.sort();
+    symbols.dedup();
+    if symbols.len() <= 1 {
+        let value = symbols.first().copied().unwrap_or(0);
+        return [vec![side.flags | SINGLE_VALUE, value as u8], vec![], vec![], vec![]];
+    }
+    let symbol_of = |value: u16| symbols.binary_search(&value).unwrap();
+    let mut freqs = vec![0; symbols.len()];
+    for &value in &side.values {
+        freqs[symbol_of(value)] += 1;
+    }
+    let lengths = code_lengths(&freqs);
+    // the longest codes get the lowest symbol numbers and the lowest codes
+    let mut by_length: Vec<usize> = (0..symbols.len()).collect();
+    by_length.sort_by_key(|&s| std::cmp::Reverse(lengths[s]));
+    let mut number = vec![0; symbols.len()];
+    for (i, &s) in by_length.iter().enumerate() {
+        number[s] = i;
+    }
+    let (min_len, max_len) = (*lengths.iter().min().unwrap(), *lengths.iter().max().unwrap());
+    let count = |len: usize| lengths.iter().filter(|&&l| l == len).count();
+    // lowest symbol number and first code of every length from the shortest on
+    let lowest: Vec<usize> = (min_len..=max_len).map(|len| lengths.iter().filter(|&&l| l > len).count()).collect();
+    let mut base = vec![0u64; lowest.len()];
+    for i in (0..base.len() - 1).rev() {
+        base[i] = (base[i + 1] + count(min_len + i + 1) as u64) / 2;
+    }
+    let code = |value: u16| {
+        let s = symbol_of(value);
+        let i = lengths[s] - min_len;
+        return (base[i] + (number[s] - lowest[i]) as u64, lengths[s]);
+    };
+    // fill the blocks, their lengths are one less than the values they hold
+    let block_bits = 8 << BLOCK_SIZE_LOG;
+    let mut blocks: Vec<BitWriter> = vec![];
+    let mut block_values: Vec<usize> = vec![];
+    for &value in &side.values {
+        let (code, len) = code(value);
+        if blocks.last().is_none_or(|b| b.bits + len > block_bits) {
+            blocks.push(BitWriter { bytes: vec![], bits: 0 });
+            block_values.push(0);
+        }
+        blocks.last_mut().unwrap().push(code, len);
+        *block_values.last_mut().unwrap() += 1;
+    }
+    let mut data = vec![];
+    let mut block_lengths = vec![];
+    for (block, &values) in blocks.iter().zip(&block_values) {
+        data.extend_from_slice(&block.bytes);
+        data.resize(data.len().next_multiple_of(1 << BLOCK_SIZE_LOG), 0);
+        block_lengths.extend_from_slice(&((values - 1) as u16).to_le_bytes());
+    }
+    // every span points at its middle value, by block and offset in it
+    let span = 1usize << SPAN_LOG;
+    let starts: Vec<usize> = block_values.iter().scan(0, |start, &n| {
+        let s = *start;
+        *start += n;
+        return Some(s);
+    }).collect();
+    let mut sparse = vec![];
+    for k in 0..side.values.len().div_ceil(span) {
+        let middle = k * span + span / 2;
+        let block = starts.partition_point(|&s| s <= middle) - 1;
+        sparse.extend_from_slice(&(block as u32).to_le_bytes());
+        sparse.extend_from_slice(&((middle - starts[block]) as u16).to_le_bytes());
+    }
+    let mut header = vec![side.flags, BLOCK_SIZE_LOG, SPAN_LOG, 0];
+    header.extend_from_slice(&(blocks.len() as u32).to_le_bytes());
+    header.extend_from_slice(&[max_len as u8, min_len as u8]);
+    for &l in &lowest {
+        header.extend_from_slice(&(l as u16).to_le_bytes());
+    }
+    header.extend_from_slice(&(symbols.len() as u16).to_le_bytes());
+    for &s in &by_length {
+        let value = symbols[s] as u32;
+        header.extend_from_slice(&[value as u8, (value >> 8) as u8 | 0xF0, 0xFF]);
+    }
+    if symbols.len() % 2 == 1 {
+        header.push(0);
+    }
+    return [header, sparse, block_lengths, data];
+}
+
+// a table file with the sides by side to move and file, one side to move for dtz tables and
+// symmetric wdl ones
+pub fn write_table(kind: Kind, material: &Material, sides: &[Vec<TableSide>]) -> Vec<u8> {
+    let files = sides[0].len();
+    let pp = material.has_pawns && material.pawn_count[1] > 0;
+    let mut bytes = kind.magic().to_vec();
+    bytes.push(((material.key != material.key2) as u8 * SPLIT) | (material.has_pawns as u8 * HAS_PAWNS));
+    // symmetric tables repeat their only side
+    for (white, black) in sides[0].iter().zip(sides.last().unwrap()) {
+        bytes.push(white.order[0] | black.order[0] << 4);
+        if pp {
+            bytes.push(white.order[1] | black.order[1] << 4);
+        }
+        for k in 0..material.piece_count {
+            bytes.push(white.layout.pieces[k] | black.layout.pieces[k] << 4);
+        }
+    }
+    if bytes.len() % 2 == 1 {
+        bytes.push(0);
+    }
+    let mut parts = vec![];
+    for f in 0..files {
+        for side in sides {
+            parts.push(compress(&side[f]));
+        }
+    }
+    for part in &parts {
+        bytes.extend_from_slice(&part[0]);
+    }
+    if kind == Kind::Dtz && bytes.len() % 2 == 1 {
+        bytes.push(0);
+    }
+    for i in 1..3 {
+        for part in &parts {
+            bytes.extend_from_slice(&part[i]);
+        }
+    }
+    for part in &parts {
+        bytes.resize(bytes.len().next_multiple_of(64), 0);
+        bytes.extend_from_slice(&part[3]);
+    }
+    return bytes;
+}
@@ -1,8 +1,9 @@
 use std::cell::UnsafeCell;
 
-use crate::{core::{Board, r#move::Move}, search::defs::{Depth, Score}};
+use crate::{core::{Board, r#move::Move}, evaluation::score::PackedScore, search::defs::{Depth, Score}};
 
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[allow(clippy::upper_case_acronyms)]
 pub enum Flag{
     EXACT = 0, 
     LOWER = 1, // fail-high
@@ -33,20 +34,12 @@ impl Default for TTEntry {
 }
 
 #[repr(C)]
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Default)]
 pub struct PawnEntry {
     pub key: u16, // part of the hash
     pub w_filesets: u8,
     pub b_filesets: u8,
-    pub w_pst: (Score, Score),
-    pub b_pst: (Score, Score),
-    pub eval: Score,
-}
-
-impl Default for PawnEntry {
-    fn default() -> Self {
-        PawnEntry { key: 0, w_filesets: 0, b_filesets: 0, w_pst: (0, 0), b_pst: (0, 0), eval: 0 }
-    }
+    pub eval: [PackedScore; 2], // white and black, each from its own point of view
 }
 
 pub struct TranspositionTable {
@@ -144,9 +137,12 @@ impl TranspositionTable{
     pub fn calculate_pv(&self, mut board: Board, pv: &mut Vec<Move>){
         let mut len = 0;
         while len < 10{
-            let res = self.lookup_position(board.hash);
-            if res.is_some(){
-                let mv = res.unwrap().best_move;
+            if let Some(entry) = self.lookup_position(board.hash){
+                let mv = entry.best_move;
+                // a hash collision can leave a move that does not fit this position
+                if !board.is_pseudo_legal(mv) || !board.is_legal(mv) {
+                    break;
+                }
                 pv.push(mv);
                 board.make_move(mv);
                 len += 1;
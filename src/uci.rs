@@ -1,6 +1,6 @@
 use std::sync::Arc;
 
-use crate::{core::{Board, Color, r#move::MoveUtil, perft::start_perft, perft_test:: test_perft}, search::{defs::{SearchInfo, SearchMode, SearchResult}, iter_deep::start_iterative_deepening_search}, transposition_table::TranspositionTable, uci_options::UciOptions};
+use crate::{book::{Book, polyglot_key}, core::{Board, Color, board::StateInfo, bitboard::BitBoardUtil, fen::START_POSITION_FEN, r#move::MoveUtil, perft::{compare_perft, compare_slider_backends, start_perft, start_perft_parallel}, perft_diff::{DiffReference, diff_divide}, perft_suite::start_perft_suite, perft_test::{test_fen, test_move_parsing, test_generators, test_legality, test_perft, test_unmake}, san_test::test_san, square::SquareUtil}, search::{defs::{Depth, SearchInfo, SearchMode, SearchResult}, iter_deep::start_iterative_deepening_search}, evaluation::{nnue, kpk_test::test_kpk, nnue_test::test_nnue, symmetry_test::test_symmetry, trace::EvalTrace, tune::start_tune}, syzygy::{probe as syzygy, syzygy_test::test_syzygy}, transposition_table::TranspositionTable, uci_options::UciOptions};
 const ENGINENAME: &str = "Vhagar";
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 const AUTHOR: &str = "Reza Ghazavi";
@@ -14,18 +14,22 @@ fn allocate_time(my_time: u128, my_inc: u128, moves_to_go: usize) -> (u128, u128
 }
 pub struct Uci {
     board: Board,
+    history: Vec<StateInfo>,
     position_cmd: String,
     tt: Arc<TranspositionTable>,
     options: UciOptions,
+    book: Option<Book>,
 }
 
 impl Uci {
     pub fn new() -> Self {
         Self {
             board: Board::default(),
+            history: Vec::new(),
             position_cmd: String::from("position startpos moves"),
             tt: Arc::new(TranspositionTable::new(DEAFAULT_TT_SIZE_MB)),
-            options: UciOptions::new()
+            options: UciOptions::new(),
+            book: None,
         }
     }
 
@@ -41,8 +45,11 @@ impl Uci {
             cmd if cmd == "isready" => self.readyok(),
             cmd if cmd == "quit" || cmd == "exit" => self.quit(),
             cmd if cmd.starts_with("position") => self.parse_position(&cmd),
-            cmd if cmd == "board" => self.print_board(),
+            cmd if cmd == "board" || cmd == "d" => self.print_board(),
+            cmd if cmd == "eval" => EvalTrace::new(&self.board, &self.tt).print(),
             cmd if cmd.starts_with("perft") => self.parse_perft(&cmd),
+            cmd if cmd.starts_with("test") => self.parse_test(&cmd),
+            cmd if cmd.starts_with("tune") => self.parse_tune(&cmd),
             cmd if cmd.starts_with("go perft") => self.parse_perft(&cmd[3..]),
             cmd if cmd.starts_with("go") => self.parse_go(&cmd),
             cmd if cmd.starts_with("setoption") => self.parse_setoption(&cmd),
@@ -59,14 +66,128 @@ impl Uci {
         let parts: Vec<String> = cmd.split_whitespace().map(|s| s.to_string()).collect();
         if parts.len() > 1 {
             if parts[1] == "test" {
-                test_perft();
+                test_perft(parts.get(2).and_then(|d| d.parse::<usize>().ok()).unwrap_or(usize::MAX));
+                return;
+            }
+            // perft diff <depth> [reference file | engine <path>], without a reference it is read
+            // from stdin
+            if parts[1] == "diff" {
+                let reference = match (parts.get(3).map(|p| p.as_str()), parts.get(4)) {
+                    (None, _) => Some(DiffReference::Stdin),
+                    (Some("engine"), Some(path)) => Some(DiffReference::Engine(path.clone())),
+                    (Some("engine"), None) => None,
+                    (Some(path), _) => Some(DiffReference::File(path.to_string())),
+                };
+                match (parts.get(2).and_then(|d| d.parse::<usize>().ok()), reference) {
+                    (Some(depth), Some(reference)) if depth > 0 => {
+                        diff_divide(&self.board, depth, &reference);
+                    }
+                    _ => println!("info string usage: perft diff <depth> [reference file | engine <path>]"),
+                }
+                return;
+            }
+            if parts[1] == "suite" {
+                let Some(path) = parts.get(2) else {
+                    println!("info string usage: perft suite <file.epd> [maxdepth]");
+                    return;
+                };
+                start_perft_suite(path, parts.get(3).and_then(|d| d.parse::<usize>().ok()).unwrap_or(usize::MAX));
+                return;
+            }
+            if parts[1] == "compare" {
+                let depth = parts.get(2).and_then(|d| d.parse::<usize>().ok()).unwrap_or(5);
+                compare_perft(&self.board, depth);
+                return;
+            }
+            if parts[1] == "backends" {
+                let depth = parts.get(2).and_then(|d| d.parse::<usize>().ok()).unwrap_or(5);
+                compare_slider_backends(&self.board, depth);
                 return;
             }
             depth = parts[1].parse::<usize>().unwrap_or(1);
         }
+        // perft N [threads T] [hash MB]
+        let option = |name: &str| {
+            parts.iter().position(|p| p == name)
+                .and_then(|i| parts.get(i + 1))
+                .and_then(|v| v.parse::<usize>().ok())
+        };
+        let threads = option("threads").unwrap_or(1);
+        let hash = option("hash").unwrap_or(0);
+        if threads > 1 || hash > 0 {
+            start_perft_parallel(&self.board, depth, threads, hash);
+            return;
+        }
         start_perft(&self.board, depth);
     }
 
+    fn parse_test(&self, cmd: &str) {
+        let parts: Vec<&str> = cmd.split_whitespace().collect();
+        match parts.get(1) {
+            Some(&"fen") => {
+                test_fen();
+            }
+            Some(&"moves") => {
+                test_move_parsing();
+            }
+            Some(&"san") => {
+                test_san();
+            }
+            Some(&"movegen") => {
+                test_generators();
+            }
+            Some(&"legality") => {
+                test_legality();
+            }
+            Some(&"unmake") => {
+                test_unmake();
+            }
+            Some(&"nnue") => {
+                test_nnue();
+            }
+            Some(&"kpk") => {
+                test_kpk();
+            }
+            // test symmetry [file], without a file the built-in positions are walked two plies deep
+            Some(&"symmetry") => {
+                test_symmetry(parts.get(2).copied());
+            }
+            // test syzygy <dir> [tables], the tables are written to the directory
+            Some(&"syzygy") => match parts.get(2) {
+                Some(dir) => {
+                    test_syzygy(dir, &parts[3..]);
+                }
+                None => println!("info string usage: test syzygy <dir> [tables]"),
+            },
+            Some(&"perft") => {
+                test_perft(parts.get(2).and_then(|d| d.parse::<usize>().ok()).unwrap_or(usize::MAX));
+            }
+            _ => println!("info string unknown test, expected one of: fen moves san movegen legality unmake nnue kpk symmetry syzygy perft"),
+        }
+    }
+
+    // tune <file> [epochs N] [threads T] [output FILE], a .json output is written as json and
+    // anything else in the layout of evaluation/defs.rs
+    fn parse_tune(&self, cmd: &str) {
+        let parts: Vec<&str> = cmd.split_whitespace().collect();
+        let Some(path) = parts.get(1) else {
+            println!("info string usage: tune <file> [epochs N] [threads T] [output FILE]");
+            return;
+        };
+        let mut epochs = 500;
+        let mut threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+        let mut output = "tuned_params.rs";
+        for pair in parts[2..].chunks(2) {
+            match pair {
+                ["epochs", n] => epochs = n.parse().unwrap_or(epochs),
+                ["threads", n] => threads = n.parse().unwrap_or(threads),
+                ["output", file] => output = file,
+                _ => {}
+            }
+        }
+        start_tune(path, epochs, threads, output);
+    }
+
     fn parse_position(&mut self, cmd: &str) {
         self.position_cmd = String::from(cmd);
         enum Tokens {
@@ -84,10 +205,7 @@ impl Uci {
         for p in parts {
             match p {
                 t if t == "position" => (), // Skip. We know we're parsing "position".
-                t if t == "startpos" => {
-                    skip_fen = true;
-                    self.board = Board::default();
-                } // "fen" is now invalidated.
+                t if t == "startpos" => skip_fen = true, // "fen" is now invalidated.
                 t if t == "fen" && !skip_fen => token = Tokens::Fen,
                 t if t == "moves" => token = Tokens::Moves,
                 _ => match token {
@@ -102,12 +220,28 @@ impl Uci {
         }
         // No FEN part in the command. Use the start position.
         if fen.is_empty() {
-            fen = String::from("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+            fen = String::from(START_POSITION_FEN);
         }
-        self.board = Board::from_fen(&fen);
+        let mut board = match Board::from_fen(&fen) {
+            Ok(board) => board,
+            Err(e) => {
+                println!("info string invalid fen \"{}\": {}", fen.trim_end(), e);
+                return;
+            }
+        };
+        board.chess960 |= self.options.chess960();
+        let mut history = Vec::with_capacity(moves.len());
         for m in moves {
-            self.board.make_move_from_str(&m);
+            match board.make_move_from_str(&m) {
+                Ok(state) => history.push(state),
+                Err(e) => {
+                    println!("info string invalid move list: {}", e);
+                    return;
+                }
+            }
         }
+        self.board = board;
+        self.history = history;
     }
 
     fn parse_go(&mut self, cmd: &str) {
@@ -122,6 +256,20 @@ impl Uci {
             MovesToGo,
         }
 
+        // a book move is played without searching, unless the search has to run until it is
+        // stopped
+        let until_stopped = cmd.split_whitespace().any(|t| t == "infinite" || t == "ponder");
+        if let Some(book) = &mut self.book
+            && !until_stopped
+            && self.options.own_book()
+            && self.board.fullmove_number as usize <= self.options.book_depth()
+            && let Some(m) = book.probe(&self.board, self.options.book_best_move())
+        {
+            println!("info string book move {}", m.to_uci(self.board.chess960));
+            println!("bestmove {}", m.to_uci(self.board.chess960));
+            return;
+        }
+
         let parts: Vec<String> = cmd.split_whitespace().map(|s| s.to_string()).collect();
         let mut token = Tokens::Nothing;
         let mut info = SearchInfo::default();
@@ -154,11 +302,7 @@ impl Uci {
                     Tokens::WInc => info.game_time.winc = p.parse::<u128>().unwrap_or(0),
                     Tokens::BInc => info.game_time.binc = p.parse::<u128>().unwrap_or(0),
                     Tokens::MovesToGo => {
-                        info.game_time.moves_to_go = if let Ok(x) = p.parse::<usize>() {
-                            Some(x)
-                        } else {
-                            None
-                        }
+                        info.game_time.moves_to_go = p.parse::<usize>().ok()
                     }
                 }, // end match token
             } // end match p
@@ -179,28 +323,72 @@ impl Uci {
 
         let best_move = start_iterative_deepening_search(
             &self.board,
+            &self.history,
             Arc::clone(&self.tt),
             &mut info,
             self.options.thread_cout(),
         );
-        println!("bestmove {}", best_move.to_str());
+        println!("bestmove {}", best_move.to_uci(self.board.chess960));
     } // end parse_go()
 
     fn parse_setoption(&mut self, cmd: &str){
-        let args = cmd.split(" ").map(|a| String::from(a)).collect::<Vec<String>>();
-        let mut i = 0;
-        let mut name = String::new();
-        let mut value = String::new();
-        while i < args.len(){
-            if i > 1 && args[i - 1] == "name"{
-                name = args[i].clone();
+        // names like "Use NNUE" and file paths may contain spaces
+        let args = cmd.split_whitespace().collect::<Vec<&str>>();
+        let name_start = args.iter().position(|a| *a == "name").map_or(args.len(), |i| i + 1);
+        let value_start = args.iter().position(|a| *a == "value").unwrap_or(args.len());
+        let name = args[name_start.min(value_start)..value_start].join(" ");
+        let value = args.get(value_start + 1..).map_or(String::new(), |v| v.join(" "));
+        self.options.set(name.clone(), value);
+        match name.as_str() {
+            "EvalFile" => self.load_network(),
+            "BookFile" => self.load_book(),
+            "SyzygyPath" => self.load_tablebases(),
+            "SyzygyProbeDepth" => syzygy::set_probe_depth(self.options.syzygy_probe_depth().clamp(1, 100) as Depth),
+            "Syzygy50MoveRule" => syzygy::set_rule_50(self.options.syzygy_50_move_rule()),
+            "Use NNUE" => {
+                nnue::set_enabled(self.options.use_nnue());
+                if self.options.use_nnue() && !nnue::is_loaded() {
+                    println!("info string no network loaded, set EvalFile first, using the classical eval");
+                }
+                self.board.refresh_accumulator();
             }
-            if i > 1 && args[i - 1] == "value"{
-                value = args[i].clone();
+            _ => {}
+        }
+    }
+    fn load_network(&mut self) {
+        let path = self.options.eval_file();
+        if path.is_empty() {
+            return;
+        }
+        match nnue::load_network(&path) {
+            Ok(()) => println!("info string loaded network {}", path),
+            Err(e) => println!("info string could not load network {}: {}", path, e),
+        }
+        self.board.refresh_accumulator();
+    }
+
+    fn load_book(&mut self) {
+        let path = self.options.book_file();
+        self.book = None;
+        if path.is_empty() {
+            return;
+        }
+        match Book::open(&path) {
+            Ok(book) => {
+                println!("info string loaded book {} with {} entries", path, book.len());
+                self.book = Some(book);
             }
-            i += 1;
+            Err(e) => println!("info string could not load book {}: {}", path, e),
+        }
+    }
+
+    fn load_tablebases(&mut self) {
+        let path = self.options.syzygy_path();
+        match syzygy::load_tablebases(&path) {
+            Ok(0) => {}
+            Ok(count) => println!("info string found {} tablebases in {}", count, path),
+            Err(e) => println!("info string could not load tablebases {}: {}", path, e),
         }
-        self.options.set(name, value);
     }
 
     fn id(&self) {
@@ -220,6 +408,24 @@ impl Uci {
     }
     fn print_board(&self) {
         self.board.print();
+        println!();
+        println!("Fen: {}", self.board.to_fen());
+        println!("Key: {:016X}", self.board.hash);
+        println!("Polyglot key: {:016X}", polyglot_key(&self.board));
+        let mut checkers = self.board.checkers;
+        let mut squares = Vec::new();
+        while checkers != 0 {
+            squares.push(checkers.pop_lsb().to_str());
+        }
+        println!("Checkers: {}", squares.join(" "));
+        if let Some(tablebases) = syzygy::active().filter(|t| t.covers(&self.board)) {
+            let mut board = self.board;
+            let wdl = tablebases.probe_wdl(&mut board);
+            let dtz = tablebases.probe_dtz(&mut board);
+            if let (Some(wdl), Some(dtz)) = (wdl, dtz) {
+                println!("Tablebases: {} dtz {}", wdl.to_str(), dtz);
+            }
+        }
     }
     fn new_game(&mut self) {
         *self = Uci::new();
@@ -239,16 +445,17 @@ impl Uci {
 }
 
 impl Uci{
-    pub fn send_info(result: &SearchResult){
-        println!("info depth {} score cp {} nodes {} q_nodes {} nps {} time {} bestmove {} pv {}", 
+    pub fn send_info(result: &SearchResult, chess960: bool){
+        println!("info depth {} score cp {} nodes {} q_nodes {} nps {} tbhits {} time {} bestmove {} pv {}", 
             result.depth,
             result.eval,
             result.nodes,
             result.q_nodes,
             (result.nodes + result.q_nodes) as u128 * 1000 / (u128::max(1, result.timer_elapsed())),
+            result.tb_hits,
             result.timer_elapsed(),
-            result.best_move.to_str(),
-            result.pv.iter().map(|a| a.to_str()).collect::<Vec<String>>().join(" ")
+            result.best_move.to_uci(chess960),
+            result.pv.iter().map(|a| a.to_uci(chess960)).collect::<Vec<String>>().join(" ")
         );
     }
 }
\ No newline at end of file
@@ -10,19 +10,105 @@ impl UciSpinOption{
     pub fn thread_option() -> UciSpinOption{
         UciSpinOption { name: String::from("Threads") ,value: 4, default: 4, min: 1, max: 128 }
     }
+    // the last full move number the book is used for
+    pub fn book_depth_option() -> UciSpinOption{
+        UciSpinOption { name: String::from("BookDepth"), value: 20, default: 20, min: 1, max: 255 }
+    }
+    // the depth from which positions with as many pieces as the largest tables are probed
+    pub fn syzygy_probe_depth_option() -> UciSpinOption{
+        UciSpinOption { name: String::from("SyzygyProbeDepth"), value: 1, default: 1, min: 1, max: 100 }
+    }
+}
+#[derive(Clone)]
+pub struct UciCheckOption{
+    name: String,
+    value: bool,
+    default: bool,
+}
+impl UciCheckOption{
+    pub fn chess960_option() -> UciCheckOption{
+        UciCheckOption { name: String::from("UCI_Chess960"), value: false, default: false }
+    }
+}
+impl UciCheckOption{
+    pub fn use_nnue_option() -> UciCheckOption{
+        UciCheckOption { name: String::from("Use NNUE"), value: false, default: false }
+    }
+}
+impl UciCheckOption{
+    pub fn own_book_option() -> UciCheckOption{
+        UciCheckOption { name: String::from("OwnBook"), value: false, default: false }
+    }
+    // always the heaviest book move instead of one drawn by weight
+    pub fn book_best_move_option() -> UciCheckOption{
+        UciCheckOption { name: String::from("BookBestMove"), value: false, default: false }
+    }
+}
+impl UciCheckOption{
+    // without it wins the 50 move rule turns into draws count as wins
+    pub fn syzygy_50_move_rule_option() -> UciCheckOption{
+        UciCheckOption { name: String::from("Syzygy50MoveRule"), value: true, default: true }
+    }
+}
+#[derive(Clone)]
+pub struct UciStringOption{
+    name: String,
+    value: String,
+    default: String,
+}
+impl UciStringOption{
+    pub fn eval_file_option() -> UciStringOption{
+        UciStringOption { name: String::from("EvalFile"), value: String::new(), default: String::new() }
+    }
+    pub fn book_file_option() -> UciStringOption{
+        UciStringOption { name: String::from("BookFile"), value: String::new(), default: String::new() }
+    }
+    // directories separated like the PATH variable
+    pub fn syzygy_path_option() -> UciStringOption{
+        UciStringOption { name: String::from("SyzygyPath"), value: String::new(), default: String::new() }
+    }
 }
 pub struct UciOptions{
-    spin_options: Vec<UciSpinOption>
+    spin_options: Vec<UciSpinOption>,
+    check_options: Vec<UciCheckOption>,
+    string_options: Vec<UciStringOption>,
 }
 impl UciOptions {
     pub fn new() -> UciOptions{
-        UciOptions { spin_options: vec![UciSpinOption::thread_option()] }
+        UciOptions {
+            spin_options: vec![
+                UciSpinOption::thread_option(),
+                UciSpinOption::book_depth_option(),
+                UciSpinOption::syzygy_probe_depth_option(),
+            ],
+            check_options: vec![
+                UciCheckOption::chess960_option(),
+                UciCheckOption::use_nnue_option(),
+                UciCheckOption::own_book_option(),
+                UciCheckOption::book_best_move_option(),
+                UciCheckOption::syzygy_50_move_rule_option(),
+            ],
+            string_options: vec![
+                UciStringOption::eval_file_option(),
+                UciStringOption::book_file_option(),
+                UciStringOption::syzygy_path_option(),
+            ],
+        }
     }
     pub fn print(&self){
         for i in 0..self.spin_options.len(){
             let spin = self.spin_options[i].clone();
             println!("option name {} type spin default {} min {} max {}", spin.name, spin.default, spin.min, spin.max);
         }
+        for i in 0..self.check_options.len(){
+            let check = self.check_options[i].clone();
+            println!("option name {} type check default {}", check.name, check.default);
+        }
+        for i in 0..self.string_options.len(){
+            let string = self.string_options[i].clone();
+            let default = if string.default.is_empty() { "<empty>" } else { &string.default };
+            println!("option name {} type string default {}", string.name, default);
+        }
     }
     pub fn set(&mut self, name: String, value: String){
         for i in 0..self.spin_options.len(){
@@ -32,6 +118,19 @@ impl UciOptions {
                 return;
             }
         }
+        for i in 0..self.check_options.len(){
+            if self.check_options[i].name == name{
+                let v: bool = value.parse().unwrap_or(self.check_options[i].default);
+                self.check_options[i].value = v;
+                return;
+            }
+        }
+        for i in 0..self.string_options.len(){
+            if self.string_options[i].name == name{
+                self.string_options[i].value = if value == "<empty>" { String::new() } else { value };
+                return;
+            }
+        }
     }
 }
 
@@ -39,4 +138,34 @@ impl UciOptions{
     pub fn thread_cout(&self) -> usize{
         return self.spin_options.iter().find(|a| a.name == "Threads").unwrap().value
     }
-}
\ No newline at end of file
+    pub fn chess960(&self) -> bool{
+        return self.check_options.iter().find(|a| a.name == "UCI_Chess960").unwrap().value
+    }
+    pub fn use_nnue(&self) -> bool{
+        return self.check_options.iter().find(|a| a.name == "Use NNUE").unwrap().value
+    }
+    pub fn eval_file(&self) -> String{
+        return self.string_options.iter().find(|a| a.name == "EvalFile").unwrap().value.clone()
+    }
+    pub fn own_book(&self) -> bool{
+        return self.check_options.iter().find(|a| a.name == "OwnBook").unwrap().value
+    }
+    pub fn book_best_move(&self) -> bool{
+        return self.check_options.iter().find(|a| a.name == "BookBestMove").unwrap().value
+    }
+    pub fn book_depth(&self) -> usize{
+        return self.spin_options.iter().find(|a| a.name == "BookDepth").unwrap().value
+    }
+    pub fn book_file(&self) -> String{
+        return self.string_options.iter().find(|a| a.name == "BookFile").unwrap().value.clone()
+    }
+    pub fn syzygy_path(&self) -> String{
+        return self.string_options.iter().find(|a| a.name == "SyzygyPath").unwrap().value.clone()
+    }
+    pub fn syzygy_probe_depth(&self) -> usize{
+        return self.spin_options.iter().find(|a| a.name == "SyzygyProbeDepth").unwrap().value
+    }
+    pub fn syzygy_50_move_rule(&self) -> bool{
+        return self.check_options.iter().find(|a| a.name == "Syzygy50MoveRule").unwrap().value
+    }
+}
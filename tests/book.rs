@@ -0,0 +1,133 @@
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use common::run;
+
+// keys from the polyglot specification
+const START: u64 = 0x463b96181691fc9c;
+const AFTER_E4: u64 = 0x823c9b50fd114196;
+const AFTER_F5: u64 = 0x22a48b5a8e47ff78;
+const AFTER_KE2: u64 = 0x652a607ca3f242c1;
+const AFTER_B4: u64 = 0x3c8123ea7b067637;
+
+fn temp_path(name: &str) -> std::path::PathBuf {
+    return std::env::temp_dir().join(format!("vhagar-book-{}-{}", std::process::id(), name));
+}
+// squares are numbered from a1, the move is to | from << 6 | promotion << 12
+fn write_book(name: &str, entries: &[(u64, &str, u16)]) -> String {
+    let square = |s: &[u8]| -> u16 { return (s[0] - b'a') as u16 + (s[1] - b'1') as u16 * 8 };
+    let mut entries = entries.to_vec();
+    entries.sort_by_key(|e| e.0);
+    let mut bytes = vec![];
+    for (key, mv, weight) in entries {
+        let mv = square(&mv.as_bytes()[2..4]) | square(&mv.as_bytes()[0..2]) << 6;
+        bytes.extend_from_slice(&key.to_be_bytes());
+        bytes.extend_from_slice(&mv.to_be_bytes());
+        bytes.extend_from_slice(&weight.to_be_bytes());
+        bytes.extend_from_slice(&0u32.to_be_bytes());
+    }
+    let path = temp_path(name);
+    std::fs::write(&path, bytes).unwrap();
+    return path.to_str().unwrap().to_string();
+}
+fn book_output(path: &str, commands: &[&str]) -> String {
+    let book_file = format!("setoption name BookFile value {}", path);
+    let mut all = vec!["setoption name OwnBook value true", &book_file];
+    all.extend_from_slice(commands);
+    return run(&all);
+}
+
+#[test]
+fn book_moves_are_played_without_searching() {
+    let path = write_book(
+        "moves.bin",
+        &[
+            (START, "e2e4", 10),
+            (START, "d2d4", 5),
+            (AFTER_E4, "d7d5", 1),
+            (AFTER_F5, "e5f6", 1),
+            (AFTER_KE2, "e8f7", 1),
+            (AFTER_B4, "b4c3", 1),
+        ],
+    );
+    for (position, best) in [
+        ("startpos", "e2e4"),
+        // the en passant square only counts when a pawn can take
+        ("fen rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1", "d7d5"),
+        ("fen rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3", "e5f6"),
+        ("fen rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPPKPPP/RNBQ1BNR b kq - 1 3", "e8f7"),
+        ("fen rnbqkbnr/p1pppppp/8/8/PpP4P/8/1P1PPPP1/RNBQKBNR b KQkq c3 0 3", "b4c3"),
+    ] {
+        let output = book_output(
+            &path,
+            &["setoption name BookBestMove value true", &format!("position {}", position), "go depth 3"],
+        );
+        assert!(output.contains("info string book move"), "{}", output);
+        assert!(output.contains(&format!("bestmove {}", best)), "{}", output);
+        assert!(!output.contains("info depth"), "{}", output);
+    }
+    std::fs::remove_file(&path).unwrap();
+}
+
+// the key the engine computes for a position, the reference keys above check how it does it
+fn engine_key(position: &str) -> u64 {
+    let output = run(&[position, "d"]);
+    let line = output.lines().find(|l| l.starts_with("Polyglot key:")).unwrap_or_else(|| panic!("{}", output));
+    return u64::from_str_radix(line.split_whitespace().last().unwrap(), 16).unwrap();
+}
+
+#[test]
+fn castles_are_stored_as_king_takes_rook() {
+    let position = "position startpos moves e2e4 e7e5 g1f3 b8c6 f1c4 g8f6";
+    let path = write_book("castles.bin", &[(engine_key(position), "e1h1", 1)]);
+    let output = book_output(&path, &[position, "go depth 3"]);
+    assert!(output.contains("info string book move e1g1"), "{}", output);
+    assert!(output.contains("bestmove e1g1"), "{}", output);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn infinite_and_ponder_searches_skip_the_book() {
+    let path = write_book("infinite.bin", &[(START, "e2e4", 1)]);
+    // the depth ends the searches
+    for go in ["go infinite depth 3", "go ponder depth 3"] {
+        let output = book_output(&path, &["position startpos", go]);
+        assert!(!output.contains("book move"), "{}", output);
+        assert!(output.contains("bestmove"), "{}", output);
+    }
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn weighted_moves_and_book_depth() {
+    let path = write_book("weighted.bin", &[(START, "e2e4", 10), (START, "d2d4", 5), (START, "g1f3", 0)]);
+    let output = book_output(&path, &["position startpos", "go depth 3", "go depth 3", "go depth 3", "go depth 3"]);
+    let moves: Vec<&str> = output.lines().filter(|l| l.starts_with("bestmove")).collect();
+    assert_eq!(moves.len(), 4, "{}", output);
+    // a move without weight is never picked
+    assert!(moves.iter().all(|m| *m == "bestmove e2e4" || *m == "bestmove d2d4"), "{}", output);
+    // the book is not used past its depth or without OwnBook
+    let output = book_output(
+        &path,
+        &[
+            "setoption name BookDepth value 2",
+            "position fen rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 3",
+            "go depth 1",
+        ],
+    );
+    assert!(!output.contains("book move"), "{}", output);
+    let output = book_output(&path, &["setoption name OwnBook value false", "position startpos", "go depth 1"]);
+    assert!(!output.contains("book move"), "{}", output);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn broken_books_are_rejected() {
+    let path = temp_path("broken.bin");
+    std::fs::write(&path, [0u8; 20]).unwrap();
+    let output = book_output(path.to_str().unwrap(), &["position startpos", "go depth 1"]);
+    assert!(output.contains("could not load book"), "{}", output);
+    assert!(output.contains("bestmove"), "{}", output);
+    std::fs::remove_file(&path).unwrap();
+}
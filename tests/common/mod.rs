@@ -0,0 +1,23 @@
+#![allow(clippy::needless_return)]
+
+use std::{
+    io::Write,
+    process::{Command, Stdio},
+};
+
+// feeds uci commands to the engine binary and returns everything it printed
+pub fn run(commands: &[&str]) -> String {
+    let mut engine = Command::new(env!("CARGO_BIN_EXE_vhagar"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("failed to start the engine");
+    let mut stdin = engine.stdin.take().unwrap();
+    for command in commands {
+        writeln!(stdin, "{}", command).unwrap();
+    }
+    writeln!(stdin, "quit").unwrap();
+    drop(stdin);
+    let output = engine.wait_with_output().unwrap();
+    return String::from_utf8(output.stdout).unwrap();
+}
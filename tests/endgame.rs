@@ -0,0 +1,120 @@
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use common::run;
+
+// the classical and the final evaluation of each position from white's point of view, from one
+// engine so the kpk bitbase is built once
+fn evaluations_of(fens: &[&str]) -> Vec<(i32, i32)> {
+    let commands: Vec<String> = fens
+        .iter()
+        .flat_map(|fen| [format!("position fen {}", fen), "eval".to_string()])
+        .collect();
+    let output = run(&commands.iter().map(|c| c.as_str()).collect::<Vec<&str>>());
+    let values = |label: &str| -> Vec<i32> {
+        return output
+            .lines()
+            .filter(|l| l.starts_with(label))
+            .map(|l| l.split_whitespace().nth(2).unwrap().parse().unwrap())
+            .collect();
+    };
+    let (classical, scaled) = (values("Classical evaluation:"), values("Final evaluation:"));
+    assert!(classical.len() == fens.len() && scaled.len() == fens.len(), "{}", output);
+    return classical.into_iter().zip(scaled).collect();
+}
+fn evaluations(fen: &str) -> (i32, i32) {
+    return evaluations_of(&[fen])[0];
+}
+fn final_eval(fen: &str) -> i32 {
+    return evaluations(fen).1;
+}
+
+#[test]
+fn known_wins() {
+    let fens = [
+        // KBNK, KQK and a pawn outside the square of the king
+        "8/8/8/4k3/8/8/2B5/4K1N1 w - - 0 1",
+        "8/8/4k3/8/8/8/3K4/Q7 w - - 0 1",
+        "8/8/8/3K4/4P3/8/8/4k3 b - - 0 1",
+        "7k/8/8/8/8/8/P7/K7 w - - 0 1",
+    ];
+    for (fen, (_, eval)) in fens.iter().zip(evaluations_of(&fens)) {
+        assert!(eval > 10000, "{}", fen);
+    }
+    // the same for black
+    assert!(final_eval("k1b1n3/8/8/8/3K4/8/8/8 b - - 0 1") < -10000);
+    // a knight pawn on the seventh does not hold against the queen
+    assert!(final_eval("8/8/8/8/8/5K2/kp6/4Q3 w - - 0 1") > 800);
+    // a rook with the king in front of the pawn
+    assert!(final_eval("8/8/8/8/8/2k5/2p5/2K4R w - - 0 1") > 450);
+}
+
+#[test]
+fn known_draws() {
+    let fens = [
+        // the king in front of the pawn, a rook pawn with the king in the corner and a bishop
+        // of the wrong color
+        "8/8/8/8/8/4k3/4P3/4K3 w - - 0 1",
+        "k7/8/8/8/8/8/P7/K7 w - - 0 1",
+        "k7/8/8/8/8/8/P7/K1B5 w - - 0 1",
+        // insufficient material
+        "8/8/4k3/8/8/8/3KN3/8 w - - 0 1",
+        "8/8/4k3/2n5/8/8/3KB3/8 w - - 0 1",
+        "8/8/4k3/8/8/8/2NKN3/8 w - - 0 1",
+    ];
+    for (fen, (_, eval)) in fens.iter().zip(evaluations_of(&fens)) {
+        assert!(eval.abs() < 10, "{}", fen);
+    }
+    // a bishop pawn on the seventh supported by its king
+    assert!(final_eval("8/8/8/8/8/5K2/2p5/1k2Q3 b - - 0 1") < 100);
+    // an advanced pawn supported by its king with the other king far away
+    assert!(final_eval("7K/8/8/8/8/2k5/2p5/7R w - - 0 1") < 100);
+}
+
+#[test]
+fn kbnk_drives_the_king_to_the_bishop_corner() {
+    // a light squared bishop mates in a8 or h1
+    let right = final_eval("k7/8/2K5/8/8/8/8/3B1N2 w - - 0 1");
+    let wrong = final_eval("7k/8/5K2/8/8/8/8/3B1N2 w - - 0 1");
+    assert!(right > wrong, "{} {}", right, wrong);
+}
+
+#[test]
+fn drawish_endgames_are_scaled() {
+    // opposite colored bishops
+    let (classical, scaled) = evaluations("8/5b2/8/3k1p2/2p5/2P1B3/3K4/8 w - - 0 1");
+    assert_eq!(scaled, classical * 16 / 64);
+    // a rook against a bishop without pawns
+    let (classical, scaled) = evaluations("8/8/4k3/8/3b4/8/3K4/R7 w - - 0 1");
+    assert_eq!(scaled, classical * 14 / 64);
+}
+
+#[test]
+fn kpk_bitbase_matches_brute_force() {
+    let output = run(&["test kpk"]);
+    assert!(output.contains("kpk test completed successfully"), "{}", output);
+}
+
+#[test]
+fn kpk_opposition() {
+    // the side to move decides it with the kings in opposition and the pawn behind
+    let evals = evaluations_of(&[
+        "8/4k3/8/4K3/4P3/8/8/8 w - - 0 1",
+        "8/4k3/8/4K3/4P3/8/8/8 b - - 0 1",
+        "8/8/8/4p3/4k3/8/4K3/8 w - - 0 1",
+    ]);
+    assert!(evals[0].1.abs() < 10);
+    assert!(evals[1].1 > 10000);
+    assert!(evals[2].1 < -10000);
+    // the search scores the known win from the bitbase
+    let output = run(&["position fen 8/8/3k4/8/8/3K4/3P4/8 w - - 0 1", "go depth 6"]);
+    let last = output.lines().rfind(|l| l.starts_with("info depth")).unwrap_or_else(|| panic!("{}", output));
+    let score: i32 = last
+        .split_whitespace()
+        .skip_while(|&t| t != "cp")
+        .nth(1)
+        .and_then(|t| t.parse().ok())
+        .unwrap_or_else(|| panic!("{}", output));
+    assert!(score > 10000, "{}", output);
+}
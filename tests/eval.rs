@@ -0,0 +1,90 @@
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use common::run;
+
+#[test]
+fn traces_every_term() {
+    let output = run(&["position startpos moves e2e4", "eval"]);
+    for term in [
+        "Material",
+        "Mobility",
+        "Mop up",
+        "PST",
+        "Pawns",
+        "Bishop pair",
+        "Rooks",
+        "Queens",
+        "Seventh rank",
+        "Tempo",
+        "King safety",
+        "Threats",
+    ] {
+        assert!(output.contains(term), "{}", output);
+    }
+    assert!(output.contains("Phase: 24 of 24"), "{}", output);
+}
+
+#[test]
+fn side_to_move_only_flips_the_tempo() {
+    let eval = |fen: &str| -> i32 {
+        let output = run(&[&format!("position fen {}", fen), "eval"]);
+        let line = output
+            .lines()
+            .find(|l| l.starts_with("Classical evaluation:"))
+            .unwrap_or_else(|| panic!("{}", output));
+        return line.split_whitespace().nth(2).unwrap().parse().unwrap();
+    };
+    // only the side to move differs, the tempo bonus is the only asymmetric term
+    let white = eval("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+    let black = eval("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR b KQkq - 0 1");
+    assert_eq!(white, -black);
+}
+
+#[test]
+fn threats_are_scored() {
+    // the middlegame threats of white and black
+    let threats = |fen: &str| -> (i32, i32) {
+        let output = run(&[&format!("position fen {}", fen), "eval"]);
+        let line = output
+            .lines()
+            .find(|l| l.trim_start().starts_with("Threats"))
+            .unwrap_or_else(|| panic!("{}", output));
+        let values: Vec<i32> = line.split_whitespace().filter_map(|v| v.parse().ok()).collect();
+        return (values[0], values[2]);
+    };
+    // a knight attacked by a rook, once undefended and once defended by a pawn
+    let hanging = threats("4k3/8/8/3n4/8/8/8/3RK3 w - - 0 1").0;
+    let defended = threats("4k3/8/4p3/3n4/8/8/8/3RK3 w - - 0 1").0;
+    assert!(hanging > 0 && defended == 0, "{} {}", hanging, defended);
+    // the same knight attacked by a pawn
+    assert!(threats("4k3/8/4p3/3n4/4P3/8/8/4K3 w - - 0 1").0 > 0);
+    // a rook pinning a knight to the king
+    let pinned = threats("3bk3/4n3/8/8/8/8/8/4R1K1 w - - 0 1").0;
+    let unpinned = threats("3b3k/4n3/8/8/8/8/8/4R1K1 w - - 0 1").0;
+    assert!(pinned > unpinned, "{} {}", pinned, unpinned);
+    // a queen that can check from a square nothing defends
+    assert!(threats("3qk3/8/8/8/8/8/8/R3K3 w - - 0 1").1 > 0);
+}
+
+#[test]
+fn evaluation_is_symmetric() {
+    let output = run(&["test symmetry"]);
+    assert!(output.contains("symmetry test completed successfully"), "{}", output);
+}
+
+#[test]
+fn symmetry_reads_epd_and_tuning_lines() {
+    let path = std::env::temp_dir().join(format!("vhagar-symmetry-{}.epd", std::process::id()));
+    std::fs::write(
+        &path,
+        "# a perft line and a tuning line\n\
+         r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - ;D1 48\n\
+         2kr3r/ppq2p2/2n1p1p1/2bpP1Pp/5P2/2P1BN2/PP1Q3P/R4RK1 b - - 1 17 [0.5]\n",
+    )
+    .unwrap();
+    let output = run(&[&format!("test symmetry {}", path.display())]);
+    std::fs::remove_file(&path).unwrap();
+    assert!(output.contains("symmetry test completed successfully, 2 positions"), "{}", output);
+}
@@ -0,0 +1,52 @@
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use common::run;
+
+// a 768x256 network in the engine's file layout filled with small pseudo random weights
+fn write_network(name: &str, values: usize) -> std::path::PathBuf {
+    let mut seed: u64 = 0x2545F4914F6CDD1D;
+    let mut bytes = Vec::with_capacity(values * 2);
+    for _ in 0..values {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        bytes.extend_from_slice(&((seed % 64) as i16 - 32).to_le_bytes());
+    }
+    let path = std::env::temp_dir().join(format!("vhagar-{}-{}.bin", name, std::process::id()));
+    std::fs::write(&path, bytes).unwrap();
+    return path;
+}
+const NETWORK_VALUES: usize = 768 * 256 + 256 + 2 * 256 + 1;
+
+#[test]
+fn searches_with_a_loaded_network() {
+    let path = write_network("net", NETWORK_VALUES);
+    let output = run(&[
+        &format!("setoption name EvalFile value {}", path.display()),
+        "setoption name Use NNUE value true",
+        "position startpos moves e2e4 e7e5",
+        "go depth 3",
+    ]);
+    std::fs::remove_file(&path).unwrap();
+
+    assert!(output.contains(&format!("info string loaded network {}", path.display())), "{}", output);
+    assert!(output.contains("bestmove "), "{}", output);
+}
+
+#[test]
+fn rejects_a_network_of_the_wrong_size() {
+    let path = write_network("short", NETWORK_VALUES / 2);
+    let output = run(&[&format!("setoption name EvalFile value {}", path.display())]);
+    std::fs::remove_file(&path).unwrap();
+
+    assert!(output.contains("could not load network"), "{}", output);
+}
+
+#[test]
+fn falls_back_to_the_classical_eval_without_a_network() {
+    let output = run(&["setoption name Use NNUE value true", "go depth 2"]);
+    assert!(output.contains("no network loaded"), "{}", output);
+    assert!(output.contains("bestmove "), "{}", output);
+}
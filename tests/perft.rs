@@ -0,0 +1,187 @@
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use common::run;
+
+#[test]
+fn builtin_perft_cases() {
+    let output = run(&["perft test 4"]);
+    assert!(output.contains("perft test completed successfully"), "{}", output);
+}
+
+// the full built-in suite takes minutes even in release, run it with --release --ignored
+#[test]
+#[ignore]
+fn builtin_perft_cases_full() {
+    let output = run(&["perft test"]);
+    assert!(output.contains("perft test completed successfully"), "{}", output);
+}
+
+#[test]
+fn epd_suite_reports_first_failing_depth() {
+    let path = std::env::temp_dir().join(format!("vhagar-suite-{}.epd", std::process::id()));
+    std::fs::write(
+        &path,
+        "# start position and kiwipete, the second has a wrong depth 2 count\n\
+         rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - ;D1 20 ;D2 400 ;D3 8902\n\
+         r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - ;D1 48 ;D2 2040 ;D3 97862\n",
+    )
+    .unwrap();
+    let output = run(&[&format!("perft suite {} 3", path.display())]);
+    std::fs::remove_file(&path).unwrap();
+
+    assert!(output.contains("perft test case 2 failed at depth 2: expected 2040 found 2039"), "{}", output);
+    assert!(output.contains("divide at depth 2:"), "{}", output);
+    assert!(output.contains("e1g1: 43"), "{}", output);
+    assert!(output.contains("1 of 2 cases passed"), "{}", output);
+}
+
+#[test]
+fn epd_suite_respects_max_depth() {
+    let path = std::env::temp_dir().join(format!("vhagar-depth-{}.epd", std::process::id()));
+    std::fs::write(&path, "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1 ;D1 14 ;D2 191 ;D7 178633661\n").unwrap();
+    let output = run(&[&format!("perft suite {} 2", path.display())]);
+    std::fs::remove_file(&path).unwrap();
+
+    assert!(output.contains("1 of 1 cases passed"), "{}", output);
+    assert!(output.contains("1 depths skipped"), "{}", output);
+}
+
+// every standard chess960 perft position, castling with the rooks and kings on all their files
+fn chess960_suite(depth: usize) -> String {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/chess960.epd");
+    return run(&[&format!("perft suite {} {}", path, depth)]);
+}
+
+#[test]
+fn chess960_perft_suite() {
+    let output = chess960_suite(3);
+    assert!(output.contains("960 of 960 cases passed"), "{}", output);
+}
+
+// depth 5 takes about three minutes in release, run it with --release --ignored
+#[test]
+#[ignore]
+fn chess960_perft_suite_deep() {
+    let output = chess960_suite(5);
+    assert!(output.contains("960 of 960 cases passed"), "{}", output);
+}
+
+#[test]
+fn self_tests() {
+    let output = run(&["test fen", "test moves", "test san", "test movegen", "test legality", "test unmake", "test nnue"]);
+    for name in ["fen", "move parsing", "san", "move generator", "legality", "unmake", "nnue"] {
+        assert!(output.contains(&format!("{} test completed successfully", name)), "{}", output);
+    }
+}
+
+// the divide lines of a perft run, in the "e2e4: 20" format the diff command reads
+fn divide_lines(position: &str, depth: usize) -> Vec<String> {
+    return run(&[position, &format!("go perft {}", depth)])
+        .lines()
+        .filter(|line| match line.split_once(": ") {
+            Some((m, n)) => (4..=5).contains(&m.len()) && n.parse::<usize>().is_ok(),
+            None => false,
+        })
+        .map(|line| line.to_string())
+        .collect();
+}
+
+#[test]
+fn divide_diff_descends_into_the_first_mismatch() {
+    let root: Vec<String> = divide_lines("position startpos", 2)
+        .into_iter()
+        .map(|line| if line == "a2a3: 20" { "a2a3: 21".to_string() } else { line })
+        .collect();
+    let mut child = divide_lines("position startpos moves a2a3", 1);
+    child.push("a7a4: 1".to_string());
+
+    let mut commands = vec!["position startpos".to_string(), "perft diff 2".to_string()];
+    commands.extend(root);
+    commands.push(String::new());
+    commands.extend(child);
+    commands.push(String::new());
+    let commands: Vec<&str> = commands.iter().map(|c| c.as_str()).collect();
+    let output = run(&commands);
+
+    assert!(output.contains("a2a3 differs at depth 2: expected 21 found 20"), "{}", output);
+    assert!(output.contains("position fen rnbqkbnr/pppppppp/8/8/8/P7/1PPPPPPP/RNBQKBNR b KQkq - 0 1"), "{}", output);
+    assert!(output.contains("missing moves: a7a4\n"), "{}", output);
+    assert!(output.contains("extra moves: \n"), "{}", output);
+}
+
+#[test]
+fn divide_diff_reads_a_reference_file() {
+    let position = "position fen r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+    let mut reference: Vec<String> =
+        divide_lines(position, 2).into_iter().filter(|line| !line.starts_with("e1g1")).collect();
+    reference.push("Nodes searched: 2039".to_string());
+    let path = std::env::temp_dir().join(format!("vhagar-divide-{}.txt", std::process::id()));
+    std::fs::write(&path, reference.join("\n")).unwrap();
+    let output = run(&[position, &format!("perft diff 2 {}", path.display())]);
+    std::fs::remove_file(&path).unwrap();
+
+    assert!(output.contains("missing moves: \n"), "{}", output);
+    assert!(output.contains("extra moves: e1g1\n"), "{}", output);
+}
+
+#[test]
+fn divide_diff_descends_through_a_reference_file() {
+    // the counts only differ below a2a3 a7a6, where the reference has a move we don't generate
+    let bump = |lines: Vec<String>, m: &str| -> Vec<String> {
+        return lines
+            .into_iter()
+            .map(|line| match line.split_once(": ") {
+                Some((mv, n)) if mv == m => format!("{}: {}", mv, n.parse::<usize>().unwrap() + 1),
+                _ => line,
+            })
+            .collect();
+    };
+    let mut reference = bump(divide_lines("position startpos", 3), "a2a3");
+    reference.push("position fen rnbqkbnr/pppppppp/8/8/8/P7/1PPPPPPP/RNBQKBNR b KQkq - 0 1".to_string());
+    reference.extend(bump(divide_lines("position startpos moves a2a3", 2), "a7a6"));
+    reference.push("position fen rnbqkbnr/1ppppppp/p7/8/8/P7/1PPPPPPP/RNBQKBNR w KQkq - 0 2".to_string());
+    reference.extend(divide_lines("position startpos moves a2a3 a7a6", 1));
+    reference.push("b1b4: 1".to_string());
+    let path = std::env::temp_dir().join(format!("vhagar-divide-deep-{}.txt", std::process::id()));
+    std::fs::write(&path, reference.join("\n")).unwrap();
+    let output = run(&["position startpos", &format!("perft diff 3 {}", path.display())]);
+    std::fs::remove_file(&path).unwrap();
+
+    assert!(output.contains("a2a3 differs at depth 3: expected 381 found 380"), "{}", output);
+    assert!(output.contains("a7a6 differs at depth 2: expected 20 found 19"), "{}", output);
+    assert!(output.contains("position fen rnbqkbnr/1ppppppp/p7/8/8/P7/1PPPPPPP/RNBQKBNR w KQkq - 0 2"), "{}", output);
+    assert!(output.contains("moves from the root: a2a3 a7a6\n"), "{}", output);
+    assert!(output.contains("missing moves: b1b4\n"), "{}", output);
+}
+
+#[test]
+fn divide_diff_stops_where_the_reference_file_ends() {
+    let position = "position fen r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+    let reference: Vec<String> = divide_lines(position, 2)
+        .into_iter()
+        .map(|line| if line == "e1g1: 43" { "e1g1: 44".to_string() } else { line })
+        .collect();
+    let path = std::env::temp_dir().join(format!("vhagar-divide-root-{}.txt", std::process::id()));
+    std::fs::write(&path, reference.join("\n")).unwrap();
+    let output = run(&[position, &format!("perft diff 2 {}", path.display())]);
+    std::fs::remove_file(&path).unwrap();
+
+    assert!(output.contains("has no divide of r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R4RK1 b kq"), "{}", output);
+    assert!(
+        output.contains("smallest mismatch: position fen r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1 move e1g1"),
+        "{}",
+        output
+    );
+}
+
+#[test]
+fn divide_diff_against_a_reference_engine() {
+    let engine = env!("CARGO_BIN_EXE_vhagar");
+    let output = run(&[
+        "position fen r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        &format!("perft diff 3 engine {}", engine),
+    ]);
+    assert!(output.contains("divide matches the reference at depth 3"), "{}", output);
+}
@@ -0,0 +1,150 @@
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use std::sync::OnceLock;
+
+use common::run;
+
+// the engine writes the 3 man tables and checks them against its own probing, once for all the
+// tests here
+fn tables() -> &'static str {
+    static DIR: OnceLock<String> = OnceLock::new();
+    return DIR.get_or_init(|| {
+        let dir = std::env::temp_dir().join(format!("vhagar-syzygy-{}", std::process::id()));
+        let dir = dir.to_str().unwrap().to_string();
+        let output = run(&[&format!("test syzygy {}", dir)]);
+        assert!(output.contains("syzygy test completed successfully"), "{}", output);
+        return dir;
+    });
+}
+fn with_tables(commands: &[&str]) -> String {
+    let path = format!("setoption name SyzygyPath value {}", tables());
+    let mut all = vec![path.as_str()];
+    all.extend_from_slice(commands);
+    return run(&all);
+}
+fn tablebase_line(position: &str) -> String {
+    let output = with_tables(&[position, "d"]);
+    let line = output.lines().find(|l| l.starts_with("Tablebases:")).unwrap_or_else(|| panic!("{}", output));
+    return line.to_string();
+}
+// the score and tbhits of the last info line
+fn last_info(output: &str) -> (i32, u64) {
+    let line = output.lines().rfind(|l| l.starts_with("info depth")).unwrap_or_else(|| panic!("{}", output));
+    let value = |name: &str| {
+        let parts = line.split_whitespace().collect::<Vec<&str>>();
+        let i = parts.iter().position(|p| *p == name).unwrap_or_else(|| panic!("{}", line));
+        return parts[i + 1].to_string();
+    };
+    return (value("cp").parse().unwrap(), value("tbhits").parse().unwrap());
+}
+fn best_move(output: &str) -> &str {
+    let line = output.lines().find(|l| l.starts_with("bestmove")).unwrap_or_else(|| panic!("{}", output));
+    return line.split_whitespace().nth(1).unwrap();
+}
+
+#[test]
+fn syzygy_options_are_listed() {
+    let output = run(&["uci"]);
+    assert!(output.contains("option name SyzygyPath type string default <empty>"), "{}", output);
+    assert!(output.contains("option name SyzygyProbeDepth type spin default 1 min 1 max 100"), "{}", output);
+    assert!(output.contains("option name Syzygy50MoveRule type check default true"), "{}", output);
+}
+
+#[test]
+fn tables_are_found_and_cleared() {
+    let output = with_tables(&[]);
+    assert!(output.contains("info string found 5 tablebases"), "{}", output);
+    let output = with_tables(&["setoption name SyzygyPath value <empty>", "position fen 8/8/8/4k3/8/8/3QK3/8 w - - 0 1", "d"]);
+    assert!(!output.contains("Tablebases:"), "{}", output);
+    let output = run(&["setoption name SyzygyPath value /nonexistent/vhagar-syzygy"]);
+    assert!(output.contains("info string could not load tablebases"), "{}", output);
+}
+
+#[test]
+fn probed_results() {
+    for (position, result) in [
+        ("position fen 8/8/8/4k3/8/8/3QK3/8 w - - 0 1", "win"),
+        ("position fen 8/8/8/4k3/8/8/3QK3/8 b - - 0 1", "loss"),
+        // the king takes the rook
+        ("position fen 8/8/8/8/8/8/2k5/3R3K b - - 0 1", "draw"),
+        ("position fen 8/8/8/4k3/8/8/3NK3/8 w - - 0 1", "draw"),
+        ("position fen 4k3/8/4K3/4P3/8/8/8/8 w - - 0 1", "win"),
+        ("position fen 8/8/8/8/4p3/4k3/8/4K3 b - - 0 1", "win"),
+        ("position fen 8/8/8/8/8/4k3/4P3/4K3 w - - 0 1", "draw"),
+    ] {
+        let line = tablebase_line(position);
+        assert!(line.starts_with(&format!("Tablebases: {} dtz", result)), "{}: {}", position, line);
+    }
+    // the dtz counts plies to the next capture or pawn move and has the sign of the result
+    assert_eq!(tablebase_line("position fen 8/8/8/8/8/8/2k5/3R3K b - - 0 1"), "Tablebases: draw dtz 0");
+    let line = tablebase_line("position fen 8/8/8/4k3/8/8/3QK3/8 b - - 0 1");
+    assert!(line.split_whitespace().last().unwrap().parse::<i32>().unwrap() < 0, "{}", line);
+}
+
+#[test]
+fn captures_into_the_tables_are_probed() {
+    // taking the knight leaves a won king and queen against king
+    let output = with_tables(&["position fen 8/8/8/3k4/8/3n4/8/3QK3 w - - 0 1", "go depth 3"]);
+    let (score, tb_hits) = last_info(&output);
+    assert!(tb_hits > 0, "{}", output);
+    assert!(score > 20000, "{}", output);
+    assert_eq!(best_move(&output), "d1d3", "{}", output);
+}
+
+#[test]
+fn root_moves_keep_the_win() {
+    // the rook is attacked, every other move drops it
+    let position = "position fen 8/8/8/8/8/8/2k5/3R3K w - - 0 1";
+    assert!(tablebase_line(position).starts_with("Tablebases: win"));
+    let output = with_tables(&[position, "go depth 1"]);
+    let (score, _) = last_info(&output);
+    assert_eq!(score, 25000, "{}", output);
+    assert!(best_move(&output).starts_with("d1"), "{}", output);
+}
+
+#[test]
+fn fifty_move_rule_turns_late_wins_into_draws() {
+    let position = "position fen 8/8/8/4k3/8/8/3QK3/8 w - - 90 1";
+    let (score, _) = last_info(&with_tables(&[position, "go depth 2"]));
+    assert!(score > 0 && score < 100, "{}", score);
+    let (score, _) = last_info(&with_tables(&["setoption name Syzygy50MoveRule value false", position, "go depth 2"]));
+    assert_eq!(score, 25000);
+}
+
+// the official KQvK and KRvK files are not generated here and can not be downloaded by the tests.
+// copy KQvK.rtbw, KQvK.rtbz, KRvK.rtbw and KRvK.rtbz into tests/data/syzygy and run it with
+// --ignored. the values are the exact distances from the retrograde analysis of test syzygy
+#[test]
+#[ignore]
+fn official_tables() {
+    let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/syzygy");
+    for name in ["KQvK.rtbw", "KQvK.rtbz", "KRvK.rtbw", "KRvK.rtbz"] {
+        assert!(std::path::Path::new(dir).join(name).is_file(), "{} is missing from {}", name, dir);
+    }
+    let path = format!("setoption name SyzygyPath value {}", dir);
+    for (fen, expected) in [
+        ("8/8/8/4k3/8/8/3QK3/8 w - - 0 1", "win dtz 13"),
+        ("8/8/8/4k3/8/8/3QK3/8 b - - 0 1", "loss dtz -14"),
+        // mate in one
+        ("k7/8/1K6/8/8/8/8/7R w - - 0 1", "win dtz 1"),
+        ("8/8/3k4/8/8/3K4/8/R7 w - - 0 1", "win dtz 23"),
+        ("8/8/3k4/8/8/3K4/8/R7 b - - 0 1", "loss dtz -24"),
+        ("8/8/8/8/8/8/2k5/3R3K w - - 0 1", "win dtz 27"),
+        // the king takes the rook
+        ("8/8/8/8/8/8/2k5/3R3K b - - 0 1", "draw dtz 0"),
+    ] {
+        let output = run(&[&path, &format!("position fen {}", fen), "d"]);
+        assert!(output.contains(&format!("Tablebases: {}", expected)), "{}: {}", fen, output);
+    }
+}
+
+// four pieces take minutes to generate in debug, run it with --release --ignored
+#[test]
+#[ignore]
+fn four_man_tables() {
+    let dir = std::env::temp_dir().join(format!("vhagar-syzygy-4-{}", std::process::id()));
+    let output = run(&[&format!("test syzygy {} KNvK KNNvK", dir.to_str().unwrap())]);
+    assert!(output.contains("syzygy test completed successfully"), "{}", output);
+}
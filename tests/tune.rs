@@ -0,0 +1,57 @@
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use common::run;
+
+const DATASET: &str = "\
+rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1 [0.5]
+r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10 [1.0]
+4k3/8/8/8/8/8/4P3/4K3 w - - c9 \"1-0\";
+4k3/4p3/8/8/8/8/8/4K3 w - - 0 1 [0.0]
+r1bqkb1r/pppp1ppp/2n2n2/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 4 4 [0.5]
+";
+
+fn temp_path(name: &str) -> std::path::PathBuf {
+    return std::env::temp_dir().join(format!("vhagar-tune-{}-{}", std::process::id(), name));
+}
+
+#[test]
+fn untuned_params_are_written_in_the_layout_of_defs() {
+    let data = temp_path("data.epd");
+    let output = temp_path("params.rs");
+    std::fs::write(&data, DATASET).unwrap();
+    let log = run(&[&format!(
+        "tune {} epochs 0 threads 2 output {}",
+        data.display(),
+        output.display()
+    )]);
+    let written = std::fs::read_to_string(&output).unwrap_or_else(|_| panic!("{}", log));
+    std::fs::remove_file(&data).unwrap();
+    std::fs::remove_file(&output).unwrap();
+
+    // the two king and pawn endings are known endgames the classical eval never sees
+    assert!(log.contains("3 of 5 positions are quiet"), "{}", log);
+    assert_eq!(written, include_str!("../src/evaluation/defs.rs"));
+}
+
+#[test]
+fn tuning_lowers_the_error_and_writes_json() {
+    let data = temp_path("data2.epd");
+    let output = temp_path("params.json");
+    std::fs::write(&data, DATASET).unwrap();
+    let log = run(&[&format!("tune {} epochs 100 output {}", data.display(), output.display())]);
+    let written = std::fs::read_to_string(&output).unwrap_or_else(|_| panic!("{}", log));
+    std::fs::remove_file(&data).unwrap();
+    std::fs::remove_file(&output).unwrap();
+
+    let errors: Vec<f64> = log
+        .lines()
+        .filter_map(|l| l.split("error ").nth(1))
+        .map(|e| e.trim().parse().unwrap())
+        .collect();
+    assert_eq!(errors.len(), 2, "{}", log);
+    assert!(errors[1] < errors[0], "{}", log);
+    assert!(written.starts_with("{\n  \"piece_values\": ["), "{}", written);
+    assert!(written.contains("\"king_attack_weights\": ["), "{}", written);
+}